@@ -0,0 +1,56 @@
+use qcms::{DataType, Intent, Profile, Transform};
+
+use super::{parallel::for_each_band, PixelFormat};
+
+/// Convert pixels described by an ICC profile to sRGB, in place
+/// Grayscale pixels are expanded to RGB, as qcms can't output grayscale
+/// Pixels are left untouched if the profile is invalid or doesn't match the pixels' format
+pub fn convert_to_srgb(
+    pixels: &mut Vec<u8>,
+    format: &mut PixelFormat,
+    icc_profile: &[u8],
+    threads: usize,
+) {
+    let Some(profile) = Profile::new_from_slice(icc_profile, false) else {
+        return;
+    };
+
+    let srgb = Profile::new_sRGB();
+
+    let data_type = match format {
+        PixelFormat::Gray8 => {
+            let Some(transform) = Transform::new_to(
+                &profile,
+                &srgb,
+                DataType::Gray8,
+                DataType::RGB8,
+                Intent::default(),
+            ) else {
+                return;
+            };
+
+            let gray = &pixels[..];
+            let mut rgb = vec![0; gray.len() * 3];
+
+            // Each band starts at the pixel with the provided index
+            for_each_band(&mut rgb, 3, threads, |first, band| {
+                transform.convert(&gray[first..first + band.len() / 3], band)
+            });
+
+            *pixels = rgb;
+            *format = PixelFormat::Rgb8;
+            return;
+        }
+        PixelFormat::Rgb8 => DataType::RGB8,
+        PixelFormat::Rgba8 => DataType::RGBA8,
+    };
+
+    let Some(transform) = Transform::new(&profile, &srgb, data_type, Intent::default()) else {
+        return;
+    };
+
+    // Pixels are converted independently, so they can be split in bands of any size
+    for_each_band(pixels, format.channels(), threads, |_, band| {
+        transform.apply(band)
+    });
+}
@@ -0,0 +1,194 @@
+/// EXIF tag containing the orientation of the image
+const ORIENTATION_TAG: u16 = 0x0112;
+
+/// EXIF tags containing the offset and length of the embedded thumbnail
+const THUMBNAIL_OFFSET_TAG: u16 = 0x0201;
+const THUMBNAIL_LENGTH_TAG: u16 = 0x0202;
+
+/// Orientation of an image, as described by the EXIF specification
+/// Indicates how the stored pixels must be transformed to be displayed properly
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Normal,
+    FlipHorizontal,
+    Rotate180,
+    FlipVertical,
+    Transpose,
+    Rotate90,
+    Transverse,
+    Rotate270,
+}
+
+impl Orientation {
+    fn from_exif_value(value: u16) -> Option<Self> {
+        match value {
+            1 => Some(Self::Normal),
+            2 => Some(Self::FlipHorizontal),
+            3 => Some(Self::Rotate180),
+            4 => Some(Self::FlipVertical),
+            5 => Some(Self::Transpose),
+            6 => Some(Self::Rotate90),
+            7 => Some(Self::Transverse),
+            8 => Some(Self::Rotate270),
+            _ => None,
+        }
+    }
+
+    /// Check if this orientation swaps the width and height of the image
+    pub fn swaps_dimensions(self) -> bool {
+        matches!(
+            self,
+            Self::Transpose | Self::Rotate90 | Self::Transverse | Self::Rotate270
+        )
+    }
+}
+
+/// Minimal reader for the IFDs (Image File Directories) of raw EXIF data
+struct ExifReader<'a> {
+    exif: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> ExifReader<'a> {
+    /// Create a reader from raw EXIF data (starting at the TIFF header)
+    fn new(exif: &'a [u8]) -> Option<Self> {
+        let big_endian = match exif.get(0..2)? {
+            b"II" => false,
+            b"MM" => true,
+            _ => return None,
+        };
+
+        let reader = Self { exif, big_endian };
+
+        if reader.read_u16(2)? != 42 {
+            return None;
+        }
+
+        Some(reader)
+    }
+
+    fn read_u16(&self, offset: usize) -> Option<u16> {
+        let bytes = self.exif.get(offset..offset + 2)?.try_into().ok()?;
+
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn read_u32(&self, offset: usize) -> Option<u32> {
+        let bytes = self.exif.get(offset..offset + 4)?.try_into().ok()?;
+
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    /// Get the offset of an IFD from the offset of the field containing it
+    fn ifd_offset_at(&self, offset: usize) -> Option<usize> {
+        match usize::try_from(self.read_u32(offset)?).ok()? {
+            // An offset of zero indicates there is no such IFD
+            0 => None,
+            ifd_offset => Some(ifd_offset),
+        }
+    }
+
+    /// Get the offset of the first IFD
+    fn first_ifd(&self) -> Option<usize> {
+        self.ifd_offset_at(4)
+    }
+
+    /// Get the offset of the IFD following the provided one
+    fn next_ifd(&self, ifd_offset: usize) -> Option<usize> {
+        let entries = usize::from(self.read_u16(ifd_offset)?);
+        self.ifd_offset_at(ifd_offset + 2 + entries * 12)
+    }
+
+    /// Get the offset of the value of a tag in an IFD
+    /// Only values of 4 bytes or less can be read from this offset, as they are
+    /// stored directly in the entry
+    fn find_tag(&self, ifd_offset: usize, tag: u16) -> Option<usize> {
+        let entries = self.read_u16(ifd_offset)?;
+
+        (0..usize::from(entries))
+            .map(|i| ifd_offset + 2 + i * 12)
+            .find(|entry_offset| self.read_u16(*entry_offset) == Some(tag))
+            .map(|entry_offset| entry_offset + 8)
+    }
+}
+
+/// Read the orientation from raw EXIF data (starting at the TIFF header)
+/// Returns `None` if the data is invalid or doesn't contain any orientation
+pub fn read_orientation(exif: &[u8]) -> Option<Orientation> {
+    let reader = ExifReader::new(exif)?;
+
+    // The orientation is always stored in the first IFD, as a SHORT
+    let value_offset = reader.find_tag(reader.first_ifd()?, ORIENTATION_TAG)?;
+
+    Orientation::from_exif_value(reader.read_u16(value_offset)?)
+}
+
+/// Get the embedded JPEG thumbnail from raw EXIF data (starting at the TIFF header)
+/// The thumbnail's pixels are stored with the same orientation as the main image's ones
+pub fn read_thumbnail(exif: &[u8]) -> Option<&[u8]> {
+    let reader = ExifReader::new(exif)?;
+
+    // The thumbnail is described by the second IFD
+    let ifd = reader.next_ifd(reader.first_ifd()?)?;
+
+    let offset = reader.read_u32(reader.find_tag(ifd, THUMBNAIL_OFFSET_TAG)?)?;
+    let length = reader.read_u32(reader.find_tag(ifd, THUMBNAIL_LENGTH_TAG)?)?;
+
+    let start = usize::try_from(offset).ok()?;
+    let end = start.checked_add(usize::try_from(length).ok()?)?;
+
+    exif.get(start..end)
+}
+
+/// Transform a buffer of pixels to apply an orientation on it
+/// Returns the new buffer along with its width and height
+pub fn apply_orientation(
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+    channels: usize,
+    orientation: Orientation,
+) -> (Vec<u8>, usize, usize) {
+    if orientation == Orientation::Normal {
+        return (pixels, width, height);
+    }
+
+    let (out_width, out_height) = if orientation.swaps_dimensions() {
+        (height, width)
+    } else {
+        (width, height)
+    };
+
+    let mut out = vec![0; pixels.len()];
+
+    for y in 0..out_height {
+        for x in 0..out_width {
+            // Find the source pixel to display at this position
+            let (src_x, src_y) = match orientation {
+                Orientation::Normal => (x, y),
+                Orientation::FlipHorizontal => (width - 1 - x, y),
+                Orientation::Rotate180 => (width - 1 - x, height - 1 - y),
+                Orientation::FlipVertical => (x, height - 1 - y),
+                Orientation::Transpose => (y, x),
+                Orientation::Rotate90 => (y, height - 1 - x),
+                Orientation::Transverse => (width - 1 - y, height - 1 - x),
+                Orientation::Rotate270 => (width - 1 - y, x),
+            };
+
+            let src = (src_y * width + src_x) * channels;
+            let dest = (y * out_width + x) * channels;
+
+            out[dest..dest + channels].copy_from_slice(&pixels[src..src + channels]);
+        }
+    }
+
+    (out, out_width, out_height)
+}
@@ -0,0 +1,91 @@
+use zune_png::{
+    zune_core::{bit_depth::BitDepth, colorspace::ColorSpace, options::EncoderOptions},
+    PngEncoder,
+};
+
+use super::{DecodedImage, PixelFormat};
+
+/// Encode a decoded image as a PNG file
+pub fn encode_png(image: &DecodedImage) -> Vec<u8> {
+    let colorspace = match image.format {
+        PixelFormat::Gray8 => ColorSpace::Luma,
+        PixelFormat::Rgb8 => ColorSpace::RGB,
+        PixelFormat::Rgba8 => ColorSpace::RGBA,
+    };
+
+    let options = EncoderOptions::default()
+        .set_width(image.width)
+        .set_height(image.height)
+        .set_colorspace(colorspace)
+        .set_depth(BitDepth::Eight);
+
+    PngEncoder::new(&image.pixels, options).encode()
+}
+
+/// Place two images side by side, centered vertically
+/// The space left around the shortest one is filled with the provided colour
+pub fn side_by_side(
+    left: &DecodedImage,
+    right: &DecodedImage,
+    background: [u8; 3],
+) -> DecodedImage {
+    let format = if left.format == PixelFormat::Rgba8 || right.format == PixelFormat::Rgba8 {
+        PixelFormat::Rgba8
+    } else if left.format == PixelFormat::Rgb8 || right.format == PixelFormat::Rgb8 {
+        PixelFormat::Rgb8
+    } else {
+        PixelFormat::Gray8
+    };
+
+    let channels = format.channels();
+    let width = left.width + right.width;
+    let height = left.height.max(right.height);
+
+    let [red, green, blue] = background;
+
+    let background = match format {
+        PixelFormat::Gray8 => vec![
+            ((u32::from(red) * 299 + u32::from(green) * 587 + u32::from(blue) * 114) / 1000) as u8,
+        ],
+        PixelFormat::Rgb8 => vec![red, green, blue],
+        PixelFormat::Rgba8 => vec![red, green, blue, 255],
+    };
+
+    let mut pixels = Vec::with_capacity(width * height * channels);
+
+    for y in 0..height {
+        for image in [left, right] {
+            let top = (height - image.height) / 2;
+
+            if y < top || y >= top + image.height {
+                for _ in 0..image.width {
+                    pixels.extend_from_slice(&background);
+                }
+
+                continue;
+            }
+
+            let row_len = image.width * image.format.channels();
+            let row = &image.pixels[(y - top) * row_len..][..row_len];
+
+            for pixel in row.chunks_exact(image.format.channels()) {
+                let rgba = match *pixel {
+                    [gray] => [gray, gray, gray, 255],
+                    [red, green, blue] => [red, green, blue, 255],
+                    [red, green, blue, alpha] => [red, green, blue, alpha],
+                    _ => unreachable!(),
+                };
+
+                pixels.extend_from_slice(&rgba[..channels]);
+            }
+        }
+    }
+
+    DecodedImage {
+        pixels,
+        format,
+        width,
+        height,
+        warning: None,
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::Cursor;
+
+use image::{ImageError, ImageFormat, ImageReader};
+
+use crate::errors::PageError;
+
+use super::{check_dimensions, DecodeOptions, DecodedImage, ImageDecoder, PixelFormat};
+
+/// Last-resort decoder for the formats no specialised decoder handles (GIF, ICO, etc.)
+/// It is slower than specialised decoders, which is why it must be registered last
+pub struct FallbackDecoder;
+
+impl FallbackDecoder {
+    /// Get the format of an image
+    /// TGA images don't have any signature, so they are assumed when the content doesn't match
+    /// any other format (this allows the TGA decoder to delegate the variants it doesn't handle)
+    fn format_of(bytes: &[u8]) -> ImageFormat {
+        image::guess_format(bytes).unwrap_or(ImageFormat::Tga)
+    }
+}
+
+impl ImageDecoder for FallbackDecoder {
+    fn extensions(&self) -> &'static [&'static str] {
+        &[
+            "bmp", "dds", "ff", "gif", "hdr", "ico", "pam", "pbm", "pgm", "pnm", "ppm", "qoi",
+            "tif", "tiff", "webp",
+        ]
+    }
+
+    fn content_matches(&self, bytes: &[u8]) -> bool {
+        image::guess_format(bytes).is_ok_and(|format| format.reading_enabled())
+    }
+
+    fn decode(&self, bytes: &[u8], _: DecodeOptions) -> Result<DecodedImage, PageError> {
+        let format = Self::format_of(bytes);
+
+        let reader = || {
+            let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+
+            // Dimensions are checked by us instead, with a limit on the total number of pixels
+            reader.no_limits();
+            reader
+        };
+
+        let (width, height) = reader().into_dimensions().map_err(convert_error)?;
+
+        let width = usize::try_from(width).unwrap();
+        let height = usize::try_from(height).unwrap();
+
+        check_dimensions(width, height)?;
+
+        let image = reader().decode().map_err(convert_error)?;
+
+        // Keep the pixels as compact as possible
+        let color = image.color();
+
+        let (pixels, format) = if color.has_alpha() {
+            (image.into_rgba8().into_raw(), PixelFormat::Rgba8)
+        } else if color.has_color() {
+            (image.into_rgb8().into_raw(), PixelFormat::Rgb8)
+        } else {
+            (image.into_luma8().into_raw(), PixelFormat::Gray8)
+        };
+
+        Ok(DecodedImage {
+            pixels,
+            format,
+            width,
+            height,
+            warning: None,
+        })
+    }
+
+    fn dimensions(&self, bytes: &[u8]) -> Result<(usize, usize), PageError> {
+        let (width, height) = ImageReader::with_format(Cursor::new(bytes), Self::format_of(bytes))
+            .into_dimensions()
+            .map_err(convert_error)?;
+
+        Ok((
+            usize::try_from(width).unwrap(),
+            usize::try_from(height).unwrap(),
+        ))
+    }
+}
+
+fn convert_error(err: ImageError) -> PageError {
+    match err {
+        ImageError::Unsupported(err) => PageError::UnsupportedFormat {
+            format: match err.format_hint() {
+                image::error::ImageFormatHint::Exact(format) => format!("{format:?}"),
+                _ => "Unrecognized".to_owned(),
+            },
+        },
+        ImageError::IoError(err) => PageError::Corrupted {
+            detail: format!("failed to read image: {err}"),
+        },
+        err => PageError::Corrupted {
+            detail: err.to_string(),
+        },
+    }
+}
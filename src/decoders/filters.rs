@@ -0,0 +1,48 @@
+use super::{parallel::for_each_band, DecodedImage, PixelFormat};
+
+/// Filters applied to the pages' colours when displaying them (e.g. to read at night)
+#[derive(Clone, Copy, PartialEq)]
+pub struct ColorFilters {
+    /// Invert the colours (white becoming black)
+    pub invert: bool,
+
+    /// Multiplier applied to the colours, between 0 and 1
+    pub brightness: f32,
+}
+
+impl ColorFilters {
+    /// Check if the filters leave the colours untouched
+    pub fn is_identity(self) -> bool {
+        !self.invert && self.brightness >= 1.0
+    }
+}
+
+/// Apply colour filters to a decoded image, in place
+/// The alpha channel is left untouched
+pub fn apply_filters(image: &mut DecodedImage, filters: ColorFilters, threads: usize) {
+    if filters.is_identity() {
+        return;
+    }
+
+    // Each channel is transformed independently, so the transformation can be precomputed
+    let brightness = filters.brightness.clamp(0.0, 1.0);
+
+    let table: [u8; 256] = std::array::from_fn(|value| {
+        let value = if filters.invert { 255 - value } else { value };
+        (value as f32 * brightness).round() as u8
+    });
+
+    let channels = image.format.channels();
+    let has_alpha = image.format == PixelFormat::Rgba8;
+
+    // Pixels are transformed independently, so they can be split in bands of any size
+    for_each_band(&mut image.pixels, channels, threads, |_, band| {
+        for pixel in band.chunks_exact_mut(channels) {
+            let color_channels = if has_alpha { 3 } else { channels };
+
+            for value in &mut pixel[..color_channels] {
+                *value = table[usize::from(*value)];
+            }
+        }
+    });
+}
@@ -1,61 +1,180 @@
-use std::path::Path;
+use zune_jpeg::{zune_core::options::DecoderOptions, JpegDecoder as ZuneJpegDecoder};
 
-use anyhow::{anyhow, bail, Context, Result};
-use zune_jpeg::JpegDecoder as ZuneJpegDecoder;
+use crate::errors::PageError;
 
-use super::{DecodedImage, ImageDecoder};
+use super::{
+    check_dimensions,
+    color::convert_to_srgb,
+    exif::{apply_orientation, read_orientation, read_thumbnail},
+    identify_pixels, DecodeOptions, DecodedImage, ImageDecoder,
+};
 
 pub struct JpegDecoder;
 
+/// Create a JPEG decoder with the options used for all pages
+fn new_decoder(bytes: &[u8]) -> ZuneJpegDecoder<'_> {
+    let mut decoder = ZuneJpegDecoder::new(bytes);
+
+    // Dimensions are checked by us instead, with a limit on the total number of pixels
+    decoder.set_options(
+        DecoderOptions::default()
+            .set_max_width(usize::MAX)
+            .set_max_height(usize::MAX),
+    );
+
+    decoder
+}
+
 impl ImageDecoder for JpegDecoder {
-    fn item_matches(path: &Path) -> bool
-    where
-        Self: Sized,
-    {
-        let Some(ext) = path.extension() else { return false; };
-        let lower_ext = ext.to_ascii_lowercase();
-
-        lower_ext == "jpg" || lower_ext == "jpeg"
+    fn extensions(&self) -> &'static [&'static str] {
+        &["jpg", "jpeg"]
     }
 
-    fn decode(bytes: &[u8]) -> Result<DecodedImage>
-    where
-        Self: Sized,
-    {
-        let mut decoder = ZuneJpegDecoder::new(bytes);
+    fn content_matches(&self, bytes: &[u8]) -> bool {
+        // Start Of Image marker, followed by the beginning of any other marker
+        bytes.starts_with(&[0xFF, 0xD8, 0xFF])
+    }
 
-        let pixel_bytes = decoder
-            .decode()
-            .map_err(|err| anyhow!("Failed to decode JPEG buffer: {err:?}"))?;
+    fn decode(&self, bytes: &[u8], options: DecodeOptions) -> Result<DecodedImage, PageError> {
+        let mut decoder = new_decoder(bytes);
 
         decoder
             .decode_headers()
-            .map_err(|err| anyhow!("Failed to decode JPEG headers: {err:?}"))?;
+            .map_err(|err| PageError::Corrupted {
+                detail: format!("failed to decode JPEG headers: {err:?}"),
+            })?;
 
-        let infos = decoder.info().context("Missing info headers from JPEG")?;
+        let infos = decoder.info().ok_or_else(|| PageError::Corrupted {
+            detail: "missing info headers from JPEG".to_owned(),
+        })?;
 
         let width = usize::from(infos.width);
         let height = usize::from(infos.height);
 
-        let rgb8_pixels = if pixel_bytes.len() == width * height * 3 {
-            pixel_bytes
-        } else if pixel_bytes.len() == width * height {
-            pixel_bytes
-                .into_iter()
-                .flat_map(|pixel| [pixel, pixel, pixel])
-                .collect()
-        } else {
-            bail!(
-                "Got invalid number of bytes from JPEG decoding: expected a multiple of {}, got {}",
-                width * height,
-                pixel_bytes.len(),
-            );
+        check_dimensions(width, height)?;
+
+        let pixel_bytes = decoder.decode().map_err(|err| PageError::Corrupted {
+            detail: if is_truncated(bytes) {
+                format!("file is truncated ({err:?})")
+            } else {
+                format!("failed to decode JPEG buffer: {err:?}")
+            },
+        })?;
+
+        let (mut pixels, mut format) = identify_pixels(pixel_bytes, width, height, "JPEG")?;
+
+        if options.color_manage {
+            if let Some(icc_profile) = decoder.icc_profile() {
+                convert_to_srgb(&mut pixels, &mut format, &icc_profile, options.threads);
+            }
+        }
+
+        // Pictures taken with e.g. a phone are often stored sideways, with an EXIF tag
+        // indicating how they should be displayed
+        let (pixels, width, height) = match decoder.exif().and_then(read_orientation) {
+            Some(orientation) => {
+                apply_orientation(pixels, width, height, format.channels(), orientation)
+            }
+            None => (pixels, width, height),
         };
 
+        // Truncated images are still decoded, missing rows being left blank
+        let warning = is_truncated(bytes)
+            .then(|| "This page is truncated, only its beginning could be decoded".to_owned());
+
         Ok(DecodedImage {
-            rgb8_pixels,
+            pixels,
+            format,
+            width,
+            height,
+            warning,
+        })
+    }
+
+    fn decode_preview(
+        &self,
+        bytes: &[u8],
+        options: DecodeOptions,
+    ) -> Result<Option<DecodedImage>, PageError> {
+        let mut decoder = new_decoder(bytes);
+
+        decoder
+            .decode_headers()
+            .map_err(|err| PageError::Corrupted {
+                detail: format!("failed to decode JPEG headers: {err:?}"),
+            })?;
+
+        // Many pictures embed a small thumbnail, which is way faster to decode than the full image
+        // The thumbnail doesn't have its own orientation tag, it uses the image's one
+        let thumbnail = decoder.exif().and_then(|exif| {
+            let image = self.decode(read_thumbnail(exif)?, options).ok()?;
+            Some((image, read_orientation(exif)))
+        });
+
+        let Some((thumbnail, orientation)) = thumbnail else {
+            return Ok(None);
+        };
+
+        let DecodedImage {
+            pixels,
+            format,
+            width,
+            height,
+            warning: _,
+        } = thumbnail;
+
+        let (pixels, width, height) = match orientation {
+            Some(orientation) => {
+                apply_orientation(pixels, width, height, format.channels(), orientation)
+            }
+            None => (pixels, width, height),
+        };
+
+        Ok(Some(DecodedImage {
+            pixels,
+            format,
             width,
             height,
+            warning: None,
+        }))
+    }
+
+    fn dimensions(&self, bytes: &[u8]) -> Result<(usize, usize), PageError> {
+        let mut decoder = new_decoder(bytes);
+
+        decoder
+            .decode_headers()
+            .map_err(|err| PageError::Corrupted {
+                detail: format!("failed to decode JPEG headers: {err:?}"),
+            })?;
+
+        let infos = decoder.info().ok_or_else(|| PageError::Corrupted {
+            detail: "missing info headers from JPEG".to_owned(),
+        })?;
+
+        let width = usize::from(infos.width);
+        let height = usize::from(infos.height);
+
+        let swapped = decoder
+            .exif()
+            .and_then(read_orientation)
+            .is_some_and(|orientation| orientation.swaps_dimensions());
+
+        Ok(if swapped {
+            (height, width)
+        } else {
+            (width, height)
         })
     }
 }
+
+/// Number of bytes at the end of an image in which the End Of Image marker is looked for
+/// Some encoders add padding or metadata after the marker
+const EOI_SEARCH_WINDOW: usize = 4096;
+
+/// Check if a JPEG image is truncated, by looking for its End Of Image marker
+fn is_truncated(bytes: &[u8]) -> bool {
+    let tail = &bytes[bytes.len().saturating_sub(EOI_SEARCH_WINDOW)..];
+
+    !tail.windows(2).any(|window| window == [0xFF, 0xD9])
+}
@@ -1,41 +1,267 @@
+mod color;
+mod exif;
+mod export;
+mod fallback;
+mod filters;
 mod jpeg;
+mod netpbm;
+mod parallel;
 mod png;
+mod svg;
+mod tga;
 
-use std::path::Path;
+mod registry;
+mod resize;
+mod trim;
 
-use anyhow::{bail, Result};
+pub use export::{encode_png, side_by_side};
+pub use filters::{apply_filters, ColorFilters};
+pub use registry::{fit, DecoderRegistry};
+pub use resize::downscale;
+pub use trim::{find_margins, trim, Margins};
 
-use self::{jpeg::JpegDecoder, png::PngDecoder};
+use std::{path::Path, sync::RwLock};
 
-pub trait ImageDecoder {
-    /// Check if a path can be handled by the source
-    /// e.g. is it a file with a specific extension, etc.
-    fn item_matches(path: &Path) -> bool
-    where
-        Self: Sized;
+use once_cell::sync::Lazy;
+
+use crate::errors::PageError;
+
+/// Registry of all the image decoders
+pub static DECODER_REGISTRY: Lazy<RwLock<DecoderRegistry>> =
+    Lazy::new(|| RwLock::new(DecoderRegistry::with_builtin_decoders()));
+
+pub trait ImageDecoder: Send + Sync {
+    /// File extensions of the images handled by the decoder
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Check if the content of a file looks like an image this decoder can handle
+    /// e.g. does it start with a specific signature
+    fn content_matches(&self, bytes: &[u8]) -> bool;
 
     /// Decode an image
-    fn decode(bytes: &[u8]) -> Result<DecodedImage>
-    where
-        Self: Sized;
+    fn decode(&self, bytes: &[u8], options: DecodeOptions) -> Result<DecodedImage, PageError>;
+
+    /// Decode a low-resolution version of an image, as quickly as possible
+    /// The result doesn't need to fit in [`PREVIEW_MAX_SIDE`], it will be downscaled afterwards
+    ///
+    /// Decoders which can't do better than a full decode don't need to implement this, `None` is then returned
+    /// Returns `None` as well if the preview can't be decoded quickly for this specific image
+    fn decode_preview(
+        &self,
+        _bytes: &[u8],
+        _options: DecodeOptions,
+    ) -> Result<Option<DecodedImage>, PageError> {
+        Ok(None)
+    }
+
+    /// Get the width and height of an image (as displayed), reading as little of it as possible
+    ///
+    /// Decoders which can't read them from the image's headers don't need to implement this,
+    /// the dimensions of a preview are then returned instead (which have the same aspect ratio)
+    fn dimensions(&self, bytes: &[u8]) -> Result<(usize, usize), PageError> {
+        let options = DecodeOptions {
+            color_manage: false,
+            max_dimension: None,
+            raster_height: Some(PREVIEW_MAX_SIDE),
+            threads: 1,
+        };
+
+        let preview = match self.decode_preview(bytes, options)? {
+            Some(preview) => preview,
+            None => self.decode(bytes, options)?,
+        };
+
+        Ok((preview.width, preview.height))
+    }
 }
 
+/// Maximum width and height of previews
+pub const PREVIEW_MAX_SIDE: usize = 384;
+
+/// Maximum number of pixels of a decoded image
+/// This avoids trying to allocate gigabytes of memory for images with huge (or bogus) dimensions
+const MAX_PIXELS: usize = 1 << 28;
+
+/// Ensure an image's dimensions, as announced by its headers, are not too large to be decoded
+fn check_dimensions(width: usize, height: usize) -> Result<(), PageError> {
+    match width.checked_mul(height) {
+        Some(pixels) if pixels <= MAX_PIXELS => Ok(()),
+        _ => Err(PageError::TooLarge { width, height }),
+    }
+}
+
+/// Options affecting how images are decoded
+#[derive(Clone, Copy)]
+pub struct DecodeOptions {
+    /// Convert pixels to sRGB using the image's embedded ICC profile (if any)
+    pub color_manage: bool,
+
+    /// Maximum width and height of decoded images
+    /// Larger images are downscaled to fit, to reduce memory and texture upload costs
+    pub max_dimension: Option<usize>,
+
+    /// Height at which vector images are rasterised
+    pub raster_height: Option<usize>,
+
+    /// Maximum number of threads the decoding of a single image may use
+    /// Only some steps of the decoding (e.g. color management, downscaling) can use several threads
+    pub threads: usize,
+}
+
+#[derive(Clone)]
 pub struct DecodedImage {
-    pub rgb8_pixels: Vec<u8>,
+    pub pixels: Vec<u8>,
+    pub format: PixelFormat,
     pub width: usize,
     pub height: usize,
+
+    /// Problem encountered while decoding the image, which could still be decoded (at least partially)
+    pub warning: Option<String>,
 }
 
-pub fn is_image_supported(filename: &Path) -> bool {
-    PngDecoder::item_matches(filename) || JpegDecoder::item_matches(filename)
+/// Image decoded to display while the full one isn't available
+pub enum PagePreview {
+    /// Low-resolution version of the image, which fits in [`PREVIEW_MAX_SIDE`]
+    Preview(DecodedImage),
+
+    /// The full image, when its decoder can't do better than a full decode
+    /// It can then be displayed directly instead of being decoded again
+    Full(DecodedImage),
 }
 
-pub fn decode_image(filename: &Path, raw: &[u8]) -> Result<DecodedImage> {
-    if PngDecoder::item_matches(filename) {
-        PngDecoder::decode(raw)
-    } else if JpegDecoder::item_matches(filename) {
-        JpegDecoder::decode(raw)
-    } else {
-        bail!("Unsupported image type provided");
+impl PagePreview {
+    /// Get the decoded image, whether it is a preview or the full image
+    pub fn into_image(self) -> DecodedImage {
+        match self {
+            Self::Preview(image) | Self::Full(image) => image,
+        }
     }
 }
+
+/// Layout of the pixels of a decoded image
+/// Grayscale images are kept as such to use a third of the memory RGB pixels would require
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Gray8,
+    Rgb8,
+    Rgba8,
+}
+
+impl PixelFormat {
+    /// Get the format of pixels made of the provided number of bytes, if any
+    pub fn from_channels(channels: usize) -> Option<Self> {
+        match channels {
+            1 => Some(Self::Gray8),
+            3 => Some(Self::Rgb8),
+            4 => Some(Self::Rgba8),
+            _ => None,
+        }
+    }
+
+    /// Number of bytes per pixel
+    pub fn channels(self) -> usize {
+        match self {
+            Self::Gray8 => 1,
+            Self::Rgb8 => 3,
+            Self::Rgba8 => 4,
+        }
+    }
+}
+
+/// Get the format of the raw pixels produced by a decoder, from their number of bytes
+/// Gray pixels with an alpha channel are expanded to RGBA ones
+fn identify_pixels(
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+    decoder_name: &str,
+) -> Result<(Vec<u8>, PixelFormat), PageError> {
+    let channels = match pixels.len().checked_div(width * height) {
+        Some(channels) if pixels.len().is_multiple_of(width * height) => channels,
+        _ => 0,
+    };
+
+    if channels == 2 {
+        let pixels = pixels
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect();
+
+        return Ok((pixels, PixelFormat::Rgba8));
+    }
+
+    match PixelFormat::from_channels(channels) {
+        Some(format) => Ok((pixels, format)),
+        None => Err(PageError::Corrupted {
+            detail: format!(
+                "got invalid number of bytes from {decoder_name} decoding: expected a multiple of {}, got {}",
+                width * height,
+                pixels.len(),
+            ),
+        }),
+    }
+}
+
+pub fn is_image_supported(filename: &Path) -> bool {
+    DECODER_REGISTRY.read().unwrap().is_supported(filename)
+}
+
+/// Get the extensions of the images that can be decoded
+pub fn supported_image_extensions() -> Vec<&'static str> {
+    DECODER_REGISTRY.read().unwrap().extensions()
+}
+
+/// Number of bytes at the beginning of a file required to identify its format
+pub const IMAGE_HEADER_LEN: usize = 64;
+
+/// Check if a file is an image that can be decoded based on its first bytes, for files whose
+/// extension isn't enough to tell (e.g. files without extension)
+pub fn is_image_content_supported(header: &[u8]) -> bool {
+    DECODER_REGISTRY
+        .read()
+        .unwrap()
+        .is_content_supported(header)
+}
+
+/// Get the name of the format of an image that is recognized but not supported by this build
+/// Returns `None` for supported images as well as for non-image files
+pub fn unsupported_image_format(filename: &Path) -> Option<&'static str> {
+    DECODER_REGISTRY
+        .read()
+        .unwrap()
+        .unsupported_format(filename)
+}
+
+/// Decode an image
+///
+/// The decoder is chosen from the content of the file, as some files don't have the
+/// extension matching their actual format. The extension is only used as a fallback
+/// if the content doesn't match any known format.
+pub fn decode_image(
+    filename: &Path,
+    raw: &[u8],
+    options: DecodeOptions,
+) -> Result<DecodedImage, PageError> {
+    DECODER_REGISTRY
+        .read()
+        .unwrap()
+        .decode(filename, raw, options)
+}
+
+/// Decode a low-resolution preview of an image, to display while the full image is being decoded
+/// Images which can't be previewed quickly are fully decoded instead
+pub fn decode_preview(
+    filename: &Path,
+    raw: &[u8],
+    options: DecodeOptions,
+) -> Result<PagePreview, PageError> {
+    DECODER_REGISTRY
+        .read()
+        .unwrap()
+        .decode_preview(filename, raw, options)
+}
+
+/// Get the width and height of an image without decoding it (when possible), e.g. to lay out pages in advance
+pub fn image_dimensions(filename: &Path, raw: &[u8]) -> Result<(usize, usize), PageError> {
+    DECODER_REGISTRY.read().unwrap().dimensions(filename, raw)
+}
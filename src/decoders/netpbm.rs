@@ -0,0 +1,199 @@
+use crate::errors::PageError;
+
+use super::{check_dimensions, DecodeOptions, DecodedImage, ImageDecoder, PixelFormat};
+
+/// Decoder for binary Netpbm images (PBM, PGM and PPM)
+/// ASCII variants are handled by the fallback decoder instead
+pub struct NetpbmDecoder;
+
+/// Kind of binary Netpbm image
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// Black and white (P4)
+    Bitmap,
+    /// Grayscale (P5)
+    Graymap,
+    /// RGB (P6)
+    Pixmap,
+}
+
+impl Kind {
+    fn from_magic(bytes: &[u8]) -> Option<Self> {
+        match bytes.get(0..2)? {
+            b"P4" => Some(Self::Bitmap),
+            b"P5" => Some(Self::Graymap),
+            b"P6" => Some(Self::Pixmap),
+            _ => None,
+        }
+    }
+}
+
+impl ImageDecoder for NetpbmDecoder {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["pbm", "pgm", "ppm", "pnm"]
+    }
+
+    fn content_matches(&self, bytes: &[u8]) -> bool {
+        Kind::from_magic(bytes).is_some()
+            && bytes.get(2).is_some_and(|byte| byte.is_ascii_whitespace())
+    }
+
+    fn decode(&self, bytes: &[u8], _: DecodeOptions) -> Result<DecodedImage, PageError> {
+        let kind = Kind::from_magic(bytes).ok_or_else(|| PageError::Corrupted {
+            detail: "invalid Netpbm signature".to_owned(),
+        })?;
+
+        let mut header = Header { bytes, offset: 2 };
+
+        let width = header.read_number()?;
+        let height = header.read_number()?;
+
+        // Bitmaps don't have a maximum value, as they only contain one bit per pixel
+        let max_value = match kind {
+            Kind::Bitmap => 1,
+            Kind::Graymap | Kind::Pixmap => header.read_number()?,
+        };
+
+        if max_value == 0 || max_value > usize::from(u16::MAX) {
+            return Err(PageError::Corrupted {
+                detail: format!("invalid Netpbm maximum value {max_value}"),
+            });
+        }
+
+        if width == 0 || height == 0 {
+            return Err(PageError::Corrupted {
+                detail: format!("invalid Netpbm dimensions {width}x{height}"),
+            });
+        }
+
+        check_dimensions(width, height)?;
+
+        // The header ends with a single whitespace character, followed by the raster
+        let raster = bytes.get(header.offset + 1..).unwrap_or_default();
+
+        let pixels = match kind {
+            Kind::Bitmap => {
+                let row_len = width.div_ceil(8);
+                let raster = expect_len(raster, row_len * height)?;
+
+                let mut pixels = Vec::with_capacity(width * height);
+
+                for row in raster.chunks_exact(row_len) {
+                    for x in 0..width {
+                        // A set bit indicates a black pixel
+                        let value = if row[x / 8] & (0x80 >> (x % 8)) == 0 {
+                            255
+                        } else {
+                            0
+                        };
+
+                        pixels.push(value);
+                    }
+                }
+
+                pixels
+            }
+
+            Kind::Graymap => read_samples(raster, width * height, max_value)?,
+
+            Kind::Pixmap => read_samples(raster, width * height * 3, max_value)?,
+        };
+
+        Ok(DecodedImage {
+            pixels,
+            format: match kind {
+                Kind::Bitmap | Kind::Graymap => PixelFormat::Gray8,
+                Kind::Pixmap => PixelFormat::Rgb8,
+            },
+            width,
+            height,
+            warning: None,
+        })
+    }
+}
+
+/// Reader for the textual header of Netpbm images
+struct Header<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Header<'_> {
+    /// Read the next number, skipping whitespaces and comments
+    fn read_number(&mut self) -> Result<usize, PageError> {
+        loop {
+            match self.bytes.get(self.offset) {
+                // Comments last until the end of the line
+                Some(b'#') => {
+                    while self
+                        .bytes
+                        .get(self.offset)
+                        .is_some_and(|byte| *byte != b'\n')
+                    {
+                        self.offset += 1;
+                    }
+                }
+                Some(byte) if byte.is_ascii_whitespace() => self.offset += 1,
+                Some(_) => break,
+                None => {
+                    return Err(PageError::Corrupted {
+                        detail: "Netpbm header is truncated".to_owned(),
+                    })
+                }
+            }
+        }
+
+        let start = self.offset;
+
+        while self.bytes.get(self.offset).is_some_and(u8::is_ascii_digit) {
+            self.offset += 1;
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.offset])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| PageError::Corrupted {
+                detail: format!("invalid number in Netpbm header at byte {start}"),
+            })
+    }
+}
+
+/// Ensure the raster contains at least the expected number of bytes
+fn expect_len(raster: &[u8], len: usize) -> Result<&[u8], PageError> {
+    raster.get(..len).ok_or_else(|| PageError::Corrupted {
+        detail: format!(
+            "Netpbm raster is truncated, {} bytes are missing",
+            len - raster.len()
+        ),
+    })
+}
+
+/// Read samples from the raster, rescaling them to 8-bit
+fn read_samples(raster: &[u8], count: usize, max_value: usize) -> Result<Vec<u8>, PageError> {
+    // Samples are stored on two bytes (big-endian) when the maximum value requires it
+    if max_value > 255 {
+        let raster = expect_len(raster, count * 2)?;
+
+        Ok(raster
+            .chunks_exact(2)
+            .map(|sample| {
+                rescale(
+                    usize::from(u16::from_be_bytes([sample[0], sample[1]])),
+                    max_value,
+                )
+            })
+            .collect())
+    } else if max_value == 255 {
+        Ok(expect_len(raster, count)?.to_vec())
+    } else {
+        Ok(expect_len(raster, count)?
+            .iter()
+            .map(|sample| rescale(usize::from(*sample), max_value))
+            .collect())
+    }
+}
+
+/// Rescale a sample to the 0-255 range
+fn rescale(sample: usize, max_value: usize) -> u8 {
+    u8::try_from(sample.min(max_value) * 255 / max_value).unwrap()
+}
@@ -0,0 +1,27 @@
+/// Process the rows of an image in parallel
+///
+/// The rows are split into (at most) as many bands of consecutive rows as there are threads,
+/// and the provided function is called on each band with the index of its first row
+pub fn for_each_band(
+    pixels: &mut [u8],
+    row_len: usize,
+    threads: usize,
+    process: impl Fn(usize, &mut [u8]) + Sync,
+) {
+    let rows = pixels.len().checked_div(row_len).unwrap_or(0);
+
+    // Not worth spawning threads for tiny images
+    if threads <= 1 || rows < threads * 16 {
+        process(0, pixels);
+        return;
+    }
+
+    let rows_per_band = rows.div_ceil(threads);
+    let process = &process;
+
+    std::thread::scope(|scope| {
+        for (i, band) in pixels.chunks_mut(rows_per_band * row_len).enumerate() {
+            scope.spawn(move || process(i * rows_per_band, band));
+        }
+    });
+}
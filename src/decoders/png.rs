@@ -1,65 +1,366 @@
-use std::path::Path;
-
-use anyhow::{anyhow, bail, Context, Result};
 use zune_png::{zune_core::result::DecodingResult, PngDecoder as ZunePngDecoder};
 
-use super::{DecodedImage, ImageDecoder};
+use crate::errors::PageError;
+
+use super::{
+    check_dimensions, color::convert_to_srgb, identify_pixels, DecodeOptions, DecodedImage,
+    ImageDecoder,
+};
+
+/// Signature all PNG images start with
+const SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
 
 pub struct PngDecoder;
 
 impl ImageDecoder for PngDecoder {
-    fn item_matches(filename: &Path) -> bool
-    where
-        Self: Sized,
-    {
-        let Some(ext) = filename.extension() else { return false; };
-        ext.to_ascii_lowercase() == "png"
-    }
-
-    fn decode(bytes: &[u8]) -> Result<DecodedImage>
-    where
-        Self: Sized,
-    {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["png"]
+    }
+
+    fn content_matches(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(SIGNATURE)
+    }
+
+    fn decode(&self, bytes: &[u8], options: DecodeOptions) -> Result<DecodedImage, PageError> {
         let mut decoder = ZunePngDecoder::new(bytes);
 
+        decoder
+            .decode_headers()
+            .map_err(|err| corrupted(bytes, format!("failed to decode PNG headers: {err:?}")))?;
+
+        // We check the dimensions before decoding to avoid allocating a huge buffer
+        let infos = decoder.get_info().ok_or_else(|| PageError::Corrupted {
+            detail: "missing info headers from PNG".to_owned(),
+        })?;
+
+        check_dimensions(infos.width, infos.height)?;
+
+        // Interlaced (Adam7) images are de-interlaced by the decoder itself, so the
+        // returned pixels are always laid out as a normal raster
         let pixels = decoder
             .decode()
-            .map_err(|err| anyhow!("Failed to decode PNG buffer: {err:?}"))?;
+            .map_err(|err| corrupted(bytes, format!("failed to decode PNG buffer: {err:?}")))?;
 
         let pixel_bytes = match pixels {
             DecodingResult::U8(pixel_bytes) => pixel_bytes,
-            DecodingResult::U16(_) => bail!("16-bit depth PNG images are not supported"),
-            DecodingResult::F32(_) => bail!("Unsupported PNG bit depth"),
-            _ => todo!(),
+            DecodingResult::U16(_) => {
+                return Err(PageError::UnsupportedFormat {
+                    format: "16-bit depth PNG".to_owned(),
+                })
+            }
+            DecodingResult::F32(_) => {
+                return Err(PageError::UnsupportedFormat {
+                    format: "Floating-point PNG".to_owned(),
+                })
+            }
+            _ => {
+                return Err(PageError::UnsupportedFormat {
+                    format: "PNG with an unknown sample type".to_owned(),
+                })
+            }
         };
 
-        decoder
-            .decode_headers()
-            .map_err(|err| anyhow!("Failed to decode PNG headers: {err:?}"))?;
-
-        let infos = decoder
-            .get_info()
-            .context("Missing info headers from PNG")?;
-
-        let rgb8_pixels = if pixel_bytes.len() == infos.width * infos.height * 3 {
-            pixel_bytes
-        } else if pixel_bytes.len() == infos.width * infos.height {
-            pixel_bytes
-                .into_iter()
-                .flat_map(|pixel| [pixel, pixel, pixel])
-                .collect()
-        } else {
-            bail!(
-                "Got invalid number of bytes from PNG decoding: expected a multiple of {}, got {}",
-                infos.width * infos.height,
-                pixel_bytes.len(),
-            );
-        };
+        let infos = decoder.get_info().ok_or_else(|| PageError::Corrupted {
+            detail: "missing info headers from PNG".to_owned(),
+        })?;
+
+        // The raster is expected to be tightly packed, but rows may still have a larger stride
+        // than the image's width, in which case they need to be packed first
+        let channels = decoder
+            .get_colorspace()
+            .map_or(0, |colorspace| colorspace.num_components());
+
+        let pixel_bytes = remove_row_padding(pixel_bytes, infos.width, infos.height, channels);
+
+        let (mut pixels, mut format) =
+            identify_pixels(pixel_bytes, infos.width, infos.height, "PNG")?;
+
+        if options.color_manage {
+            if let Some(icc_profile) = &infos.icc_profile {
+                convert_to_srgb(&mut pixels, &mut format, icc_profile, options.threads);
+            }
+        }
 
         Ok(DecodedImage {
-            rgb8_pixels,
+            pixels,
+            format,
             width: infos.width,
             height: infos.height,
+            warning: None,
         })
     }
+
+    fn dimensions(&self, bytes: &[u8]) -> Result<(usize, usize), PageError> {
+        let mut decoder = ZunePngDecoder::new(bytes);
+
+        decoder
+            .decode_headers()
+            .map_err(|err| corrupted(bytes, format!("failed to decode PNG headers: {err:?}")))?;
+
+        let infos = decoder.get_info().ok_or_else(|| PageError::Corrupted {
+            detail: "missing info headers from PNG".to_owned(),
+        })?;
+
+        Ok((infos.width, infos.height))
+    }
+}
+
+/// Remove the padding at the end of each row of a raster, if there is any
+fn remove_row_padding(pixels: Vec<u8>, width: usize, height: usize, channels: usize) -> Vec<u8> {
+    let row_len = width * channels;
+
+    if row_len == 0 || pixels.len() == row_len * height || !pixels.len().is_multiple_of(height) {
+        return pixels;
+    }
+
+    let stride = pixels.len() / height;
+
+    if stride < row_len {
+        return pixels;
+    }
+
+    pixels
+        .chunks_exact(stride)
+        .flat_map(|row| &row[..row_len])
+        .copied()
+        .collect()
+}
+
+/// Create an error for a PNG image that failed to decode, indicating where it is damaged
+/// This allows to tell if a file is truncated or has been corrupted somewhere in the middle
+fn corrupted(bytes: &[u8], detail: String) -> PageError {
+    PageError::Corrupted {
+        detail: match locate_damage(bytes) {
+            Some(location) => format!("{detail} ({location})"),
+            None => detail,
+        },
+    }
+}
+
+/// Walk through the chunks of a PNG image to find the first damaged one
+/// Returns `None` if the structure of the image looks fine
+fn locate_damage(bytes: &[u8]) -> Option<String> {
+    let mut offset = SIGNATURE.len();
+
+    loop {
+        // Each chunk is made of its length, its type, its data and a checksum of the last two
+        let Some(header) = bytes.get(offset..offset + 8) else {
+            return Some(format!(
+                "file ends at byte {} without an IEND chunk",
+                bytes.len()
+            ));
+        };
+
+        let length = usize::try_from(u32::from_be_bytes(header[0..4].try_into().unwrap())).unwrap();
+        let chunk_type = String::from_utf8_lossy(&header[4..8]);
+
+        let data_end = offset + 8 + length;
+
+        let Some(crc) = bytes.get(data_end..data_end + 4) else {
+            return Some(format!(
+                "chunk {chunk_type} at byte {offset} is truncated, {} bytes are missing",
+                data_end + 4 - bytes.len()
+            ));
+        };
+
+        if crc32fast::hash(&bytes[offset + 4..data_end])
+            != u32::from_be_bytes(crc.try_into().unwrap())
+        {
+            return Some(format!(
+                "chunk {chunk_type} at byte {offset} has an invalid checksum"
+            ));
+        }
+
+        if chunk_type == "IEND" {
+            return None;
+        }
+
+        offset = data_end + 4;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoders::PixelFormat;
+
+    /// Starting column and row, then column and row steps of each Adam7 pass
+    const ADAM7_PASSES: [(usize, usize, usize, usize); 7] = [
+        (0, 0, 8, 8),
+        (4, 0, 8, 8),
+        (0, 4, 4, 8),
+        (2, 0, 4, 4),
+        (0, 2, 2, 4),
+        (1, 0, 2, 2),
+        (0, 1, 1, 2),
+    ];
+
+    const OPTIONS: DecodeOptions = DecodeOptions {
+        color_manage: false,
+        max_dimension: None,
+        raster_height: None,
+        threads: 1,
+    };
+
+    /// Build a PNG image from an 8-bit raster, with unfiltered rows and uncompressed data
+    fn encode(
+        pixels: &[u8],
+        width: usize,
+        height: usize,
+        channels: usize,
+        interlaced: bool,
+    ) -> Vec<u8> {
+        let mut scanlines = vec![];
+
+        let passes: &[_] = if interlaced {
+            &ADAM7_PASSES
+        } else {
+            &[(0, 0, 1, 1)]
+        };
+
+        for &(x0, y0, dx, dy) in passes {
+            // Empty passes have no scanline at all
+            if x0 >= width || y0 >= height {
+                continue;
+            }
+
+            for y in (y0..height).step_by(dy) {
+                scanlines.push(0);
+
+                for x in (x0..width).step_by(dx) {
+                    let offset = (y * width + x) * channels;
+                    scanlines.extend_from_slice(&pixels[offset..offset + channels]);
+                }
+            }
+        }
+
+        // zlib stream made of a single stored block
+        let mut data = vec![0x78, 0x01, 0x01];
+        let len = u16::try_from(scanlines.len()).unwrap();
+        data.extend_from_slice(&len.to_le_bytes());
+        data.extend_from_slice(&(!len).to_le_bytes());
+        data.extend_from_slice(&scanlines);
+
+        let (mut a, mut b) = (1u32, 0u32);
+
+        for &byte in &scanlines {
+            a = (a + u32::from(byte)) % 65521;
+            b = (b + a) % 65521;
+        }
+
+        data.extend_from_slice(&((b << 16) | a).to_be_bytes());
+
+        let color_type = match channels {
+            1 => 0,
+            3 => 2,
+            _ => unreachable!(),
+        };
+
+        let mut header = vec![];
+        header.extend_from_slice(&u32::try_from(width).unwrap().to_be_bytes());
+        header.extend_from_slice(&u32::try_from(height).unwrap().to_be_bytes());
+        header.extend_from_slice(&[8, color_type, 0, 0, u8::from(interlaced)]);
+
+        let mut png = SIGNATURE.to_vec();
+
+        for (chunk_type, chunk_data) in [(b"IHDR", header), (b"IDAT", data), (b"IEND", vec![])] {
+            let mut chunk = chunk_type.to_vec();
+            chunk.extend_from_slice(&chunk_data);
+
+            png.extend_from_slice(&u32::try_from(chunk_data.len()).unwrap().to_be_bytes());
+            png.extend_from_slice(&chunk);
+            png.extend_from_slice(&crc32fast::hash(&chunk).to_be_bytes());
+        }
+
+        png
+    }
+
+    /// Generate a raster where (almost) every byte is different
+    fn raster(width: usize, height: usize, channels: usize) -> Vec<u8> {
+        (0..width * height * channels)
+            .map(|i| u8::try_from(i * 7 % 251).unwrap())
+            .collect()
+    }
+
+    fn assert_interlaced_matches(width: usize, height: usize, channels: usize) {
+        let pixels = raster(width, height, channels);
+
+        let reference = PngDecoder
+            .decode(&encode(&pixels, width, height, channels, false), OPTIONS)
+            .unwrap();
+
+        let interlaced = PngDecoder
+            .decode(&encode(&pixels, width, height, channels, true), OPTIONS)
+            .unwrap();
+
+        assert_eq!(reference.pixels, pixels);
+        assert_eq!(interlaced.pixels, reference.pixels);
+        assert_eq!(interlaced.width, reference.width);
+        assert_eq!(interlaced.height, reference.height);
+        assert!(interlaced.format == reference.format);
+    }
+
+    #[test]
+    fn decodes_interlaced_gray() {
+        assert_interlaced_matches(13, 9, 1);
+
+        let decoded = PngDecoder
+            .decode(&encode(&raster(13, 9, 1), 13, 9, 1, true), OPTIONS)
+            .unwrap();
+
+        assert!(decoded.format == PixelFormat::Gray8);
+        assert_eq!((decoded.width, decoded.height), (13, 9));
+    }
+
+    #[test]
+    fn decodes_interlaced_rgb() {
+        assert_interlaced_matches(11, 10, 3);
+
+        let decoded = PngDecoder
+            .decode(&encode(&raster(11, 10, 3), 11, 10, 3, true), OPTIONS)
+            .unwrap();
+
+        assert!(decoded.format == PixelFormat::Rgb8);
+        assert_eq!((decoded.width, decoded.height), (11, 10));
+    }
+
+    #[test]
+    fn decodes_interlaced_tiny_images() {
+        // Some of the passes are empty for images smaller than 8x8
+        assert_interlaced_matches(1, 1, 1);
+        assert_interlaced_matches(3, 2, 3);
+        assert_interlaced_matches(5, 7, 1);
+    }
+
+    #[test]
+    fn removes_row_padding() {
+        // Two rows of 2 RGB pixels, each padded with 2 bytes
+        let padded = vec![1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12, 0, 0];
+
+        assert_eq!(
+            remove_row_padding(padded, 2, 2, 3),
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]
+        );
+    }
+
+    #[test]
+    fn keeps_packed_rows() {
+        let packed = raster(4, 3, 3);
+        assert_eq!(remove_row_padding(packed.clone(), 4, 3, 3), packed);
+    }
+
+    #[test]
+    fn keeps_rows_with_invalid_stride() {
+        // Not a multiple of the height
+        let odd = vec![0; 13];
+        assert_eq!(remove_row_padding(odd.clone(), 2, 2, 3), odd);
+
+        // Shorter than the expected rows
+        let short = vec![0; 8];
+        assert_eq!(remove_row_padding(short.clone(), 2, 2, 3), short);
+
+        // Unknown colorspace
+        let unknown = vec![0; 12];
+        assert_eq!(remove_row_padding(unknown.clone(), 2, 2, 0), unknown);
+    }
 }
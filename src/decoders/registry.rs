@@ -0,0 +1,398 @@
+use std::path::Path;
+
+use crate::errors::PageError;
+
+use super::{
+    fallback::FallbackDecoder, jpeg::JpegDecoder, netpbm::NetpbmDecoder, png::PngDecoder,
+    resize::downscale_to_fit, svg::SvgDecoder, tga::TgaDecoder, DecodeOptions, DecodedImage,
+    ImageDecoder, PagePreview, PREVIEW_MAX_SIDE,
+};
+
+/// An image format that is recognized but can't be decoded by this build
+struct UnsupportedFormat {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    content_matches: fn(&[u8]) -> bool,
+}
+
+/// Files matching these formats are reported as skipped pages instead of being silently ignored
+/// Formats for which a decoder is registered are considered supported instead
+const UNSUPPORTED_FORMATS: &[UnsupportedFormat] = &[
+    UnsupportedFormat {
+        name: "AVIF",
+        extensions: &["avif"],
+        content_matches: |bytes| bytes.get(4..12) == Some(b"ftypavif"),
+    },
+    UnsupportedFormat {
+        name: "JPEG XL",
+        extensions: &["jxl"],
+        content_matches: |bytes| {
+            bytes.starts_with(&[0xFF, 0x0A]) || bytes.starts_with(b"\0\0\0\x0CJXL \r\n\x87\n")
+        },
+    },
+    UnsupportedFormat {
+        name: "HEIF",
+        extensions: &["heif", "heic"],
+        content_matches: |bytes| {
+            matches!(
+                bytes.get(4..12),
+                Some(b"ftypheic" | b"ftypheix" | b"ftypmif1" | b"ftypmsf1")
+            )
+        },
+    },
+];
+
+/// List of all decoders images can be decoded with
+pub struct DecoderRegistry {
+    decoders: Vec<Box<dyn ImageDecoder>>,
+}
+
+impl DecoderRegistry {
+    /// Create a registry with all the decoders supported by this build
+    pub fn with_builtin_decoders() -> Self {
+        let mut registry = Self { decoders: vec![] };
+
+        registry.register(Box::new(PngDecoder));
+        registry.register(Box::new(JpegDecoder));
+        registry.register(Box::new(NetpbmDecoder));
+        registry.register(Box::new(SvgDecoder));
+        registry.register(Box::new(TgaDecoder));
+
+        // Must be registered last, so it's only used when no specialised decoder can be
+        registry.register(Box::new(FallbackDecoder));
+
+        registry
+    }
+
+    /// Register a new decoder
+    /// Decoders registered first take precedence
+    pub fn register(&mut self, decoder: Box<dyn ImageDecoder>) {
+        self.decoders.push(decoder);
+    }
+
+    /// Find the decoder handling the extension of a file
+    fn decoder_for_extension(&self, filename: &Path) -> Option<&dyn ImageDecoder> {
+        let ext = filename.extension()?;
+
+        self.decoders
+            .iter()
+            .find(|decoder| {
+                decoder
+                    .extensions()
+                    .iter()
+                    .any(|c_ext| ext.eq_ignore_ascii_case(c_ext))
+            })
+            .map(Box::as_ref)
+    }
+
+    /// Get the extensions of the images handled by all decoders
+    pub fn extensions(&self) -> Vec<&'static str> {
+        self.decoders
+            .iter()
+            .flat_map(|decoder| decoder.extensions().iter().copied())
+            .collect()
+    }
+
+    /// Check if a file can be decoded, based on its extension
+    pub fn is_supported(&self, filename: &Path) -> bool {
+        self.decoder_for_extension(filename).is_some()
+    }
+
+    /// Check if a file can be decoded, based on its first bytes
+    pub fn is_content_supported(&self, header: &[u8]) -> bool {
+        self.decoders
+            .iter()
+            .any(|decoder| decoder.content_matches(header))
+    }
+
+    /// Get the name of the format of an image that is recognized but not supported by this build
+    /// Returns `None` for supported images as well as for non-image files
+    pub fn unsupported_format(&self, filename: &Path) -> Option<&'static str> {
+        if self.is_supported(filename) {
+            return None;
+        }
+
+        let ext = filename.extension()?;
+
+        UNSUPPORTED_FORMATS
+            .iter()
+            .find(|format| {
+                format
+                    .extensions
+                    .iter()
+                    .any(|c_ext| ext.eq_ignore_ascii_case(c_ext))
+            })
+            .map(|format| format.name)
+    }
+
+    /// Decode an image, choosing the decoder from its content first and its extension second
+    pub fn decode(
+        &self,
+        filename: &Path,
+        raw: &[u8],
+        options: DecodeOptions,
+    ) -> Result<DecodedImage, PageError> {
+        let image = self.find_decoder(filename, raw)?.decode(raw, options)?;
+
+        Ok(match options.max_dimension {
+            Some(max_dimension) => fit(image, max_dimension, options.threads),
+            None => image,
+        })
+    }
+
+    /// Decode a low-resolution preview of an image, which fits in [`PREVIEW_MAX_SIDE`]
+    /// Images whose decoder can't do better than a full decode are fully decoded instead,
+    /// so the full image can be displayed directly
+    pub fn decode_preview(
+        &self,
+        filename: &Path,
+        raw: &[u8],
+        options: DecodeOptions,
+    ) -> Result<PagePreview, PageError> {
+        let decoder = self.find_decoder(filename, raw)?;
+
+        // Vector images can directly be rasterised at the preview's size
+        // Previews are small, and several threads generate them at once
+        let preview_options = DecodeOptions {
+            raster_height: Some(PREVIEW_MAX_SIDE),
+            threads: 1,
+            ..options
+        };
+
+        if let Some(image) = decoder.decode_preview(raw, preview_options)? {
+            return Ok(PagePreview::Preview(fit(image, PREVIEW_MAX_SIDE, 1)));
+        }
+
+        let image = decoder.decode(raw, options)?;
+
+        Ok(PagePreview::Full(match options.max_dimension {
+            Some(max_dimension) => fit(image, max_dimension, options.threads),
+            None => image,
+        }))
+    }
+
+    /// Get the width and height of an image, choosing the decoder like when decoding it
+    pub fn dimensions(&self, filename: &Path, raw: &[u8]) -> Result<(usize, usize), PageError> {
+        self.find_decoder(filename, raw)?.dimensions(raw)
+    }
+
+    /// Find the decoder to use for an image
+    fn find_decoder(&self, filename: &Path, raw: &[u8]) -> Result<&dyn ImageDecoder, PageError> {
+        if let Some(decoder) = self
+            .decoders
+            .iter()
+            .find(|decoder| decoder.content_matches(raw))
+        {
+            return Ok(decoder.as_ref());
+        }
+
+        if let Some(format) = UNSUPPORTED_FORMATS
+            .iter()
+            .find(|format| (format.content_matches)(raw))
+        {
+            return Err(PageError::UnsupportedFormat {
+                format: format.name.to_owned(),
+            });
+        }
+
+        match self.decoder_for_extension(filename) {
+            Some(decoder) => Ok(decoder),
+            None => Err(PageError::UnsupportedFormat {
+                format: match filename.extension() {
+                    Some(ext) => ext.to_string_lossy().to_uppercase(),
+                    None => "Unrecognized".to_owned(),
+                },
+            }),
+        }
+    }
+}
+
+/// Downscale a decoded image so none of its sides exceed the provided size
+pub fn fit(image: DecodedImage, max_side: usize, threads: usize) -> DecodedImage {
+    let DecodedImage {
+        pixels,
+        format,
+        width,
+        height,
+        warning,
+    } = image;
+
+    let (pixels, width, height) =
+        downscale_to_fit(pixels, width, height, format.channels(), max_side, threads);
+
+    DecodedImage {
+        pixels,
+        format,
+        width,
+        height,
+        warning,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoders::PixelFormat;
+
+    /// Decoder recognizing images from their extensions and signature, identified by its width
+    struct MockDecoder {
+        id: usize,
+        extensions: &'static [&'static str],
+        signature: &'static [u8],
+    }
+
+    impl ImageDecoder for MockDecoder {
+        fn extensions(&self) -> &'static [&'static str] {
+            self.extensions
+        }
+
+        fn content_matches(&self, bytes: &[u8]) -> bool {
+            bytes.starts_with(self.signature)
+        }
+
+        fn decode(&self, _: &[u8], _: DecodeOptions) -> Result<DecodedImage, PageError> {
+            Ok(DecodedImage {
+                pixels: vec![0; self.id],
+                format: PixelFormat::Gray8,
+                width: self.id,
+                height: 1,
+                warning: None,
+            })
+        }
+
+        fn dimensions(&self, _: &[u8]) -> Result<(usize, usize), PageError> {
+            Ok((self.id, 1))
+        }
+    }
+
+    /// Registry with a decoder for "foo" images, then one for "bar" and "foo" images
+    fn registry() -> DecoderRegistry {
+        let mut registry = DecoderRegistry { decoders: vec![] };
+
+        registry.register(Box::new(MockDecoder {
+            id: 1,
+            extensions: &["foo"],
+            signature: b"FOO",
+        }));
+
+        registry.register(Box::new(MockDecoder {
+            id: 2,
+            extensions: &["bar", "foo"],
+            signature: b"BAR",
+        }));
+
+        registry
+    }
+
+    /// Get the ID of the decoder chosen for an image
+    fn decoder_id(registry: &DecoderRegistry, filename: &str, raw: &[u8]) -> Option<usize> {
+        let decoder = registry.find_decoder(Path::new(filename), raw).ok()?;
+        Some(decoder.dimensions(raw).unwrap().0)
+    }
+
+    #[test]
+    fn matches_extensions() {
+        let registry = registry();
+
+        assert!(registry.is_supported(Path::new("page.foo")));
+        assert!(registry.is_supported(Path::new("page.BAR")));
+        assert!(!registry.is_supported(Path::new("page.baz")));
+        assert!(!registry.is_supported(Path::new("foo")));
+
+        assert_eq!(decoder_id(&registry, "page.bar", b"data"), Some(2));
+        assert_eq!(decoder_id(&registry, "page.Bar", b"data"), Some(2));
+        assert_eq!(decoder_id(&registry, "page.baz", b"data"), None);
+    }
+
+    #[test]
+    fn matches_content() {
+        let registry = registry();
+
+        assert!(registry.is_content_supported(b"FOO..."));
+        assert!(registry.is_content_supported(b"BAR..."));
+        assert!(!registry.is_content_supported(b"BAZ..."));
+
+        assert_eq!(decoder_id(&registry, "page", b"BAR..."), Some(2));
+        assert_eq!(decoder_id(&registry, "page.baz", b"FOO..."), Some(1));
+    }
+
+    #[test]
+    fn prefers_content_over_extension() {
+        let registry = registry();
+
+        assert_eq!(decoder_id(&registry, "page.foo", b"BAR..."), Some(2));
+        assert_eq!(decoder_id(&registry, "page.bar", b"FOO..."), Some(1));
+    }
+
+    #[test]
+    fn prefers_decoders_registered_first() {
+        let registry = registry();
+
+        // Both decoders handle the extension
+        assert_eq!(decoder_id(&registry, "page.foo", b"data"), Some(1));
+
+        let mut registry = registry;
+        registry.register(Box::new(MockDecoder {
+            id: 3,
+            extensions: &["baz"],
+            signature: b"FOO",
+        }));
+
+        // Both decoders match the content
+        assert_eq!(decoder_id(&registry, "page.baz", b"FOO..."), Some(1));
+    }
+
+    #[test]
+    fn fully_decodes_images_without_previews() {
+        let registry = registry();
+
+        let options = DecodeOptions {
+            color_manage: false,
+            max_dimension: None,
+            raster_height: None,
+            threads: 1,
+        };
+
+        let preview = registry
+            .decode_preview(Path::new("page.foo"), b"data", options)
+            .unwrap();
+
+        assert!(matches!(preview, PagePreview::Full(image) if image.width == 1));
+    }
+
+    #[test]
+    fn reports_unsupported_formats() {
+        let registry = registry();
+
+        assert_eq!(
+            registry.unsupported_format(Path::new("page.avif")),
+            Some("AVIF")
+        );
+        assert_eq!(registry.unsupported_format(Path::new("page.foo")), None);
+        assert_eq!(registry.unsupported_format(Path::new("notes.txt")), None);
+
+        let err = registry
+            .find_decoder(Path::new("page.foo"), b"\0\0\0\0ftypavif")
+            .err()
+            .unwrap();
+
+        assert!(matches!(err, PageError::UnsupportedFormat { format } if format == "AVIF"));
+    }
+
+    #[test]
+    fn decodes_with_the_chosen_decoder() {
+        let registry = registry();
+
+        let options = DecodeOptions {
+            color_manage: false,
+            max_dimension: None,
+            raster_height: None,
+            threads: 1,
+        };
+
+        let decoded = registry
+            .decode(Path::new("page.bar"), b"data", options)
+            .unwrap();
+        assert_eq!((decoded.width, decoded.height), (2, 1));
+    }
+}
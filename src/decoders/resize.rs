@@ -0,0 +1,89 @@
+use super::parallel::for_each_band;
+
+/// Downscale an image with a box filter, so that none of its sides exceed the provided size,
+/// preserving its aspect ratio
+/// Images that already fit are returned untouched
+///
+/// Returns the new buffer along with its width and height
+pub fn downscale_to_fit(
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+    channels: usize,
+    max_side: usize,
+    threads: usize,
+) -> (Vec<u8>, usize, usize) {
+    if width <= max_side && height <= max_side {
+        return (pixels, width, height);
+    }
+
+    let ratio = max_side as f64 / width.max(height) as f64;
+
+    let out_width = ((width as f64 * ratio).round() as usize).clamp(1, max_side);
+    let out_height = ((height as f64 * ratio).round() as usize).clamp(1, max_side);
+
+    let out = downscale(
+        &pixels, width, height, channels, out_width, out_height, threads,
+    );
+
+    (out, out_width, out_height)
+}
+
+/// Downscale an image to the provided size with a box filter
+/// Rows are computed in parallel using at most the provided number of threads
+pub fn downscale(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    channels: usize,
+    out_width: usize,
+    out_height: usize,
+    threads: usize,
+) -> Vec<u8> {
+    assert!(out_width <= width && out_height <= height);
+
+    let mut out = vec![0; out_width * out_height * channels];
+
+    for_each_band(
+        &mut out,
+        out_width * channels,
+        threads,
+        |first_row, band| {
+            let mut sums = vec![0u32; channels];
+
+            for (i, out_row) in band.chunks_exact_mut(out_width * channels).enumerate() {
+                let out_y = first_row + i;
+
+                // Each output pixel is the average of all the source pixels it covers
+                let y_start = out_y * height / out_height;
+                let y_end = ((out_y + 1) * height / out_height).max(y_start + 1);
+
+                for (out_x, out_pixel) in out_row.chunks_exact_mut(channels).enumerate() {
+                    let x_start = out_x * width / out_width;
+                    let x_end = ((out_x + 1) * width / out_width).max(x_start + 1);
+
+                    sums.fill(0);
+
+                    for y in y_start..y_end {
+                        let row = &pixels
+                            [(y * width + x_start) * channels..(y * width + x_end) * channels];
+
+                        for pixel in row.chunks_exact(channels) {
+                            for (sum, value) in sums.iter_mut().zip(pixel) {
+                                *sum += u32::from(*value);
+                            }
+                        }
+                    }
+
+                    let count = u32::try_from((y_end - y_start) * (x_end - x_start)).unwrap();
+
+                    for (value, sum) in out_pixel.iter_mut().zip(&sums) {
+                        *value = u8::try_from(sum / count).unwrap();
+                    }
+                }
+            }
+        },
+    );
+
+    out
+}
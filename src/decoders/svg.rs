@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use resvg::{
+    tiny_skia::{Color, Pixmap, Transform},
+    usvg::{fontdb::Database, Options, Tree},
+};
+
+use crate::errors::PageError;
+
+use super::{check_dimensions, DecodeOptions, DecodedImage, ImageDecoder, PixelFormat};
+
+/// Height vector images are rasterised at when no hint is provided
+const DEFAULT_RASTER_HEIGHT: usize = 2048;
+
+/// Fonts available to render texts in vector images
+/// Loading system fonts is slow, so it's only done once
+static FONTS: Lazy<Arc<Database>> = Lazy::new(|| {
+    let mut fonts = Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+pub struct SvgDecoder;
+
+impl ImageDecoder for SvgDecoder {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["svg", "svgz"]
+    }
+
+    fn content_matches(&self, bytes: &[u8]) -> bool {
+        // SVG images may start with an XML declaration, comments, a doctype, etc.
+        // so we look for the root element in the beginning of the file instead
+        let start = &bytes[..bytes.len().min(4096)];
+
+        let text = String::from_utf8_lossy(start);
+        let text = text.trim_start_matches('\u{FEFF}').trim_start();
+
+        (text.starts_with("<?xml") || text.starts_with("<svg") || text.starts_with("<!--"))
+            && text.contains("<svg")
+    }
+
+    fn decode(&self, bytes: &[u8], options: DecodeOptions) -> Result<DecodedImage, PageError> {
+        let tree = Tree::from_data(
+            bytes,
+            &Options {
+                fontdb: Arc::clone(&FONTS),
+                ..Options::default()
+            },
+        )
+        .map_err(|err| PageError::Corrupted {
+            detail: format!("failed to parse SVG: {err}"),
+        })?;
+
+        // Vector images don't have an actual size, so they are rasterised at the requested height
+        let size = tree.size();
+        let height = options
+            .raster_height
+            .unwrap_or(DEFAULT_RASTER_HEIGHT)
+            .max(1);
+        let scale = height as f32 / size.height();
+        let width = ((size.width() * scale).round() as usize).max(1);
+
+        check_dimensions(width, height)?;
+
+        let mut pixmap = Pixmap::new(
+            u32::try_from(width).unwrap(),
+            u32::try_from(height).unwrap(),
+        )
+        .ok_or(PageError::TooLarge { width, height })?;
+
+        // Transparent areas are displayed on white, like in most viewers
+        pixmap.fill(Color::WHITE);
+
+        resvg::render(
+            &tree,
+            Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+
+        // As the background is opaque, pixels are not premultiplied and the alpha can be dropped
+        let pixels = pixmap
+            .data()
+            .chunks_exact(4)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect();
+
+        Ok(DecodedImage {
+            pixels,
+            format: PixelFormat::Rgb8,
+            width,
+            height,
+            warning: None,
+        })
+    }
+
+    fn decode_preview(
+        &self,
+        bytes: &[u8],
+        options: DecodeOptions,
+    ) -> Result<Option<DecodedImage>, PageError> {
+        // Rasterising at the preview's height is as fast as it gets
+        self.decode(bytes, options).map(Some)
+    }
+}
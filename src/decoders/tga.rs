@@ -0,0 +1,173 @@
+use std::borrow::Cow;
+
+use crate::errors::PageError;
+
+use super::{
+    check_dimensions, fallback::FallbackDecoder, DecodeOptions, DecodedImage, ImageDecoder,
+    PixelFormat,
+};
+
+/// Length of the header all TGA images start with
+const HEADER_LEN: usize = 18;
+
+/// Decoder for true-color TGA images (24 and 32-bit, uncompressed or RLE-compressed)
+/// Other variants (color-mapped, grayscale, etc.) are handled by the fallback decoder instead
+pub struct TgaDecoder;
+
+impl ImageDecoder for TgaDecoder {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["tga"]
+    }
+
+    fn content_matches(&self, _: &[u8]) -> bool {
+        // TGA images don't have any signature, so they can only be recognized by their extension
+        false
+    }
+
+    fn decode(&self, bytes: &[u8], options: DecodeOptions) -> Result<DecodedImage, PageError> {
+        let header = bytes
+            .get(..HEADER_LEN)
+            .ok_or_else(|| PageError::Corrupted {
+                detail: "TGA header is truncated".to_owned(),
+            })?;
+
+        let id_len = usize::from(header[0]);
+        let color_map_type = header[1];
+        let image_type = header[2];
+        let color_map_len = usize::from(u16::from_le_bytes([header[5], header[6]]));
+        let color_map_entry_bits = usize::from(header[7]);
+        let width = usize::from(u16::from_le_bytes([header[12], header[13]]));
+        let height = usize::from(u16::from_le_bytes([header[14], header[15]]));
+        let depth = header[16];
+        let descriptor = header[17];
+
+        let compressed = match (image_type, depth) {
+            (2, 24 | 32) => false,
+            (10, 24 | 32) => true,
+            _ => return FallbackDecoder.decode(bytes, options),
+        };
+
+        if width == 0 || height == 0 {
+            return Err(PageError::Corrupted {
+                detail: format!("invalid TGA dimensions {width}x{height}"),
+            });
+        }
+
+        check_dimensions(width, height)?;
+
+        let bytes_per_pixel = usize::from(depth / 8);
+
+        // The pixels come after the image's identifier and its color map (unused here)
+        let color_map_size = if color_map_type == 1 {
+            color_map_len * color_map_entry_bits.div_ceil(8)
+        } else {
+            0
+        };
+
+        let data = bytes
+            .get(HEADER_LEN + id_len + color_map_size..)
+            .unwrap_or_default();
+
+        let raster = if compressed {
+            Cow::Owned(decode_rle(data, width * height, bytes_per_pixel)?)
+        } else {
+            let len = width * height * bytes_per_pixel;
+
+            Cow::Borrowed(data.get(..len).ok_or_else(|| PageError::Corrupted {
+                detail: format!(
+                    "TGA raster is truncated, {} bytes are missing",
+                    len - data.len()
+                ),
+            })?)
+        };
+
+        // The alpha channel is only meaningful if the descriptor indicates it has some bits
+        let has_alpha = bytes_per_pixel == 4 && descriptor & 0x0F != 0;
+        let right_to_left = descriptor & 0x10 != 0;
+        let top_to_bottom = descriptor & 0x20 != 0;
+
+        let row_len = width * bytes_per_pixel;
+        let mut pixels = Vec::with_capacity(width * height * 3);
+
+        for y in 0..height {
+            // Rows are stored from the bottom to the top, unless indicated otherwise
+            let src_y = if top_to_bottom { y } else { height - 1 - y };
+            let row = &raster[src_y * row_len..(src_y + 1) * row_len];
+
+            for x in 0..width {
+                let src_x = if right_to_left { width - 1 - x } else { x };
+                let pixel = &row[src_x * bytes_per_pixel..(src_x + 1) * bytes_per_pixel];
+
+                // Pixels are stored as BGR(A)
+                let rgb = [pixel[2], pixel[1], pixel[0]];
+
+                if has_alpha {
+                    // Transparent areas are displayed on white, like in most viewers
+                    let alpha = u16::from(pixel[3]);
+
+                    pixels.extend(rgb.map(|value| {
+                        u8::try_from((u16::from(value) * alpha + 255 * (255 - alpha)) / 255)
+                            .unwrap()
+                    }));
+                } else {
+                    pixels.extend(rgb);
+                }
+            }
+        }
+
+        Ok(DecodedImage {
+            pixels,
+            format: PixelFormat::Rgb8,
+            width,
+            height,
+            warning: None,
+        })
+    }
+}
+
+/// Decode a RLE-compressed raster
+/// Packets either repeat a single pixel or contain several raw pixels, and may span several rows
+fn decode_rle(data: &[u8], pixels: usize, bytes_per_pixel: usize) -> Result<Vec<u8>, PageError> {
+    let len = pixels * bytes_per_pixel;
+
+    let mut raster = Vec::with_capacity(len);
+    let mut offset = 0;
+
+    let truncated = || PageError::Corrupted {
+        detail: format!("TGA RLE stream is truncated after {} bytes", data.len()),
+    };
+
+    while raster.len() < len {
+        let packet = *data.get(offset).ok_or_else(truncated)?;
+        offset += 1;
+
+        let count = usize::from(packet & 0x7F) + 1;
+
+        if raster.len() + count * bytes_per_pixel > len {
+            return Err(PageError::Corrupted {
+                detail: "TGA RLE stream contains more pixels than the image".to_owned(),
+            });
+        }
+
+        if packet & 0x80 != 0 {
+            let pixel = data
+                .get(offset..offset + bytes_per_pixel)
+                .ok_or_else(truncated)?;
+
+            for _ in 0..count {
+                raster.extend_from_slice(pixel);
+            }
+
+            offset += bytes_per_pixel;
+        } else {
+            let pixels = data
+                .get(offset..offset + count * bytes_per_pixel)
+                .ok_or_else(truncated)?;
+
+            raster.extend_from_slice(pixels);
+            offset += count * bytes_per_pixel;
+        }
+    }
+
+    Ok(raster)
+}
@@ -0,0 +1,121 @@
+use super::{DecodedImage, PixelFormat};
+
+/// Proportion of pixels of a row or column which may differ from the margins' colour,
+/// so noise and dust in scans don't prevent margins from being detected
+const MAX_OUTLIERS: f32 = 0.01;
+
+/// Minimum proportion of the page's area which must be kept, so actual content
+/// (e.g. a drawing with a uniform background) isn't mistaken for margins
+const MIN_KEPT_AREA: f32 = 0.6;
+
+/// Margins of a page, as proportions of its width and height
+/// This allows to apply the margins detected on a preview to the full page
+#[derive(Clone, Copy)]
+pub struct Margins {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+/// Detect the near-uniform margins around an image, whose colour is the one of its top-left pixel
+/// Pixels whose luminance differs from it by at most `threshold` are considered to be part of the margins
+///
+/// Returns `None` if there are no margins, or if they are too large to be only margins
+pub fn find_margins(image: &DecodedImage, threshold: u8) -> Option<Margins> {
+    let DecodedImage { width, height, .. } = *image;
+
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let margin_luminance = luminance(image, 0, 0);
+    let is_margin = |x, y| luminance(image, x, y).abs_diff(margin_luminance) <= threshold;
+
+    let uniform = |pixels: &mut dyn Iterator<Item = (usize, usize)>, len: usize| {
+        let max_outliers = (len as f32 * MAX_OUTLIERS) as usize;
+        pixels
+            .filter(|&(x, y)| !is_margin(x, y))
+            .nth(max_outliers)
+            .is_none()
+    };
+
+    let uniform_row = |y: usize| uniform(&mut (0..width).map(|x| (x, y)), width);
+    let uniform_column = |x: usize, top: usize, bottom: usize| {
+        uniform(&mut (top..bottom).map(|y| (x, y)), bottom - top)
+    };
+
+    let top = (0..height).find(|&y| !uniform_row(y))?;
+    let bottom = (top..height).rev().find(|&y| !uniform_row(y))? + 1;
+    let left = (0..width).find(|&x| !uniform_column(x, top, bottom))?;
+    let right = (left..width)
+        .rev()
+        .find(|&x| !uniform_column(x, top, bottom))?
+        + 1;
+
+    let kept_area = ((right - left) * (bottom - top)) as f32 / (width * height) as f32;
+
+    if kept_area == 1.0 || kept_area < MIN_KEPT_AREA {
+        return None;
+    }
+
+    Some(Margins {
+        left: left as f32 / width as f32,
+        top: top as f32 / height as f32,
+        right: (width - right) as f32 / width as f32,
+        bottom: (height - bottom) as f32 / height as f32,
+    })
+}
+
+/// Remove the provided margins from an image
+pub fn trim(image: DecodedImage, margins: Margins) -> DecodedImage {
+    let left = (margins.left * image.width as f32) as usize;
+    let top = (margins.top * image.height as f32) as usize;
+    let right = image.width - (margins.right * image.width as f32) as usize;
+    let bottom = image.height - (margins.bottom * image.height as f32) as usize;
+
+    // Tiny images (e.g. previews) may not have anything left once rounded
+    if left >= right || top >= bottom {
+        return image;
+    }
+
+    let DecodedImage {
+        pixels,
+        format,
+        width,
+        warning,
+        ..
+    } = image;
+
+    let channels = format.channels();
+
+    let pixels = pixels
+        .chunks_exact(width * channels)
+        .skip(top)
+        .take(bottom - top)
+        .flat_map(|row| &row[left * channels..right * channels])
+        .copied()
+        .collect();
+
+    DecodedImage {
+        pixels,
+        format,
+        width: right - left,
+        height: bottom - top,
+        warning,
+    }
+}
+
+/// Get the luminance of a pixel
+fn luminance(image: &DecodedImage, x: usize, y: usize) -> u8 {
+    let channels = image.format.channels();
+    let offset = (y * image.width + x) * channels;
+
+    match image.format {
+        PixelFormat::Gray8 => image.pixels[offset],
+        PixelFormat::Rgb8 | PixelFormat::Rgba8 => {
+            let [r, g, b] = [0, 1, 2].map(|channel| u32::from(image.pixels[offset + channel]));
+            ((r * 299 + g * 587 + b * 114) / 1000) as u8
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+
+/// Error happening while loading or decoding a page
+#[derive(Debug, Clone)]
+pub enum PageError {
+    /// The page's data couldn't be read (e.g. file was removed, archive is unreadable)
+    Io { detail: String },
+
+    /// The page's format (or a variant of it) can't be decoded by this build
+    UnsupportedFormat { format: String },
+
+    /// The page's data is invalid
+    Corrupted { detail: String },
+
+    /// The page's dimensions are too large to be decoded
+    TooLarge { width: usize, height: usize },
+
+    /// The requested page doesn't exist in the source
+    OutOfRange { page: usize },
+}
+
+impl fmt::Display for PageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { detail } => write!(f, "Failed to read this page: {detail}"),
+            Self::UnsupportedFormat { format } => {
+                write!(f, "{format} images are not supported in this build")
+            }
+            Self::Corrupted { detail } => write!(f, "This page seems to be corrupted: {detail}"),
+            Self::TooLarge { width, height } => {
+                write!(
+                    f,
+                    "This page is too large to be displayed ({width}x{height})"
+                )
+            }
+            Self::OutOfRange { page } => write!(f, "Page {} doesn't exist", page + 1),
+        }
+    }
+}
+
+impl PageError {
+    /// Check if loading the page again may succeed (e.g. after a network hiccup),
+    /// unlike when its data itself can't be decoded
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Io { .. })
+    }
+}
+
+impl std::error::Error for PageError {}
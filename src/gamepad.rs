@@ -0,0 +1,46 @@
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use gilrs::{Button, EventType, Gilrs};
+
+/// Start a thread listening to the buttons pressed on gamepads, including the ones connected later on
+///
+/// Presses are sent through the returned channel, along with the error preventing gamepads
+/// from being used (if any)
+pub fn spawn_gamepad_listener(ctx: egui::Context) -> Receiver<Result<Button, String>> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut gilrs = match Gilrs::new() {
+            Ok(gilrs) => gilrs,
+
+            // Gamepads aren't supported on this platform, so none will ever be connected
+            Err(gilrs::Error::NotImplemented(_)) => return,
+
+            Err(err) => {
+                let _ = sender.send(Err(format!("Gamepads can't be used: {err}")));
+                return;
+            }
+        };
+
+        loop {
+            let Some(event) = gilrs.next_event_blocking(None) else {
+                continue;
+            };
+
+            if let EventType::ButtonPressed(button, _) = event.event {
+                // Stop once the application is closed
+                if sender.send(Ok(button)).is_err() {
+                    return;
+                }
+
+                // The press is handled by the UI thread
+                ctx.request_repaint();
+            }
+        }
+    });
+
+    receiver
+}
@@ -2,6 +2,7 @@
 /// Useful for representing a list of loading values that's filled progressively
 pub struct GapVec<T> {
     items: Vec<Option<T>>,
+    filled: usize,
 }
 
 impl<T> GapVec<T> {
@@ -11,6 +12,7 @@ impl<T> GapVec<T> {
             items:
                 // TODO: find a more proper syntax
                 (0..size).map(|_| None).collect(),
+            filled: 0,
         }
     }
 
@@ -26,6 +28,40 @@ impl<T> GapVec<T> {
     /// Set the value at a provided index
     /// Panics if the index does not exist
     pub fn set(&mut self, index: usize, value: T) {
-        self.items[index] = Some(value);
+        if self.items[index].replace(value).is_none() {
+            self.filled += 1;
+        }
+    }
+
+    /// Remove the value at a provided index
+    /// Panics if the index does not exist
+    pub fn clear(&mut self, index: usize) {
+        self.take(index);
+    }
+
+    /// Remove the value at a provided index and return it
+    /// Panics if the index does not exist
+    pub fn take(&mut self, index: usize) -> Option<T> {
+        let value = self.items[index].take();
+
+        if value.is_some() {
+            self.filled -= 1;
+        }
+
+        value
+    }
+
+    /// Move the values to new indexes, each one providing the index its value comes from
+    /// Panics if one of the previous indexes does not exist
+    pub fn reorder(&mut self, previous_indexes: &[usize]) {
+        let mut items = std::mem::take(&mut self.items);
+
+        self.items = previous_indexes.iter().map(|&index| items[index].take()).collect();
+        self.filled = self.items.iter().filter(|item| item.is_some()).count();
+    }
+
+    /// Get the number of indexes which have a value
+    pub fn loaded_count(&self) -> usize {
+        self.filled
     }
 }
@@ -0,0 +1,602 @@
+use std::{borrow::Cow, collections::BTreeMap, fmt};
+
+use egui::{Event, InputState, Key, Modifiers};
+#[cfg(feature = "gamepad")]
+use gilrs::Button;
+use serde::{Deserialize, Serialize};
+
+/// Action which can be triggered with a keyboard shortcut
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    // Panning actions come first, as they prevent the page-turning ones bound
+    // to the same keys from turning the page until the pages' edge is reached
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    PreviousPage,
+    NextPage,
+    PreviousSinglePage,
+    NextSinglePage,
+    FirstPage,
+    LastPage,
+    JumpToPage,
+    GoBack,
+    PreviousItem,
+    NextItem,
+    PreviousChapter,
+    NextChapter,
+    ToggleOverview,
+    ToggleThumbnailStrip,
+    AddBookmark,
+    ListBookmarks,
+    Undo,
+    Redo,
+    ToggleDoublePage,
+    ShiftPairing,
+    ToggleRightToLeft,
+    RotateClockwise,
+    RotateCounterClockwise,
+    CycleFitMode,
+    CycleScalingFilter,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    CyclePagesCounter,
+    ToggleColorManagement,
+    ToggleTrimMargins,
+    CycleBackground,
+    ToggleInvertColors,
+    CycleBrightness,
+    CycleSortOrder,
+    ToggleSlideshow,
+    InspectPages,
+    ExportPage,
+    ExportSpread,
+    ToggleWatchChanges,
+    Reload,
+    DiscardCache,
+    ToggleDebugOverlay,
+    ToggleWindowed,
+    OpenFile,
+    OpenFolder,
+    RecentItems,
+    RevealItem,
+    TrashItem,
+    EditKeyBindings,
+    Help,
+    Exit,
+}
+
+impl Action {
+    /// All actions, in the order they are handled and listed
+    pub const ALL: &'static [Self] = &[
+        Self::PanLeft,
+        Self::PanRight,
+        Self::PanUp,
+        Self::PanDown,
+        Self::PreviousPage,
+        Self::NextPage,
+        Self::PreviousSinglePage,
+        Self::NextSinglePage,
+        Self::FirstPage,
+        Self::LastPage,
+        Self::JumpToPage,
+        Self::GoBack,
+        Self::PreviousItem,
+        Self::NextItem,
+        Self::PreviousChapter,
+        Self::NextChapter,
+        Self::ToggleOverview,
+        Self::ToggleThumbnailStrip,
+        Self::AddBookmark,
+        Self::ListBookmarks,
+        Self::Undo,
+        Self::Redo,
+        Self::ToggleDoublePage,
+        Self::ShiftPairing,
+        Self::ToggleRightToLeft,
+        Self::RotateClockwise,
+        Self::RotateCounterClockwise,
+        Self::CycleFitMode,
+        Self::CycleScalingFilter,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::ResetZoom,
+        Self::CyclePagesCounter,
+        Self::ToggleColorManagement,
+        Self::ToggleTrimMargins,
+        Self::CycleBackground,
+        Self::ToggleInvertColors,
+        Self::CycleBrightness,
+        Self::CycleSortOrder,
+        Self::ToggleSlideshow,
+        Self::InspectPages,
+        Self::ExportPage,
+        Self::ExportSpread,
+        Self::ToggleWatchChanges,
+        Self::Reload,
+        Self::DiscardCache,
+        Self::ToggleDebugOverlay,
+        Self::ToggleWindowed,
+        Self::OpenFile,
+        Self::OpenFolder,
+        Self::RecentItems,
+        Self::RevealItem,
+        Self::TrashItem,
+        Self::EditKeyBindings,
+        Self::Help,
+        Self::Exit,
+    ];
+
+    /// Describe what the action does
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::PanLeft => "Move the pages to the left, when they're larger than the window",
+            Self::PanRight => "Move the pages to the right, when they're larger than the window",
+            Self::PanUp => "Move the pages up, when they're larger than the window",
+            Self::PanDown => "Move the pages down, when they're larger than the window",
+            Self::PreviousPage => "Go to the previous page",
+            Self::NextPage => "Go to the next page",
+            Self::PreviousSinglePage => {
+                "Go to the previous page (one at a time in double page mode)"
+            }
+            Self::NextSinglePage => "Go to the next page (one at a time in double page mode)",
+            Self::FirstPage => "Go to the first page",
+            Self::LastPage => "Go to the last page",
+            Self::JumpToPage => "Jump to a specific page or chapter",
+            Self::GoBack => "Go back to the page displayed before the last jump",
+            Self::PreviousItem => "Open the previous item in the folder",
+            Self::NextItem => "Open the next item in the folder",
+            Self::PreviousChapter => "Go to the previous chapter",
+            Self::NextChapter => "Go to the next chapter",
+            Self::ToggleOverview => "Toggle the overview of all pages",
+            Self::ToggleThumbnailStrip => "Toggle the thumbnail strip",
+            Self::AddBookmark => "Bookmark the current page",
+            Self::ListBookmarks => "List the bookmarks of the current item",
+            Self::Undo => {
+                "Undo the last change to the current item's bookmarks, rotation, pairing or page"
+            }
+            Self::Redo => "Redo the last undone change",
+            Self::ToggleDoublePage => "Toggle double page mode",
+            Self::ShiftPairing => "Pair the pages with an offset of one in double page mode",
+            Self::ToggleRightToLeft => "Toggle right-to-left reading",
+            Self::RotateClockwise => "Rotate the pages clockwise",
+            Self::RotateCounterClockwise => "Rotate the pages counter-clockwise",
+            Self::CycleFitMode => "Cycle the fit mode",
+            Self::CycleScalingFilter => "Cycle the filter the pages are scaled with",
+            Self::ZoomIn => "Zoom in",
+            Self::ZoomOut => "Zoom out",
+            Self::ResetZoom => "Reset the zoom",
+            Self::CyclePagesCounter => "Cycle the pages counter display",
+            Self::ToggleColorManagement => "Toggle colour management",
+            Self::ToggleTrimMargins => "Toggle trimming the pages' margins",
+            Self::CycleBackground => "Cycle the background colour",
+            Self::ToggleInvertColors => "Toggle inverting the pages' colours",
+            Self::CycleBrightness => "Cycle the pages' brightness",
+            Self::CycleSortOrder => "Cycle the pages sort order",
+            Self::ToggleSlideshow => "Start or stop the slideshow",
+            Self::InspectPages => "Inspect the current item for corrupted pages",
+            Self::ExportPage => "Save the current page's file",
+            Self::ExportSpread => "Save the displayed pages as a single PNG image",
+            Self::ToggleWatchChanges => "Toggle reloading the current item when it changes on disk",
+            Self::Reload => "Reload the current item",
+            Self::DiscardCache => "Discard the loaded pages, to load them again from the disk",
+            Self::ToggleDebugOverlay => "Toggle the debug overlay",
+            Self::ToggleWindowed => "Switch between a borderless and a normal window",
+            Self::OpenFile => "Open a file",
+            Self::OpenFolder => "Open a folder",
+            Self::RecentItems => "List the recently opened items",
+            Self::RevealItem => {
+                "Show the current item (or the current page's file for folders) in the file manager"
+            }
+            Self::TrashItem => "Move the current item to the trash and open the next one",
+            Self::EditKeyBindings => "Change the keyboard shortcuts",
+            Self::Help => "Show the keyboard shortcuts",
+            Self::Exit => "Exit the application",
+        }
+    }
+
+    /// Get the keys the action is bound to by default
+    fn default_keys(self) -> Vec<KeyCombo> {
+        fn key(key: Key) -> KeyCombo {
+            KeyCombo {
+                key,
+                ctrl: false,
+                shift: false,
+                alt: false,
+            }
+        }
+
+        fn ctrl(key: Key) -> KeyCombo {
+            KeyCombo {
+                key,
+                ctrl: true,
+                shift: false,
+                alt: false,
+            }
+        }
+
+        fn shift(key: Key) -> KeyCombo {
+            KeyCombo {
+                key,
+                ctrl: false,
+                shift: true,
+                alt: false,
+            }
+        }
+
+        fn ctrl_shift(key: Key) -> KeyCombo {
+            KeyCombo {
+                key,
+                ctrl: true,
+                shift: true,
+                alt: false,
+            }
+        }
+
+        fn alt(key: Key) -> KeyCombo {
+            KeyCombo {
+                key,
+                ctrl: false,
+                shift: false,
+                alt: true,
+            }
+        }
+
+        match self {
+            Self::PanLeft => vec![key(Key::ArrowLeft)],
+            Self::PanRight => vec![key(Key::ArrowRight)],
+            Self::PanUp => vec![key(Key::ArrowUp)],
+            Self::PanDown => vec![key(Key::ArrowDown)],
+            Self::PreviousPage => vec![
+                key(Key::ArrowLeft),
+                key(Key::PageUp),
+                key(Key::Backspace),
+                shift(Key::Space),
+            ],
+            Self::NextPage => vec![key(Key::ArrowRight), key(Key::PageDown), key(Key::Space)],
+            Self::PreviousSinglePage => vec![
+                shift(Key::ArrowLeft),
+                shift(Key::PageUp),
+                shift(Key::Backspace),
+            ],
+            Self::NextSinglePage => vec![shift(Key::ArrowRight), shift(Key::PageDown)],
+            Self::FirstPage => vec![key(Key::Home)],
+            Self::LastPage => vec![key(Key::End)],
+            Self::JumpToPage => vec![key(Key::G)],
+            Self::GoBack => vec![alt(Key::ArrowLeft), key(Key::H)],
+            Self::PreviousItem => vec![ctrl(Key::ArrowLeft)],
+            Self::NextItem => vec![ctrl(Key::ArrowRight)],
+            Self::PreviousChapter => vec![ctrl(Key::PageUp)],
+            Self::NextChapter => vec![ctrl(Key::PageDown)],
+            Self::ToggleOverview => vec![key(Key::T), key(Key::Tab)],
+            Self::ToggleThumbnailStrip => vec![shift(Key::T)],
+            Self::AddBookmark => vec![key(Key::B)],
+            Self::ListBookmarks => vec![shift(Key::B)],
+            Self::Undo => vec![ctrl(Key::Z)],
+            Self::Redo => vec![ctrl_shift(Key::Z)],
+            Self::ToggleDoublePage => vec![key(Key::D)],
+            Self::ShiftPairing => vec![shift(Key::D)],
+            Self::ToggleRightToLeft => vec![key(Key::R)],
+            Self::RotateClockwise => vec![ctrl(Key::R)],
+            Self::RotateCounterClockwise => vec![ctrl_shift(Key::R)],
+            Self::CycleFitMode => vec![key(Key::F)],
+            Self::CycleScalingFilter => vec![shift(Key::F)],
+            // The plus key often requires Shift to be held
+            Self::ZoomIn => vec![key(Key::PlusEquals), shift(Key::PlusEquals)],
+            Self::ZoomOut => vec![key(Key::Minus)],
+            Self::ResetZoom => vec![key(Key::Num0)],
+            Self::CyclePagesCounter => vec![key(Key::I)],
+            Self::ToggleColorManagement => vec![key(Key::C)],
+            Self::ToggleTrimMargins => vec![key(Key::M)],
+            Self::CycleBackground => vec![shift(Key::C)],
+            Self::ToggleInvertColors => vec![key(Key::N)],
+            Self::CycleBrightness => vec![shift(Key::N)],
+            Self::CycleSortOrder => vec![key(Key::S)],
+            Self::ToggleSlideshow => vec![key(Key::P)],
+            Self::InspectPages => vec![key(Key::V)],
+            Self::ExportPage => vec![ctrl(Key::S)],
+            Self::ExportSpread => vec![ctrl_shift(Key::S)],
+            Self::ToggleWatchChanges => vec![key(Key::W)],
+            Self::Reload => vec![key(Key::F5)],
+            Self::DiscardCache => vec![ctrl(Key::F5)],
+            Self::ToggleDebugOverlay => vec![key(Key::F3)],
+            Self::ToggleWindowed => vec![key(Key::F11), alt(Key::Enter)],
+            Self::OpenFile => vec![ctrl(Key::O)],
+            Self::OpenFolder => vec![ctrl_shift(Key::O)],
+            Self::RecentItems => vec![ctrl(Key::E)],
+            Self::RevealItem => vec![ctrl_shift(Key::E)],
+            Self::TrashItem => vec![key(Key::Delete)],
+            Self::EditKeyBindings => vec![ctrl(Key::K)],
+            Self::Help => vec![key(Key::F1)],
+            Self::Exit => vec![key(Key::Escape)],
+        }
+    }
+
+    /// Get the gamepad buttons the action is bound to by default
+    #[cfg(feature = "gamepad")]
+    fn default_buttons(self) -> Vec<Button> {
+        match self {
+            Self::PanUp => vec![Button::DPadUp],
+            Self::PanDown => vec![Button::DPadDown],
+            Self::PreviousPage => vec![Button::LeftTrigger, Button::DPadLeft],
+            Self::NextPage => vec![Button::RightTrigger, Button::DPadRight],
+            Self::JumpToPage => vec![Button::Start],
+            Self::ToggleDoublePage => vec![Button::Select],
+            _ => vec![],
+        }
+    }
+
+    /// Check if the action is turning the page
+    pub fn is_page_turn(self) -> bool {
+        matches!(
+            self,
+            Self::PreviousPage | Self::NextPage | Self::PreviousSinglePage | Self::NextSinglePage
+        )
+    }
+
+    /// Get the action going the other way, for the page-turning and item-changing ones
+    fn mirrored(self) -> Self {
+        match self {
+            Self::PreviousPage => Self::NextPage,
+            Self::NextPage => Self::PreviousPage,
+            Self::PreviousSinglePage => Self::NextSinglePage,
+            Self::NextSinglePage => Self::PreviousSinglePage,
+            Self::PreviousItem => Self::NextItem,
+            Self::NextItem => Self::PreviousItem,
+            _ => self,
+        }
+    }
+
+    /// Check if the action is panning the pages
+    fn is_pan(self) -> bool {
+        matches!(
+            self,
+            Self::PanLeft | Self::PanRight | Self::PanUp | Self::PanDown
+        )
+    }
+}
+
+/// A key to press, along with the modifiers to hold
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct KeyCombo {
+    pub key: Key,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl KeyCombo {
+    /// Get the combo corresponding to a key pressed with some modifiers
+    pub fn pressed(key: Key, modifiers: Modifiers) -> Self {
+        Self {
+            key,
+            ctrl: modifiers.ctrl,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+        }
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+
+        write!(f, "{}", self.key.name())
+    }
+}
+
+/// Inputs (keys or gamepad buttons) bound to each action
+/// Only the bindings changed by the user are stored, the other actions using their default ones
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct Bindings<T>(BTreeMap<Action, Vec<T>>);
+
+impl<T> Default for Bindings<T> {
+    fn default() -> Self {
+        Self(BTreeMap::new())
+    }
+}
+
+impl<T: Clone + PartialEq> Bindings<T> {
+    /// Get the inputs an action is bound to
+    fn get(&self, action: Action, defaults: fn(Action) -> Vec<T>) -> Cow<'_, [T]> {
+        match self.0.get(&action) {
+            Some(inputs) => Cow::Borrowed(inputs),
+            None => Cow::Owned(defaults(action)),
+        }
+    }
+
+    /// Bind an action to an input (in addition to its current ones)
+    fn add(&mut self, action: Action, input: T, defaults: fn(Action) -> Vec<T>) {
+        let inputs = self.0.entry(action).or_insert_with(|| defaults(action));
+
+        if !inputs.contains(&input) {
+            inputs.push(input);
+        }
+    }
+
+    /// Unbind an action from an input
+    fn remove(&mut self, action: Action, input: &T, defaults: fn(Action) -> Vec<T>) {
+        self.0
+            .entry(action)
+            .or_insert_with(|| defaults(action))
+            .retain(|c_input| c_input != input);
+    }
+
+    /// Get the actions bound to an input
+    fn actions(&self, input: &T, defaults: fn(Action) -> Vec<T>) -> Vec<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .filter(|action| self.get(*action, defaults).contains(input))
+            .collect()
+    }
+
+    /// List the inputs bound to several actions at once
+    /// Panning and page-turning actions can share inputs, as the pages are only turned once
+    /// they can't be panned anymore
+    fn conflicts(&self, defaults: fn(Action) -> Vec<T>) -> Vec<(T, Action, Action)> {
+        let mut conflicts = vec![];
+
+        for (index, action) in Action::ALL.iter().copied().enumerate() {
+            for other in Action::ALL[index + 1..].iter().copied() {
+                if action.is_pan() && other.is_page_turn() {
+                    continue;
+                }
+
+                for input in self.get(action, defaults).iter() {
+                    if self.get(other, defaults).contains(input) {
+                        conflicts.push((input.clone(), action, other));
+                    }
+                }
+            }
+        }
+
+        conflicts
+    }
+}
+
+/// Keys and gamepad buttons bound to each action
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct KeyBindings {
+    keys: Bindings<KeyCombo>,
+    #[cfg(feature = "gamepad")]
+    buttons: Bindings<Button>,
+}
+
+impl KeyBindings {
+    /// Get the keys an action is bound to
+    pub fn keys(&self, action: Action) -> Cow<'_, [KeyCombo]> {
+        self.keys.get(action, Action::default_keys)
+    }
+
+    /// Get the gamepad buttons an action is bound to
+    #[cfg(feature = "gamepad")]
+    pub fn buttons(&self, action: Action) -> Cow<'_, [Button]> {
+        self.buttons.get(action, Action::default_buttons)
+    }
+
+    /// Get the names of the keys and gamepad buttons an action is bound to
+    pub fn input_names(&self, action: Action) -> Vec<String> {
+        let keys = self.keys(action);
+        let keys = keys.iter().map(ToString::to_string);
+
+        #[cfg(feature = "gamepad")]
+        let buttons = self.buttons(action);
+        #[cfg(feature = "gamepad")]
+        let keys = keys.chain(buttons.iter().map(|button| format!("{button:?}")));
+
+        keys.collect()
+    }
+
+    /// Bind an action to a key (in addition to its current ones)
+    pub fn add_key(&mut self, action: Action, combo: KeyCombo) {
+        self.keys.add(action, combo, Action::default_keys);
+    }
+
+    /// Bind an action to a gamepad button (in addition to its current ones)
+    #[cfg(feature = "gamepad")]
+    pub fn add_button(&mut self, action: Action, button: Button) {
+        self.buttons.add(action, button, Action::default_buttons);
+    }
+
+    /// Unbind an action from a key
+    /// Actions without any key can't be triggered from the keyboard anymore
+    pub fn remove_key(&mut self, action: Action, combo: KeyCombo) {
+        self.keys.remove(action, &combo, Action::default_keys);
+    }
+
+    /// Unbind an action from a gamepad button
+    #[cfg(feature = "gamepad")]
+    pub fn remove_button(&mut self, action: Action, button: Button) {
+        self.buttons
+            .remove(action, &button, Action::default_buttons);
+    }
+
+    /// Bind an action to its default keys and buttons again
+    pub fn reset(&mut self, action: Action) {
+        self.keys.0.remove(&action);
+
+        #[cfg(feature = "gamepad")]
+        self.buttons.0.remove(&action);
+    }
+
+    /// Get the actions triggered by each key pressed during the frame, and whether it was repeated
+    ///
+    /// If `mirrored` is set, the horizontal arrow keys go the other way (panning excepted)
+    pub fn triggered(&self, i: &InputState, mirrored: bool) -> Vec<(Vec<Action>, bool)> {
+        i.events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Key {
+                    key,
+                    pressed: true,
+                    repeat,
+                    modifiers,
+                } => Some((KeyCombo::pressed(*key, *modifiers), *repeat)),
+                _ => None,
+            })
+            .map(|(combo, repeat)| {
+                let actions = self.keys.actions(&combo, Action::default_keys);
+                let mirror = mirrored && matches!(combo.key, Key::ArrowLeft | Key::ArrowRight);
+
+                (mirror_actions(actions, mirror), repeat)
+            })
+            .collect()
+    }
+
+    /// Get the actions triggered by a gamepad button
+    ///
+    /// If `mirrored` is set, the horizontal D-pad buttons go the other way (panning excepted)
+    #[cfg(feature = "gamepad")]
+    pub fn triggered_by_button(&self, button: Button, mirrored: bool) -> Vec<Action> {
+        let actions = self.buttons.actions(&button, Action::default_buttons);
+        let mirror = mirrored && matches!(button, Button::DPadLeft | Button::DPadRight);
+
+        mirror_actions(actions, mirror)
+    }
+
+    /// List the keys and buttons bound to several actions at once
+    pub fn conflicts(&self) -> Vec<(String, Action, Action)> {
+        let keys = self
+            .keys
+            .conflicts(Action::default_keys)
+            .into_iter()
+            .map(|(combo, action, other)| (combo.to_string(), action, other));
+
+        #[cfg(feature = "gamepad")]
+        let keys = keys.chain(
+            self.buttons
+                .conflicts(Action::default_buttons)
+                .into_iter()
+                .map(|(button, action, other)| (format!("{button:?}"), action, other)),
+        );
+
+        keys.collect()
+    }
+}
+
+/// Make the provided actions go the other way, if `mirror` is set
+fn mirror_actions(actions: Vec<Action>, mirror: bool) -> Vec<Action> {
+    if mirror {
+        actions.into_iter().map(Action::mirrored).collect()
+    } else {
+        actions
+    }
+}
@@ -0,0 +1,19 @@
+#![forbid(unsafe_code)]
+#![forbid(unused_must_use)]
+#![warn(unused_crate_dependencies)]
+
+//! Image sources and decoders of the reader, which can be used to load pages without its interface
+//!
+//! New kinds of sources can be registered in [`sources::SOURCE_REGISTRY`] by implementing [`sources::SourceFactory`]
+
+pub mod decoders;
+pub mod errors;
+pub mod sources;
+
+// Dependencies only used by the application itself
+use eframe as _;
+use egui as _;
+#[cfg(feature = "gamepad")]
+use gilrs as _;
+use num_cpus as _;
+use rfd as _;
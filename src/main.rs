@@ -6,14 +6,39 @@
 
 use std::path::PathBuf;
 
-mod decoders;
+#[cfg(feature = "gamepad")]
+mod gamepad;
 mod gap_vec;
+mod keybindings;
+mod metrics;
+mod reveal;
 mod settings;
-mod sources;
+mod stats;
+mod trash;
 mod ui;
+mod watcher;
 
 use eframe::NativeOptions;
 use once_cell::sync::Lazy;
+use reader::{decoders, errors, sources};
+
+// Dependencies only used by the library
+use crc32fast as _;
+use encoding_rs as _;
+#[cfg(feature = "pdf")]
+use hayro as _;
+use image as _;
+use qcms as _;
+use resvg as _;
+use roxmltree as _;
+use tar as _;
+#[cfg(feature = "rar")]
+use unrar as _;
+#[cfg(feature = "remote")]
+use ureq as _;
+use zip_next as _;
+use zune_jpeg as _;
+use zune_png as _;
 
 use self::ui::{app::ReaderApp, show_err_dialog};
 
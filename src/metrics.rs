@@ -0,0 +1,84 @@
+use std::{collections::VecDeque, time::Duration};
+
+/// Number of pages for which timings are kept
+const TIMINGS_CAPACITY: usize = 16;
+
+/// Time spent on the different steps required to display a page
+#[derive(Clone, Copy)]
+pub struct PageTimings {
+    pub page: usize,
+
+    /// Time spent reading the page from its source
+    pub load: Option<Duration>,
+
+    /// Time spent decoding the page's image
+    pub decode: Option<Duration>,
+
+    /// Time spent uploading the decoded image to the GPU
+    pub upload: Option<Duration>,
+}
+
+/// Performance metrics, displayed in the debug overlay
+pub struct Metrics {
+    /// Timings of the most recently handled pages (oldest first)
+    timings: VecDeque<PageTimings>,
+
+    /// Number of times a displayed page was found in the retained textures
+    pub cache_hits: usize,
+
+    /// Number of times a displayed page had to be decoded and uploaded again
+    pub cache_misses: usize,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            timings: VecDeque::with_capacity(TIMINGS_CAPACITY),
+            cache_hits: 0,
+            cache_misses: 0,
+        }
+    }
+
+    /// Register the time spent reading a page from its source
+    pub fn record_load(&mut self, page: usize, duration: Duration) {
+        self.timings_mut(page).load = Some(duration);
+    }
+
+    /// Register the time spent decoding a page
+    pub fn record_decode(&mut self, page: usize, duration: Duration) {
+        self.timings_mut(page).decode = Some(duration);
+    }
+
+    /// Register the time spent uploading a page to the GPU
+    pub fn record_upload(&mut self, page: usize, duration: Duration) {
+        self.timings_mut(page).upload = Some(duration);
+    }
+
+    /// Timings of the most recently handled pages (oldest first)
+    pub fn timings(&self) -> impl DoubleEndedIterator<Item = &PageTimings> {
+        self.timings.iter()
+    }
+
+    /// Get the timings of a page, creating them (and forgetting the oldest ones) if required
+    fn timings_mut(&mut self, page: usize) -> &mut PageTimings {
+        let index = match self.timings.iter().position(|timings| timings.page == page) {
+            Some(index) => index,
+            None => {
+                if self.timings.len() == TIMINGS_CAPACITY {
+                    self.timings.pop_front();
+                }
+
+                self.timings.push_back(PageTimings {
+                    page,
+                    load: None,
+                    decode: None,
+                    upload: None,
+                });
+
+                self.timings.len() - 1
+            }
+        };
+
+        &mut self.timings[index]
+    }
+}
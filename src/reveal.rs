@@ -0,0 +1,103 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+
+/// Show a file (or a folder) in the platform's file manager, selected if supported
+/// Platforms where it can't be selected open its parent directory instead
+pub fn reveal(path: &Path) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Failed to find {}", path.display()))?;
+
+    if select(&path) {
+        return Ok(());
+    }
+
+    let parent = path.parent().unwrap_or(&path);
+
+    open(parent).with_context(|| format!("Failed to open {}", parent.display()))
+}
+
+/// Show an item selected in the file manager
+/// Returns `false` if it's not supported on this platform, or if it failed
+#[cfg(target_os = "windows")]
+fn select(path: &Path) -> bool {
+    use std::os::windows::process::CommandExt;
+
+    // Explorer expects the path right after the comma, quoted if it contains spaces
+    Command::new("explorer")
+        .raw_arg(format!("/select,\"{}\"", path.display()))
+        .spawn()
+        .is_ok()
+}
+
+#[cfg(target_os = "macos")]
+fn select(path: &Path) -> bool {
+    Command::new("open").arg("-R").arg(path).spawn().is_ok()
+}
+
+/// File managers implementing the freedesktop.org interface (e.g. Nautilus, Dolphin, Nemo)
+/// can select an item, others can only open its parent directory
+#[cfg(all(unix, not(target_os = "macos")))]
+fn select(path: &Path) -> bool {
+    Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+        ])
+        .arg(format!("array:string:{}", file_uri(path)))
+        .arg("string:")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn select(_path: &Path) -> bool {
+    false
+}
+
+/// Open a directory in the file manager
+fn open(dir: &Path) -> Result<()> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    Command::new(program)
+        .arg(dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    Ok(())
+}
+
+/// Get the `file://` URI of an absolute path
+#[cfg(all(unix, not(target_os = "macos")))]
+fn file_uri(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut uri = String::from("file://");
+
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    uri
+}
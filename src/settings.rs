@@ -1,20 +1,262 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    keybindings::KeyBindings,
+    sources::{SortOrder, ZipNamesEncoding},
+    LOGICAL_CORES,
+};
+
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub right_to_left: bool,
+    /// In right-to-left mode, make the left arrow key (and the other horizontal inputs) go to
+    /// the next page, as the pages are laid out from right to left
+    pub invert_keys_in_rtl: bool,
     pub double_page: bool,
-    pub display_pages_number: bool,
+    pub pages_counter: PagesCounter,
+    /// Corner of the window the pages counter is displayed in
+    pub counter_corner: Corner,
+    /// Size of the pages counter's text
+    pub counter_font_size: f32,
+    /// Opacity of the pages counter's background, between 0 and 1
+    pub counter_background_opacity: f32,
+    /// Only display the pages counter for a moment after the page changes
+    pub counter_auto_hide: bool,
     pub display_first_page_in_single_mode: bool,
+    /// Pages whose width divided by their height exceeds this ratio are displayed alone in double
+    /// page mode, as they are double-page spreads scanned as a single image (never if not set)
+    pub wide_page_ratio: Option<f32>,
+    /// Split the wide pages in two halves, read one after the other, when a single page is displayed at a time
+    pub split_wide_pages: bool,
+    pub color_manage: bool,
+    /// Remove the near-uniform margins around the pages (e.g. the white borders of scans)
+    pub trim_margins: bool,
+    /// Maximum difference of luminance (0-255) with the margins' colour for a pixel to be part of them
+    pub trim_threshold: u8,
+    /// Colour of the area around the pages (red, green and blue)
+    pub background: [u8; 3],
+    /// Invert the pages' colours, e.g. to read white pages at night
+    pub invert_colors: bool,
+    /// Multiplier applied to the pages' colours to dim them, between 0 and 1
+    pub brightness: f32,
+    /// Pages larger than this (on any side) are downscaled when decoded
+    pub max_decode_dimension: Option<usize>,
+    /// Names of the image sources that shouldn't be used to open items
+    pub disabled_sources: Vec<String>,
+    /// Maximum number of threads used to decode the page being displayed
+    /// Determined from the number of logical cores if not set
+    pub decode_threads: Option<usize>,
+    /// Display timings of the last decoded pages on top of them
+    pub debug_overlay: bool,
+    /// Resolution PDF pages are rasterised at (pages made of a single image are displayed as-is)
+    pub pdf_render_dpi: usize,
+    /// Order the pages of directories and archives are sorted in
+    pub page_sort: SortOrder,
+    /// Encoding of the files' names in ZIP archives that don't use UTF-8
+    pub zip_names_encoding: ZipNamesEncoding,
+    /// Reload the opened item automatically when it changes on disk
+    pub watch_changes: bool,
+    /// Check all the pages of an item for corruption when it's opened
+    pub inspect_on_open: bool,
+    /// Keep the zoom level when changing pages, instead of fitting the next ones to the window
+    pub keep_zoom: bool,
+    /// How the pages are scaled to the window
+    pub fit_mode: FitMode,
+    /// Filter used to scale the pages' pixels
+    pub scaling_filter: ScalingFilter,
+    /// Display a strip with the thumbnails of the pages around the current one
+    pub thumbnail_strip: bool,
+    /// Display a status bar with details about the current page when the mouse gets close to the bottom of the window
+    pub status_bar: bool,
+    /// Open the next item of the directory when going past the last page (and the previous one
+    /// when going before the first page)
+    pub auto_advance_items: bool,
+    /// Go back to the first page when going past the last one (and the other way around)
+    pub wrap_navigation: bool,
+    /// Time each page is displayed for during a slideshow, in seconds
+    pub slideshow_delay_secs: u64,
+    /// Ask for a confirmation before exiting the application with Escape while an item is opened
+    pub confirm_exit: bool,
+    /// Display the application in a normal window, instead of a borderless maximised one
+    pub windowed: bool,
+    /// Position and size of the normal window, remembered when switching to the borderless one
+    pub window_geometry: Option<WindowGeometry>,
+    /// Hide the mouse cursor after it wasn't used for this number of seconds (never if 0)
+    pub hide_cursor_delay_secs: u64,
+    /// Distance to scroll for (in a single gesture) to turn the page
+    pub scroll_threshold: f32,
+    /// Scroll the pages and turn them the other way
+    pub invert_scroll: bool,
+    /// Keys bound to each action (only the ones changed by the user)
+    pub key_bindings: KeyBindings,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             right_to_left: false,
+            invert_keys_in_rtl: true,
             double_page: false,
-            display_pages_number: true,
+            pages_counter: PagesCounter::Pages,
+            counter_corner: Corner::TopRight,
+            counter_font_size: 18.0,
+            counter_background_opacity: 1.0,
+            counter_auto_hide: false,
             display_first_page_in_single_mode: true,
+            wide_page_ratio: Some(1.0),
+            split_wide_pages: false,
+            color_manage: false,
+            trim_margins: false,
+            trim_threshold: 24,
+            background: BACKGROUND_PRESETS[1].1,
+            invert_colors: false,
+            brightness: 1.0,
+            max_decode_dimension: None,
+            disabled_sources: vec![],
+            decode_threads: None,
+            debug_overlay: false,
+            pdf_render_dpi: 150,
+            page_sort: SortOrder::Natural,
+            zip_names_encoding: ZipNamesEncoding::Cp437,
+            watch_changes: false,
+            inspect_on_open: false,
+            keep_zoom: false,
+            fit_mode: FitMode::FitPage,
+            scaling_filter: ScalingFilter::Linear,
+            thumbnail_strip: false,
+            status_bar: false,
+            auto_advance_items: false,
+            wrap_navigation: false,
+            slideshow_delay_secs: 5,
+            confirm_exit: false,
+            windowed: false,
+            window_geometry: None,
+            hide_cursor_delay_secs: 2,
+            scroll_threshold: 50.0,
+            invert_scroll: false,
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Get the number of threads to decode the page being displayed with
+    pub fn decode_threads(&self) -> usize {
+        // We keep half of the cores for the loading threads and the UI
+        self.decode_threads.unwrap_or(*LOGICAL_CORES / 2).max(1)
+    }
+}
+
+/// Position and size of a window (in points)
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct WindowGeometry {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Corner of the window
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Background colours which can be cycled through, along with their name
+pub const BACKGROUND_PRESETS: &[(&str, [u8; 3])] = &[
+    ("black", [0, 0, 0]),
+    ("dark gray", [27, 27, 27]),
+    ("white", [255, 255, 255]),
+];
+
+/// Get the background preset coming after the provided colour (cycles back to the first one)
+/// Custom colours are followed by the first preset
+pub fn next_background(background: [u8; 3]) -> (&'static str, [u8; 3]) {
+    let index = BACKGROUND_PRESETS
+        .iter()
+        .position(|(_, color)| *color == background)
+        .map_or(0, |index| (index + 1) % BACKGROUND_PRESETS.len());
+
+    BACKGROUND_PRESETS[index]
+}
+
+/// How pages are scaled to the window
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FitMode {
+    /// Display the pages entirely, so landscape pages don't overflow the window
+    FitPage,
+    /// Fit the pages to the window's height
+    FitHeight,
+    /// Fit the pages to the window's width (useful for tall pages, e.g. webtoons)
+    FitWidth,
+    /// Display one pixel of the pages per pixel of the screen
+    Original,
+}
+
+impl FitMode {
+    /// Get the next fit mode (cycles back to the first one)
+    pub fn next(self) -> Self {
+        match self {
+            Self::FitPage => Self::FitHeight,
+            Self::FitHeight => Self::FitWidth,
+            Self::FitWidth => Self::Original,
+            Self::Original => Self::FitPage,
+        }
+    }
+}
+
+/// Filter used to scale the pages' pixels
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ScalingFilter {
+    /// Interpolate between the pixels, which smoothes them
+    Linear,
+    /// Use the nearest pixel, which keeps the pixels sharp (e.g. for pixel art)
+    Nearest,
+    /// Use the nearest pixel for pages enlarged more than twice, interpolate otherwise
+    Auto,
+}
+
+impl ScalingFilter {
+    /// Get the next filter (cycles back to the first one)
+    pub fn next(self) -> Self {
+        match self {
+            Self::Linear => Self::Nearest,
+            Self::Nearest => Self::Auto,
+            Self::Auto => Self::Linear,
+        }
+    }
+
+    /// Get a human-readable description of the filter
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Nearest => "nearest-neighbour",
+            Self::Auto => "automatic",
+        }
+    }
+}
+
+/// What to display in the pages counter overlay
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PagesCounter {
+    Hidden,
+    Pages,
+    PagesAndRemaining,
+    PagesRemainingAndEta,
+}
+
+impl PagesCounter {
+    /// Get the next display mode (cycles back to the first one)
+    pub fn next(self) -> Self {
+        match self {
+            Self::Hidden => Self::Pages,
+            Self::Pages => Self::PagesAndRemaining,
+            Self::PagesAndRemaining => Self::PagesRemainingAndEta,
+            Self::PagesRemainingAndEta => Self::Hidden,
         }
     }
 }
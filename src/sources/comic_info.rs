@@ -0,0 +1,80 @@
+use roxmltree::{Document, Node};
+
+use super::Metadata;
+
+/// Name of the file describing a comic book, as used by ComicRack and most tagging tools
+pub const COMIC_INFO_FILE_NAME: &str = "ComicInfo.xml";
+
+/// Content of a `ComicInfo.xml` file
+pub struct ComicInfo {
+    pub metadata: Metadata,
+
+    /// Indexes of the pages (in the book's images) in reading order, if specified
+    pub pages: Option<Vec<usize>>,
+
+    /// Indexes of the images that must not be displayed
+    pub deleted_pages: Vec<usize>,
+}
+
+impl ComicInfo {
+    /// Parse a `ComicInfo.xml` file
+    /// Returns `None` if the file is invalid
+    pub fn parse(content: &str) -> Option<Self> {
+        let document = Document::parse(content).ok()?;
+        let root = document.root_element();
+
+        if !root.has_tag_name("ComicInfo") {
+            return None;
+        }
+
+        let metadata = Metadata {
+            title: child_text(root, "Title"),
+            series: child_text(root, "Series"),
+            right_to_left: child_text(root, "Manga").and_then(|manga| match manga.as_str() {
+                "YesAndRightToLeft" => Some(true),
+                "No" => Some(false),
+                // Mangas are not always read from right to left (e.g. when translated)
+                _ => None,
+            }),
+            reading_order: None,
+        };
+
+        let mut pages = vec![];
+        let mut deleted_pages = vec![];
+
+        for page in root
+            .children()
+            .filter(|node| node.has_tag_name("Pages"))
+            .flat_map(|pages| pages.children())
+            .filter(|node| node.has_tag_name("Page"))
+        {
+            let Some(image) = page.attribute("Image").and_then(|image| image.parse().ok()) else {
+                continue;
+            };
+
+            // Deleted pages are still part of the archive, but must not be displayed
+            if page.attribute("Type") == Some("Deleted") {
+                deleted_pages.push(image);
+            } else {
+                pages.push(image);
+            }
+        }
+
+        Some(Self {
+            metadata,
+            pages: (!pages.is_empty()).then_some(pages),
+            deleted_pages,
+        })
+    }
+}
+
+/// Get the (trimmed) text of a node's child, if it isn't empty
+fn child_text(node: Node, tag_name: &str) -> Option<String> {
+    let text = node
+        .children()
+        .find(|child| child.has_tag_name(tag_name))?
+        .text()?
+        .trim();
+
+    (!text.is_empty()).then(|| text.to_owned())
+}
@@ -1,11 +1,14 @@
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-use anyhow::{bail, Result};
+use anyhow::Result;
 
-use super::ImageSource;
+use crate::errors::PageError;
+
+use super::{ImageSource, SkippedPages};
 
 /// An empty set of images
 /// Useful when no real source is opened
+#[derive(Default)]
 pub struct EmptySource;
 
 impl EmptySource {
@@ -15,32 +18,23 @@ impl EmptySource {
 }
 
 impl ImageSource for EmptySource {
-    fn item_matches(_: &Path) -> bool
-    where
-        Self: Sized,
-    {
-        false
+    fn total_pages(&self) -> usize {
+        0
     }
 
-    fn load(_: &Path) -> Result<Self>
-    where
-        Self: Sized,
-    {
-        bail!("Empty sources cannot be loaded from a path");
+    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError> {
+        Err(PageError::OutOfRange { page })
     }
 
-    fn total_pages(&self) -> usize {
-        0
+    fn quick_clone(&self) -> Result<Box<dyn ImageSource>> {
+        Ok(Box::new(Self))
     }
 
-    fn load_page(&mut self, _: usize) -> Result<(PathBuf, Vec<u8>), String> {
-        Err("Cannot load any page from an empty source".to_owned())
+    fn skipped_pages(&self) -> SkippedPages {
+        SkippedPages::default()
     }
 
-    fn quick_clone(&self) -> Result<Box<dyn ImageSource>>
-    where
-        Self: Sized,
-    {
-        Ok(Box::new(Self))
+    fn page_name(&self, _page: usize) -> Option<String> {
+        None
     }
 }
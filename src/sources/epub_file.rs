@@ -0,0 +1,272 @@
+use std::{collections::HashMap, io, path::Path};
+
+use anyhow::{Context, Result};
+use roxmltree::{Document, ParsingOptions};
+use zip_next::ZipArchive;
+
+use crate::decoders::{is_image_supported, unsupported_image_format};
+
+use super::{
+    is_junk_file, shared_file::SharedFile, zip_file::ZipFile, ImageSource, Probe, SkippedPages,
+    SourceFactory,
+};
+
+/// Factory for EPUB books
+///
+/// EPUB books are ZIP archives, so they are handled by [`ZipFile`], with the pages
+/// ordered following the book's spine instead of the files' names
+pub struct EpubFileFactory;
+
+impl SourceFactory for EpubFileFactory {
+    fn name(&self) -> &'static str {
+        "EPUB book"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["epub"]
+    }
+
+    fn probe(&self, path: &Path) -> Probe {
+        if !path.is_file() {
+            return Probe::Decline("not a file".to_owned());
+        }
+
+        let Some(ext) = path.extension() else {
+            return Probe::Decline("file has no extension".to_owned());
+        };
+
+        if self
+            .extensions()
+            .iter()
+            .any(|c_ext| ext.eq_ignore_ascii_case(c_ext))
+        {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline(format!(
+                "extension is not one of: {}",
+                self.extensions().join(", ")
+            ))
+        }
+    }
+
+    fn probe_content(&self, header: &[u8]) -> Probe {
+        // EPUB books are ZIP archives starting with an uncompressed file indicating their type
+        if header.starts_with(b"PK\x03\x04")
+            && header.get(30..58) == Some(b"mimetypeapplication/epub+zip")
+        {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline("not an EPUB book".to_owned())
+        }
+    }
+
+    fn load(&self, path: &Path) -> Result<Box<dyn ImageSource>> {
+        Ok(Box::new(load_epub(path)?))
+    }
+}
+
+type Archive = ZipArchive<SharedFile>;
+
+/// Load an EPUB book
+///
+/// Each item of the spine is resolved to the image it displays. If this fails, all images
+/// of the book are used in the order they are stored in
+fn load_epub(path: &Path) -> Result<ZipFile> {
+    let file = SharedFile::open(path).context("Failed to open book file")?;
+
+    let mut archive = ZipArchive::new(file).context("Failed to open book content")?;
+
+    let mut file_indexes = HashMap::new();
+    let mut images = vec![];
+    let mut skipped_pages = SkippedPages::default();
+
+    for i in 0..archive.len() {
+        let item = archive
+            .by_index_raw(i)
+            .context("Failed to read file in book")?;
+
+        if !item.is_file() {
+            continue;
+        }
+
+        let name = item.name().to_owned();
+
+        if is_junk_file(Path::new(&name)) {
+            // Junk files can't be part of the spine either
+            continue;
+        } else if is_image_supported(Path::new(&name)) {
+            images.push(i);
+        } else if let Some(format) = unsupported_image_format(Path::new(&name)) {
+            skipped_pages.add(format);
+        }
+
+        file_indexes.insert(name, i);
+    }
+
+    let page_file_indexes = match spine_images(&mut archive, &file_indexes) {
+        Some(spine_images) if !spine_images.is_empty() => spine_images,
+        _ => images,
+    };
+
+    ZipFile::with_pages(archive, page_file_indexes, skipped_pages)
+}
+
+/// Get the images displayed by the items of the book's spine (= its reading order)
+fn spine_images(
+    archive: &mut Archive,
+    file_indexes: &HashMap<String, usize>,
+) -> Option<Vec<usize>> {
+    let container = read_text(archive, "META-INF/container.xml")?;
+    let container = parse_xml(&container)?;
+
+    // The package document describes the book's content
+    let package_path = container
+        .descendants()
+        .find(|node| node.has_tag_name("rootfile"))?
+        .attribute("full-path")?
+        .to_owned();
+
+    let package = read_text(archive, &package_path)?;
+    let package = parse_xml(&package)?;
+
+    let manifest = package
+        .descendants()
+        .filter(|node| node.has_tag_name("item"))
+        .filter_map(|node| {
+            Some((
+                node.attribute("id")?,
+                (
+                    node.attribute("href")?,
+                    node.attribute("media-type").unwrap_or_default(),
+                ),
+            ))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut images = vec![];
+
+    for item_ref in package
+        .descendants()
+        .filter(|node| node.has_tag_name("itemref"))
+    {
+        let Some((href, media_type)) = item_ref.attribute("idref").and_then(|id| manifest.get(id))
+        else {
+            continue;
+        };
+
+        let item_path = resolve_href(&package_path, href);
+
+        // Spine items are usually documents displaying a single image, but may be images too
+        let image_path = if media_type.starts_with("image/") {
+            Some(item_path)
+        } else {
+            first_image_of_document(archive, &item_path)
+        };
+
+        if let Some(image_path) = image_path {
+            if let Some(index) = file_indexes.get(&image_path) {
+                if is_image_supported(Path::new(&image_path)) {
+                    images.push(*index);
+                }
+            }
+        }
+    }
+
+    Some(images)
+}
+
+/// Get the path of the first image displayed by an (X)HTML document
+fn first_image_of_document(archive: &mut Archive, path: &str) -> Option<String> {
+    let content = read_text(archive, path)?;
+
+    let document = parse_xml(&content)?;
+
+    // Images are either HTML images or SVG ones, the latter being common in fixed-layout books
+    let href = document.descendants().find_map(|node| {
+        if node.has_tag_name("img") {
+            node.attribute("src")
+        } else if node.has_tag_name("image") {
+            node.attributes()
+                .find(|attr| attr.name() == "href")
+                .map(|attr| attr.value())
+        } else {
+            None
+        }
+    })?;
+
+    Some(resolve_href(path, href))
+}
+
+/// Parse an XML document
+/// Documents may have a DTD, especially XHTML ones
+fn parse_xml(content: &str) -> Option<Document<'_>> {
+    Document::parse_with_options(
+        content,
+        ParsingOptions {
+            allow_dtd: true,
+            ..ParsingOptions::default()
+        },
+    )
+    .ok()
+}
+
+/// Read a text file from the book
+fn read_text(archive: &mut Archive, path: &str) -> Option<String> {
+    io::read_to_string(archive.by_name(path).ok()?).ok()
+}
+
+/// Resolve a link found in a file to the path of the file it points to, inside the book
+fn resolve_href(from: &str, href: &str) -> String {
+    // The fragment doesn't matter, as we only need the file
+    let href = href.split('#').next().unwrap_or_default();
+    let href = percent_decode(href);
+
+    let mut components = from.split('/').map(str::to_owned).collect::<Vec<_>>();
+
+    // Links are relative to the directory of the file they are in
+    components.pop();
+
+    if href.starts_with('/') {
+        components.clear();
+    }
+
+    for component in href.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component.to_owned()),
+        }
+    }
+
+    components.join("/")
+}
+
+/// Decode the percent-encoded characters of a link (e.g. "%20" for a space)
+fn percent_decode(href: &str) -> String {
+    let bytes = href.as_bytes();
+
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let value = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+
+        match value {
+            Some(value) => {
+                decoded.push(value);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
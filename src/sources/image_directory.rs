@@ -1,73 +1,417 @@
 use std::{
+    collections::HashSet,
     fs,
     path::{Path, PathBuf},
+    time::UNIX_EPOCH,
 };
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
-use crate::decoders::is_image_supported;
+use crate::{
+    decoders::{is_image_supported, unsupported_image_format},
+    errors::PageError,
+};
+
+use super::{
+    is_junk_file,
+    natural_sort::natural_cmp,
+    page_order::{sort_pages, sort_pages_by_chapter, SortKey},
+    reading_order::{ReadingOrder, READING_ORDER_FILE_NAMES},
+    Chapter, ImageSource, Metadata, Probe, SkippedPages, SortOrder, SourceFactory,
+};
 
-use super::ImageSource;
+/// Maximum depth of the subdirectories images are looked for in
+pub const MAX_DEPTH: usize = 8;
 
 /// Handler for directory of images
+///
+/// Images in subdirectories are included as well, each directory being a chapter
+/// When opening a single image, its whole directory is opened at this image instead
 #[derive(Clone)]
 pub struct ImageDirectory {
-    image_files: Vec<PathBuf>,
+    image_files: Vec<ImageFile>,
+    chapters: Vec<Chapter>,
+    skipped_pages: SkippedPages,
+    initial_page: usize,
+    /// Symbolic links whose target doesn't exist, relative to the opened directory
+    broken_links: Vec<PathBuf>,
+    /// Manifest the pages are ordered by, in which case they can't be sorted
+    reading_order: Option<ReadingOrder>,
 }
 
-impl ImageSource for ImageDirectory {
-    fn item_matches(path: &Path) -> bool
-    where
-        Self: Sized,
-    {
-        path.is_dir()
-    }
-
-    fn load(path: &Path) -> Result<Self>
-    where
-        Self: Sized,
-    {
-        assert!(Self::item_matches(path));
-
-        let items = fs::read_dir(path)?.collect::<Result<Vec<_>, _>>()?;
-
-        let mut image_files = items
-            .into_iter()
-            .filter_map(|item| {
-                let path = item.path();
-
-                if path.is_file() && is_image_supported(&path) {
-                    Some(path)
-                } else {
-                    None
+/// Directory found while looking for images
+struct Directory {
+    /// Path relative to the opened directory
+    path: PathBuf,
+    files: Vec<DirectoryFile>,
+}
+
+/// File found while looking for images
+/// For symbolic links, the metadata is the one of their target
+struct DirectoryFile {
+    path: PathBuf,
+    metadata: fs::Metadata,
+    is_symlink: bool,
+}
+
+/// Image file inside the directory
+#[derive(Clone)]
+struct ImageFile {
+    path: PathBuf,
+    /// Modification time, in nanoseconds since the Unix epoch
+    modified: Option<u64>,
+    /// Position of the file when listing the directory
+    position: usize,
+    /// Size of the file, in bytes
+    size: Option<u64>,
+}
+
+impl ImageFile {
+    fn sort_key(&self) -> SortKey<'_> {
+        SortKey {
+            path: &self.path,
+            modified: self.modified,
+            position: self.position,
+        }
+    }
+}
+
+/// Factory for [`ImageDirectory`]
+pub struct ImageDirectoryFactory;
+
+impl SourceFactory for ImageDirectoryFactory {
+    fn name(&self) -> &'static str {
+        "Image directory"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn probe(&self, path: &Path) -> Probe {
+        if path.is_dir() || (path.is_file() && is_image_supported(path)) {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline("not a directory nor a supported image".to_owned())
+        }
+    }
+
+    fn load(&self, path: &Path) -> Result<Box<dyn ImageSource>> {
+        if path.is_file() {
+            Ok(Box::new(ImageDirectory::load_around(path)?))
+        } else {
+            Ok(Box::new(ImageDirectory::load(path)?))
+        }
+    }
+}
+
+impl ImageDirectory {
+    /// Load a directory of images, sorted in natural order
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut directories = vec![];
+        let mut broken_links = vec![];
+
+        list_directories(
+            path,
+            Path::new(""),
+            0,
+            &mut HashSet::new(),
+            &mut directories,
+            &mut broken_links,
+        )?;
+
+        directories.sort_by(|a, b| natural_cmp(&a.path, &b.path));
+
+        let mut image_files = vec![];
+        let mut chapters = vec![];
+        let mut skipped_pages = SkippedPages::default();
+        let mut position = 0;
+
+        // Symbolic links may point to files that are already listed, which must only appear once
+        // As directories are sorted, the file is kept where it appears first
+        // Without any symbolic link, each file is only listed once
+        let has_symlinks = directories
+            .iter()
+            .any(|directory| directory.files.iter().any(|file| file.is_symlink));
+
+        let mut listed_files = HashSet::new();
+
+        for directory in directories {
+            let mut directory_images = vec![];
+
+            for DirectoryFile {
+                path: item_path,
+                metadata,
+                ..
+            } in directory.files
+            {
+                if is_image_supported(&item_path) {
+                    if has_symlinks
+                        && !listed_files
+                            .insert(fs::canonicalize(&item_path).unwrap_or(item_path.clone()))
+                    {
+                        continue;
+                    }
+
+                    directory_images.push(ImageFile {
+                        path: item_path,
+                        modified: modification_time(&metadata),
+                        position,
+                        size: Some(metadata.len()),
+                    });
+
+                    position += 1;
+                } else if let Some(format) = unsupported_image_format(&item_path) {
+                    skipped_pages.add(format);
                 }
-            })
-            .collect::<Vec<_>>();
+            }
 
-        image_files.sort();
+            if directory_images.is_empty() {
+                continue;
+            }
 
-        Ok(Self { image_files })
+            sort_pages(
+                &mut directory_images,
+                SortOrder::Natural,
+                ImageFile::sort_key,
+            );
+
+            // Images directly inside the opened directory are named after it
+            let name = if directory.path.as_os_str().is_empty() {
+                path.file_name().unwrap_or(path.as_os_str())
+            } else {
+                directory.path.as_os_str()
+            };
+
+            chapters.push(Chapter {
+                first_page: image_files.len(),
+                name: name.to_string_lossy().into_owned(),
+            });
+
+            image_files.extend(directory_images);
+        }
+
+        // A directory without any subdirectory doesn't have chapters
+        if chapters.len() == 1 {
+            chapters.clear();
+        }
+
+        let reading_order = READING_ORDER_FILE_NAMES.iter().find_map(|name| {
+            let content = fs::read_to_string(path.join(name)).ok()?;
+
+            Some(ReadingOrder::apply(
+                name,
+                &content,
+                &mut image_files,
+                |image_file| {
+                    image_file
+                        .path
+                        .strip_prefix(path)
+                        .unwrap_or(&image_file.path)
+                },
+            ))
+        });
+
+        // Pages are not grouped by directory anymore
+        if reading_order.is_some() {
+            chapters.clear();
+        }
+
+        Ok(Self {
+            image_files,
+            chapters,
+            skipped_pages,
+            initial_page: 0,
+            broken_links,
+            reading_order,
+        })
     }
 
+    /// Load the directory an image is in, starting at this image
+    pub fn load_around(image_path: &Path) -> Result<Self> {
+        // The path is made absolute so it can be compared with the listed ones
+        let image_path = std::path::absolute(image_path).context("Failed to get image's path")?;
+
+        let directory = image_path.parent().context("Image is not in a directory")?;
+
+        let mut image_directory = Self::load(directory)?;
+
+        image_directory.initial_page = image_directory
+            .image_files
+            .iter()
+            .position(|image_file| image_file.path == image_path)
+            .unwrap_or(0);
+
+        Ok(image_directory)
+    }
+}
+
+impl ImageSource for ImageDirectory {
     fn total_pages(&self) -> usize {
         self.image_files.len()
     }
 
-    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), String> {
-        let page_path = self
+    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError> {
+        let page_path = &self
             .image_files
             .get(page)
-            .ok_or_else(|| format!("Page {page} was not found"))?;
+            .ok_or(PageError::OutOfRange { page })?
+            .path;
 
         fs::read(page_path)
             .map(|page| (page_path.to_owned(), page))
-            .map_err(|err| format!("Failed to load file for page {page}: {err}"))
+            .map_err(|err| PageError::Io {
+                detail: format!("failed to load file '{}': {err}", page_path.display()),
+            })
     }
 
-    fn quick_clone(&self) -> Result<Box<dyn ImageSource>>
-    where
-        Self: Sized,
-    {
+    fn quick_clone(&self) -> Result<Box<dyn ImageSource>> {
         Ok(Box::new(self.clone()))
     }
+
+    fn page_name(&self, page: usize) -> Option<String> {
+        let name = self.image_files.get(page)?.path.file_name()?;
+        Some(name.to_string_lossy().into_owned())
+    }
+
+    fn page_size_hint(&self, page: usize) -> Option<u64> {
+        self.image_files.get(page)?.size
+    }
+
+    fn skipped_pages(&self) -> SkippedPages {
+        self.skipped_pages.clone()
+    }
+
+    fn chapters(&self) -> Vec<Chapter> {
+        self.chapters.clone()
+    }
+
+    fn initial_page(&self) -> usize {
+        self.initial_page
+    }
+
+    fn warning(&self) -> Option<String> {
+        let broken_links = match self.broken_links.as_slice() {
+            [] => None,
+            [link] => Some(format!("Broken link skipped: {}", link.display())),
+            links => Some(format!(
+                "{} broken links skipped, including {}",
+                links.len(),
+                links[0].display()
+            )),
+        };
+
+        let warnings = [
+            broken_links,
+            self.reading_order.as_ref().and_then(ReadingOrder::warning),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        (!warnings.is_empty()).then(|| warnings.join("\n"))
+    }
+
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            reading_order: self
+                .reading_order
+                .as_ref()
+                .map(|reading_order| reading_order.file_name.clone()),
+            ..Metadata::default()
+        }
+    }
+
+    fn sort_pages(&mut self, order: SortOrder) -> Option<Vec<usize>> {
+        if self.reading_order.is_some() {
+            return None;
+        }
+
+        Some(sort_pages_by_chapter(
+            &mut self.image_files,
+            &self.chapters,
+            order,
+            ImageFile::sort_key,
+        ))
+    }
+}
+
+/// List the files of a directory and of its subdirectories (up to [`MAX_DEPTH`])
+/// The provided path is relative to the root directory
+fn list_directories(
+    root: &Path,
+    path: &Path,
+    depth: usize,
+    visited: &mut HashSet<PathBuf>,
+    directories: &mut Vec<Directory>,
+    broken_links: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let full_path = root.join(path);
+
+    // Symbolic links may create loops, so we make sure to never visit a directory twice
+    if !visited.insert(fs::canonicalize(&full_path)?) {
+        return Ok(());
+    }
+
+    let mut files = vec![];
+
+    for item in fs::read_dir(&full_path)? {
+        let item = item?;
+        let item_path = path.join(item.file_name());
+
+        if is_junk_file(&item_path) {
+            continue;
+        }
+
+        // Symbolic links are followed, so their target's metadata is used
+        let item_full_path = item.path();
+
+        let metadata = match fs::metadata(&item_full_path) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                if item
+                    .file_type()
+                    .is_ok_and(|file_type| file_type.is_symlink())
+                {
+                    broken_links.push(item_path);
+                }
+
+                continue;
+            }
+        };
+
+        if metadata.is_file() {
+            files.push(DirectoryFile {
+                path: item_full_path,
+                metadata,
+                is_symlink: item
+                    .file_type()
+                    .is_ok_and(|file_type| file_type.is_symlink()),
+            });
+        } else if metadata.is_dir() && depth < MAX_DEPTH {
+            // Unreadable subdirectories are ignored (e.g. if they are protected by the system)
+            let _ = list_directories(
+                root,
+                &item_path,
+                depth + 1,
+                visited,
+                directories,
+                broken_links,
+            );
+        }
+    }
+
+    directories.push(Directory {
+        path: path.to_owned(),
+        files,
+    });
+
+    Ok(())
+}
+
+/// Get the modification time of a directory's item, in nanoseconds since the Unix epoch
+fn modification_time(metadata: &fs::Metadata) -> Option<u64> {
+    let modified = metadata.modified().ok()?;
+
+    u64::try_from(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos()).ok()
 }
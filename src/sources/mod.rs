@@ -1,49 +1,646 @@
+mod comic_info;
 mod empty;
+mod epub_file;
 mod image_directory;
+mod natural_sort;
+mod page_order;
+#[cfg(feature = "pdf")]
+mod pdf_file;
+mod playlist;
+#[cfg(feature = "rar")]
+mod rar_file;
+mod reading_order;
+mod registry;
+#[cfg(feature = "remote")]
+mod remote_zip;
+mod shared_file;
+mod tar_file;
 mod zip_file;
 
 pub use empty::EmptySource;
+pub use page_order::SortOrder;
+#[cfg(feature = "pdf")]
+pub use pdf_file::PDF_RENDER_DPI;
+pub use registry::{Probe, SourceFactory, SourceRegistry};
+pub use zip_file::{ZipNamesEncoding, ZIP_NAMES_ENCODING};
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::SystemTime,
+};
 
-use anyhow::{bail, Result};
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
 
-use self::{image_directory::ImageDirectory, zip_file::ZipFile};
+use crate::errors::PageError;
+
+/// Names of the files created by operating systems, which are never pages
+const JUNK_FILE_NAMES: &[&str] = &["Thumbs.db", "desktop.ini"];
+
+/// Name of the directory macOS stores resource forks in, when creating archives
+const JUNK_DIRECTORY_NAME: &str = "__MACOSX";
+
+/// Registry of all the sources images can be loaded from
+pub static SOURCE_REGISTRY: Lazy<RwLock<SourceRegistry>> =
+    Lazy::new(|| RwLock::new(SourceRegistry::with_builtin_sources()));
 
 /// Source providing a set of images
+/// Sources are created through a [`SourceFactory`]
 pub trait ImageSource: Send + Sync {
-    /// Check if a path can be handled by the source
-    /// e.g. is it a file with a specific extension, etc.
-    fn item_matches(path: &Path) -> bool
-    where
-        Self: Sized;
-
-    /// Load an image set from a path
-    /// Should come after a check from [`ImageSource::item_matches`]
-    fn load(path: &Path) -> Result<Self>
-    where
-        Self: Sized;
-
     /// Get the total number of pages (= number of images) in the set
     fn total_pages(&self) -> usize;
 
     /// Load a page (= an image) as a vector of bytes
-    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), String>;
+    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError>;
 
     /// Quick clone
     fn quick_clone(&self) -> Result<Box<dyn ImageSource>>;
+
+    /// Get the images that were excluded from the pages list because their format
+    /// is recognized but can't be decoded by this build
+    fn skipped_pages(&self) -> SkippedPages;
+
+    /// Get the name of a page (e.g. its file's path in an archive), without loading it
+    /// Returns `None` if the page doesn't exist or has no name
+    fn page_name(&self, page: usize) -> Option<String>;
+
+    /// Get the size of a page's data, without loading it (e.g. its compressed size in an archive)
+    /// Used to estimate the loading progress, so it doesn't need to be exact
+    fn page_size_hint(&self, _page: usize) -> Option<u64> {
+        None
+    }
+
+    /// Get the chapters the pages are split in (if any)
+    fn chapters(&self) -> Vec<Chapter> {
+        vec![]
+    }
+
+    /// Get a message to display to the user when the source is opened (e.g. about performance)
+    fn warning(&self) -> Option<String> {
+        None
+    }
+
+    /// Get the page to display when the source is opened
+    fn initial_page(&self) -> usize {
+        0
+    }
+
+    /// Get information about the book (e.g. its title), if provided by the source
+    fn metadata(&self) -> Metadata {
+        Metadata::default()
+    }
+
+    /// Sort the pages in the provided order
+    /// Returns the previous index of each page, or `None` if the pages' order can't be changed
+    fn sort_pages(&mut self, _order: SortOrder) -> Option<Vec<usize>> {
+        None
+    }
+}
+
+/// Information about a book, provided by some sources
+#[derive(Clone, Default)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub series: Option<String>,
+    /// Reading direction of the book (if known)
+    pub right_to_left: Option<bool>,
+    /// Name of the manifest the pages' order comes from, if it isn't derived from their names
+    pub reading_order: Option<String>,
+}
+
+impl Metadata {
+    /// Get the name to display for the book (if any)
+    pub fn display_name(&self) -> Option<String> {
+        match (&self.series, &self.title) {
+            (Some(series), Some(title)) => Some(format!("{series}: {title}")),
+            (Some(name), None) | (None, Some(name)) => Some(name.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Part of a source, starting at a given page (e.g. a volume inside an archive)
+#[derive(Clone)]
+pub struct Chapter {
+    pub first_page: usize,
+    pub name: String,
+}
+
+/// Count of the pages skipped because of their format, grouped by format name
+#[derive(Clone, Default)]
+pub struct SkippedPages {
+    by_format: BTreeMap<&'static str, usize>,
+}
+
+impl SkippedPages {
+    /// Register a skipped page
+    pub fn add(&mut self, format: &'static str) {
+        *self.by_format.entry(format).or_default() += 1;
+    }
+
+    /// Add the pages skipped by another source
+    pub fn merge(&mut self, other: &SkippedPages) {
+        for (format, count) in &other.by_format {
+            *self.by_format.entry(format).or_default() += count;
+        }
+    }
+
+    /// Total number of skipped pages
+    pub fn total(&self) -> usize {
+        self.by_format.values().sum()
+    }
+
+    /// Get a human-readable summary of the skipped pages (if any)
+    /// e.g. "14 pages skipped: WebP not supported in this build"
+    pub fn summary(&self) -> Option<String> {
+        let total = self.total();
+
+        if total == 0 {
+            return None;
+        }
+
+        let formats = self.by_format.keys().copied().collect::<Vec<_>>();
+
+        Some(format!(
+            "{total} page{} skipped: {} not supported in this build",
+            if total > 1 { "s" } else { "" },
+            formats.join(", ")
+        ))
+    }
+}
+
+/// Error returned when an item requires a password to be opened
+#[derive(Debug)]
+pub struct PasswordRequired {
+    /// Indicates if a password was provided but is wrong
+    pub wrong_password: bool,
+}
+
+impl fmt::Display for PasswordRequired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.wrong_password {
+            write!(f, "Wrong password")
+        } else {
+            write!(f, "A password is required to open this item")
+        }
+    }
+}
+
+impl std::error::Error for PasswordRequired {}
+
+/// Identity of an image source on disk
+/// Two sources with the same fingerprint are considered to hold the same content
+#[derive(PartialEq, Eq)]
+pub struct SourceFingerprint {
+    canonical_path: PathBuf,
+    len: u64,
+    modified: Option<SystemTime>,
+
+    /// Size and modification time of the files of a directory (and of its subdirectories)
+    /// A directory's own metadata doesn't change when one of its files is edited
+    entries: Vec<(PathBuf, u64, Option<SystemTime>)>,
+}
+
+impl SourceFingerprint {
+    /// Compute the fingerprint of the item at the provided path
+    pub fn compute(path: &Path) -> Result<Self> {
+        let canonical_path = fs::canonicalize(path).context("Failed to canonicalize path")?;
+        let metadata = fs::metadata(&canonical_path).context("Failed to get item's metadata")?;
+
+        if !metadata.is_dir() {
+            return Ok(Self {
+                canonical_path,
+                len: metadata.len(),
+                modified: metadata.modified().ok(),
+                entries: vec![],
+            });
+        }
+
+        // A directory's own metadata changes when junk files (e.g. thumbnails) are created inside it,
+        // so only its files are taken into account
+        let mut entries = vec![];
+
+        list_entries(
+            &canonical_path,
+            Path::new(""),
+            0,
+            &mut HashSet::new(),
+            &mut entries,
+        )
+        .context("Failed to list directory's content")?;
+
+        entries.sort();
+
+        Ok(Self {
+            canonical_path,
+            len: 0,
+            modified: None,
+            entries,
+        })
+    }
+}
+
+/// List the files of a directory and of its subdirectories (up to the depth images are looked for in)
+/// The provided path is relative to the root directory
+fn list_entries(
+    root: &Path,
+    path: &Path,
+    depth: usize,
+    visited: &mut HashSet<PathBuf>,
+    entries: &mut Vec<(PathBuf, u64, Option<SystemTime>)>,
+) -> Result<()> {
+    let full_path = root.join(path);
+
+    // Symbolic links may create loops, so we make sure to never visit a directory twice
+    if !visited.insert(fs::canonicalize(&full_path)?) {
+        return Ok(());
+    }
+
+    for item in fs::read_dir(&full_path)? {
+        let item = item?;
+        let item_path = path.join(item.file_name());
+
+        if is_junk_file(&item_path) {
+            continue;
+        }
+
+        // Broken symbolic links are ignored by the sources as well
+        let Ok(metadata) = fs::metadata(item.path()) else {
+            continue;
+        };
+
+        if metadata.is_dir() {
+            if depth < image_directory::MAX_DEPTH {
+                list_entries(root, &item_path, depth + 1, visited, entries)?;
+            }
+        } else {
+            entries.push((item_path, metadata.len(), metadata.modified().ok()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Image source loaded by [`load_if_changed`], along with its fingerprint
+pub struct ChangedSource {
+    pub img_source: Box<dyn ImageSource>,
+    pub fingerprint: Option<SourceFingerprint>,
+}
+
+/// Load an image source, unless it's the one with the provided fingerprint (which is already loaded)
+/// and it didn't change since then
+/// The source's fingerprint is computed before loading it, so any change happening in-between is detected the next time
+pub fn load_if_changed(
+    path: &Path,
+    loaded: Option<&SourceFingerprint>,
+    load: impl FnOnce() -> Result<Box<dyn ImageSource>>,
+) -> Result<Option<ChangedSource>> {
+    let fingerprint = SourceFingerprint::compute(path).ok();
+
+    if fingerprint.is_some() && fingerprint.as_ref() == loaded {
+        return Ok(None);
+    }
+
+    Ok(Some(ChangedSource {
+        img_source: load()?,
+        fingerprint,
+    }))
+}
+
+/// Check if a file is a junk one that must be ignored by the sources (e.g. `.DS_Store`)
+/// Hidden files are ignored too, which includes AppleDouble files (e.g. `._page001.png`)
+/// The path must be relative to the source's root, so only its own content is checked
+pub fn is_junk_file(path: &Path) -> bool {
+    if path
+        .components()
+        .any(|component| component.as_os_str() == JUNK_DIRECTORY_NAME)
+    {
+        return true;
+    }
+
+    let Some(file_name) = path.file_name().and_then(|file_name| file_name.to_str()) else {
+        return false;
+    };
+
+    file_name.starts_with('.')
+        || JUNK_FILE_NAMES
+            .iter()
+            .any(|junk| file_name.eq_ignore_ascii_case(junk))
 }
 
 /// Try to load a path as an image source
-pub fn load_image_source(path: &Path) -> Result<Box<dyn ImageSource>> {
-    macro_rules! identify_source {
-        ($($source: ident),+) => {{
-            $( if $source::item_matches(path) {
-                return Ok(Box::new($source::load(path)?))
-            } )+
-        }}
+/// Fails with [`PasswordRequired`] if a password is required but none (or a wrong one) is provided
+pub fn load_image_source(path: &Path, password: Option<&str>) -> Result<Box<dyn ImageSource>> {
+    SOURCE_REGISTRY.read().unwrap().load(path, password)
+}
+
+/// Load several paths as a single image source, read one after the other
+pub fn load_playlist(paths: &[PathBuf]) -> Result<Box<dyn ImageSource>> {
+    Ok(Box::new(playlist::Playlist::load(paths)?))
+}
+
+/// List the items of a directory that can be opened (subdirectories and supported files),
+/// sorted in natural order, so they can be read one after the other (e.g. volumes of a series)
+pub fn series_items(directory: &Path) -> Result<Vec<PathBuf>> {
+    let extensions = SOURCE_REGISTRY.read().unwrap().extensions();
+
+    let mut items = vec![];
+
+    for item in fs::read_dir(directory).context("Failed to read directory")? {
+        let item = item.context("Failed to read item in directory")?;
+        let path = item.path();
+
+        if is_junk_file(Path::new(&item.file_name())) {
+            continue;
+        }
+
+        if path.is_dir()
+            || path.extension().is_some_and(|ext| {
+                extensions
+                    .iter()
+                    .any(|c_ext| ext.eq_ignore_ascii_case(c_ext))
+            })
+        {
+            items.push(path);
+        }
+    }
+
+    items.sort_by(|a, b| natural_sort::natural_cmp(a, b));
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, io, io::Write, ops::Range, process};
+
+    use zip_next::{write::FileOptions, ZipArchive, ZipWriter};
+
+    use super::*;
+
+    /// Files found in archives and directories created on macOS and Windows
+    const JUNK_FILES: &[&str] = &[
+        "__MACOSX/._p1.png",
+        "__MACOSX/pages/._p2.png",
+        ".DS_Store",
+        "._p2.png",
+        "Thumbs.db",
+        "THUMBS.DB",
+        "desktop.ini",
+    ];
+
+    const PAGES: &[&str] = &["p1.png", "p2.png", "p10.png"];
+
+    /// Get a path in the temporary directory, unique to the provided name and the current process
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("reader-test-{}-{name}", process::id()))
+    }
+
+    fn page_names(source: &dyn ImageSource) -> Vec<String> {
+        (0..source.total_pages())
+            .map(|page| source.page_name(page).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn detects_junk_files() {
+        for junk in JUNK_FILES {
+            assert!(is_junk_file(Path::new(junk)), "{junk} is a junk file");
+        }
+
+        for page in PAGES {
+            assert!(!is_junk_file(Path::new(page)), "{page} is a page");
+        }
+
+        assert!(!is_junk_file(Path::new("chapter 1/p1.png")));
+        assert!(!is_junk_file(Path::new("MACOSX/p1.png")));
+        assert!(!is_junk_file(Path::new("Thumbs.db.png")));
+        assert!(is_junk_file(Path::new("chapter 1/.hidden.png")));
+    }
+
+    #[test]
+    fn zip_file_ignores_junk_files() {
+        let mut zip = ZipWriter::new(io::Cursor::new(vec![]));
+
+        for name in JUNK_FILES.iter().chain(PAGES) {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(b"page").unwrap();
+        }
+
+        let path = temp_path("junk.zip");
+        fs::write(&path, zip.finish().unwrap().into_inner()).unwrap();
+
+        let source = zip_file::ZipFile::load(&path, None);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(page_names(&source.unwrap()), PAGES);
+    }
+
+    #[test]
+    fn zip_file_sorts_pages_listed_in_natural_order() {
+        let mut zip = ZipWriter::new(io::Cursor::new(vec![]));
+
+        for name in ["chapter 1/p2.png", "chapter 1/p10.png", "chapter 2/p1.png"] {
+            zip.start_file(name, FileOptions::default()).unwrap();
+            zip.write_all(b"page").unwrap();
+        }
+
+        zip.start_file("ComicInfo.xml", FileOptions::default())
+            .unwrap();
+        zip.write_all(
+            br#"<ComicInfo><Pages><Page Image="0"/><Page Image="1"/><Page Image="2"/></Pages></ComicInfo>"#,
+        )
+        .unwrap();
+
+        let path = temp_path("natural-comic-info.zip");
+        fs::write(&path, zip.finish().unwrap().into_inner()).unwrap();
+
+        let source = zip_file::ZipFile::load(&path, None);
+        fs::remove_file(&path).unwrap();
+
+        let mut source = source.unwrap();
+        let chapters = source.chapters();
+
+        assert_eq!(
+            chapters
+                .iter()
+                .map(|chapter| (chapter.first_page, chapter.name.as_str()))
+                .collect::<Vec<_>>(),
+            [(0, "chapter 1"), (2, "chapter 2")]
+        );
+
+        assert!(source.sort_pages(SortOrder::Name).is_some());
+        assert_eq!(
+            page_names(&source),
+            ["chapter 1/p10.png", "chapter 1/p2.png", "chapter 2/p1.png"]
+        );
+    }
+
+    /// In-memory file whose reads fail for a range of positions, like a file on a lost connection
+    struct FailingReader {
+        file: io::Cursor<Vec<u8>>,
+        failing: Range<u64>,
+    }
+
+    impl io::Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.failing.contains(&self.file.position()) {
+                return Err(shared_file::ReaderError::wrap(io::Error::other(
+                    "connection lost",
+                )));
+            }
+
+            self.file.read(buf)
+        }
+    }
+
+    impl io::Seek for FailingReader {
+        fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+            self.file.seek(pos)
+        }
+    }
+
+    #[test]
+    fn zip_file_tells_reading_errors_from_corruption() {
+        let content = vec![b'p'; 1000];
+
+        let mut zip = ZipWriter::new(io::Cursor::new(vec![]));
+        zip.start_file(
+            "p1.png",
+            FileOptions::default().compression_method(zip_next::CompressionMethod::Stored),
+        )
+        .unwrap();
+        zip.write_all(&content).unwrap();
+
+        let bytes = zip.finish().unwrap().into_inner();
+
+        // The file's content comes right after its 30 bytes header and its name
+        let content_start = 30 + "p1.png".len() as u64;
+        let content_end = content_start + content.len() as u64;
+
+        let read = |bytes: Vec<u8>, failing: Range<u64>| {
+            let file = io::Cursor::new(bytes);
+            let mut archive = ZipArchive::new(FailingReader { file, failing }).unwrap();
+
+            zip_file::read_file(&mut archive, 0, None)
+        };
+
+        let (_, read_content) = read(bytes.clone(), 0..0).unwrap();
+        assert_eq!(read_content, content);
+
+        // Reading errors may go away when reading the page again
+        let err = read(bytes.clone(), content_start + 100..content_end).unwrap_err();
+        assert!(err.is_transient(), "{err}");
+
+        // Invalid content doesn't
+        let mut corrupted = bytes;
+        corrupted[usize::try_from(content_start).unwrap() + 100] = b'q';
+
+        let err = read(corrupted, 0..0).unwrap_err();
+        assert!(matches!(err, PageError::Corrupted { .. }), "{err}");
+    }
+
+    #[test]
+    fn image_directory_ignores_junk_files() {
+        let path = temp_path("junk");
+
+        for name in JUNK_FILES.iter().chain(PAGES) {
+            let file_path = path.join(name);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, b"page").unwrap();
+        }
+
+        let source = image_directory::ImageDirectory::load(&path);
+        fs::remove_dir_all(&path).unwrap();
+
+        assert_eq!(page_names(&source.unwrap()), PAGES);
+    }
+
+    /// Source with a fixed number of empty pages
+    struct MockSource(usize);
+
+    impl ImageSource for MockSource {
+        fn total_pages(&self) -> usize {
+            self.0
+        }
+
+        fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError> {
+            Ok((PathBuf::from(format!("{page}.png")), vec![]))
+        }
+
+        fn quick_clone(&self) -> Result<Box<dyn ImageSource>> {
+            Ok(Box::new(Self(self.0)))
+        }
+
+        fn skipped_pages(&self) -> SkippedPages {
+            SkippedPages::default()
+        }
+
+        fn page_name(&self, page: usize) -> Option<String> {
+            Some(format!("{page}.png"))
+        }
+    }
+
+    /// Create a directory with a page at its root and one in a subdirectory
+    fn create_book(name: &str) -> PathBuf {
+        let path = temp_path(name);
+
+        fs::create_dir_all(path.join("chapter 1")).unwrap();
+        fs::write(path.join("p1.png"), b"page").unwrap();
+        fs::write(path.join("chapter 1/p2.png"), b"page").unwrap();
+
+        path
     }
 
-    identify_source!(ImageDirectory, ZipFile);
-    bail!("Provided item is not supported");
+    #[test]
+    fn keeps_identical_source() {
+        let path = create_book("identical");
+
+        let loaded = SourceFingerprint::compute(&path).unwrap();
+
+        // Junk files aren't part of the book
+        fs::write(path.join("Thumbs.db"), b"thumbnails").unwrap();
+
+        let result = load_if_changed(&path, Some(&loaded), || {
+            panic!("an unchanged source must not be loaded again")
+        });
+
+        fs::remove_dir_all(&path).unwrap();
+
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn loads_changed_source() {
+        let path = create_book("changed");
+
+        let loaded = SourceFingerprint::compute(&path).unwrap();
+
+        // Editing a page doesn't change the directories' own metadata
+        fs::write(path.join("chapter 1/p2.png"), b"edited page").unwrap();
+
+        let result = load_if_changed(&path, Some(&loaded), || Ok(Box::new(MockSource(2))));
+        let changed = SourceFingerprint::compute(&path).unwrap();
+
+        fs::remove_dir_all(&path).unwrap();
+
+        let loaded_source = result.unwrap().unwrap();
+
+        assert_eq!(loaded_source.img_source.total_pages(), 2);
+        assert!(loaded_source.fingerprint.unwrap() == changed);
+        assert!(changed != loaded);
+    }
+
+    #[test]
+    fn loads_other_source() {
+        let path = create_book("other");
+
+        let result = load_if_changed(&path, None, || Ok(Box::new(MockSource(1))));
+        fs::remove_dir_all(&path).unwrap();
+
+        assert_eq!(result.unwrap().unwrap().img_source.total_pages(), 1);
+    }
 }
@@ -0,0 +1,218 @@
+use std::{cmp::Ordering, iter::Peekable, path::Path, str::CharIndices};
+
+/// Compare two paths naturally, component by component (see [`natural_cmp_str`])
+/// This way, files are grouped by directory whatever their names are
+pub fn natural_cmp(a: &Path, b: &Path) -> Ordering {
+    let mut a_components = a.components();
+    let mut b_components = b.components();
+
+    loop {
+        match (a_components.next(), b_components.next()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_component), Some(b_component)) => {
+                let ordering = natural_cmp_str(
+                    &a_component.as_os_str().to_string_lossy(),
+                    &b_component.as_os_str().to_string_lossy(),
+                );
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// Compare two strings naturally: runs of digits are compared by their numeric value,
+/// while other characters are compared case-insensitively
+/// e.g. "page2" comes before "page10", and "ch2_05" before "ch10_01"
+pub fn natural_cmp_str(a: &str, b: &str) -> Ordering {
+    let mut a_chunks = Chunks::new(a);
+    let mut b_chunks = Chunks::new(b);
+
+    loop {
+        let ordering = match (a_chunks.next(), b_chunks.next()) {
+            (None, None) => break,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(Chunk::Number(a)), Some(Chunk::Number(b))) => cmp_numbers(a, b),
+            (Some(a), Some(b)) => a.first_char().cmp(&b.first_char()),
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    // Strings that only differ by their case or leading zeros are still ordered consistently
+    a.cmp(b)
+}
+
+/// Compare two runs of digits by their value, without any risk of overflow
+fn cmp_numbers(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Part of a string, as seen by the natural comparison
+enum Chunk<'a> {
+    /// Run of ASCII digits
+    Number(&'a str),
+
+    /// Any other character, lowercased
+    Char(char),
+}
+
+impl Chunk<'_> {
+    /// Get the first character of the chunk, used to order numbers and other characters
+    fn first_char(&self) -> char {
+        match self {
+            Chunk::Number(number) => number.chars().next().unwrap(),
+            Chunk::Char(c) => *c,
+        }
+    }
+}
+
+/// Iterator over the chunks of a string
+struct Chunks<'a> {
+    str: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Chunks<'a> {
+    fn new(str: &'a str) -> Self {
+        Self {
+            str,
+            chars: str.char_indices().peekable(),
+        }
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = Chunk<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, c) = self.chars.next()?;
+
+        if !c.is_ascii_digit() {
+            // Characters lowercasing to several ones (which is rare) are compared using the first one
+            return Some(Chunk::Char(c.to_lowercase().next().unwrap_or(c)));
+        }
+
+        let mut end = start + 1;
+
+        while let Some((i, _)) = self.chars.next_if(|(_, c)| c.is_ascii_digit()) {
+            end = i + 1;
+        }
+
+        Some(Chunk::Number(&self.str[start..end]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Check that the provided strings are naturally sorted, whatever order they're in beforehand
+    fn assert_sorted(sorted: &[&str]) {
+        let mut strings = sorted.to_vec();
+
+        strings.reverse();
+        strings.sort_by(|a, b| natural_cmp_str(a, b));
+
+        assert_eq!(strings, sorted);
+    }
+
+    #[test]
+    fn compares_numbers_by_value() {
+        assert_sorted(&["page1", "page2", "page10", "page11", "page100"]);
+        assert_eq!(natural_cmp_str("page2", "page10"), Ordering::Less);
+        assert_eq!(natural_cmp_str("page10", "page2"), Ordering::Greater);
+    }
+
+    #[test]
+    fn compares_each_number() {
+        assert_sorted(&["ch2_05", "ch2_10", "ch10_01", "ch10_02"]);
+        assert_eq!(natural_cmp_str("ch2_05", "ch10_01"), Ordering::Less);
+    }
+
+    #[test]
+    fn ignores_leading_zeros() {
+        assert_sorted(&["page2", "page003", "page10"]);
+
+        // Numbers with the same value are still ordered consistently
+        assert_eq!(natural_cmp_str("page002", "page2"), Ordering::Less);
+        assert_eq!(natural_cmp_str("page2", "page002"), Ordering::Greater);
+    }
+
+    #[test]
+    fn ignores_case() {
+        assert_sorted(&["a", "B", "c"]);
+        assert_sorted(&["Page1", "page2", "PAGE10"]);
+
+        // Strings only differing by their case are still ordered consistently
+        assert_eq!(natural_cmp_str("Page", "page"), Ordering::Less);
+        assert_eq!(natural_cmp_str("page", "Page"), Ordering::Greater);
+        assert_eq!(natural_cmp_str("page", "page"), Ordering::Equal);
+    }
+
+    #[test]
+    fn handles_non_ascii_characters() {
+        assert_sorted(&["Écho", "été", "ü"]);
+        assert_sorted(&["chapitre 2 - été", "chapitre 10 - automne"]);
+        assert_eq!(natural_cmp_str("Éte", "éte"), Ordering::Less);
+
+        // Non-ASCII digits aren't part of numbers
+        assert_sorted(&["page١٠", "page٢"]);
+        assert_sorted(&["页2", "页10"]);
+    }
+
+    #[test]
+    fn handles_long_numbers() {
+        assert_sorted(&[
+            "page9",
+            "page99999999999999999999999999999999",
+            "page100000000000000000000000000000000",
+            "page100000000000000000000000000000001",
+        ]);
+    }
+
+    #[test]
+    fn orders_prefixes_first() {
+        assert_sorted(&["", "page", "page1", "page1a", "pagea"]);
+    }
+
+    #[test]
+    fn compares_paths_component_by_component() {
+        let mut paths = [
+            "vol10/page1",
+            "vol2/page10",
+            "vol2/page2",
+            "vol2",
+            "vol1 extra/page1",
+            "vol1/page3",
+        ]
+        .map(PathBuf::from);
+
+        paths.sort_by(|a, b| natural_cmp(a, b));
+
+        assert_eq!(
+            paths,
+            [
+                "vol1/page3",
+                "vol1 extra/page1",
+                "vol2",
+                "vol2/page2",
+                "vol2/page10",
+                "vol10/page1",
+            ]
+            .map(PathBuf::from)
+        );
+    }
+}
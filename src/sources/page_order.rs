@@ -0,0 +1,120 @@
+use std::{cmp::Ordering, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{natural_sort::natural_cmp, Chapter};
+
+/// Order the pages of a source are sorted in
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Plain comparison of the files' paths
+    Name,
+    /// Comparison of the files' paths, with numbers compared by their value
+    Natural,
+    /// Oldest files first
+    ModifiedTime,
+    /// Order the files are stored in (e.g. the one used by the archive's packer)
+    ArchiveOrder,
+}
+
+impl SortOrder {
+    /// Get the next sort order (cycles back to the first one)
+    pub fn next(self) -> Self {
+        match self {
+            Self::Name => Self::Natural,
+            Self::Natural => Self::ModifiedTime,
+            Self::ModifiedTime => Self::ArchiveOrder,
+            Self::ArchiveOrder => Self::Name,
+        }
+    }
+
+    /// Get a human-readable description of the sort order
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Natural => "name (natural order)",
+            Self::ModifiedTime => "modification time",
+            Self::ArchiveOrder => "archive order",
+        }
+    }
+}
+
+/// Information a page is sorted with
+pub struct SortKey<'a> {
+    /// Path of the page's file
+    pub path: &'a Path,
+
+    /// Modification time of the page's file, only comparable with the ones of the same source
+    pub modified: Option<u64>,
+
+    /// Position of the page's file in its container
+    pub position: usize,
+}
+
+impl SortKey<'_> {
+    fn cmp(&self, other: &Self, order: SortOrder) -> Ordering {
+        match order {
+            SortOrder::Name => self.path.cmp(other.path),
+            SortOrder::Natural => natural_cmp(self.path, other.path),
+            SortOrder::ModifiedTime => self
+                .modified
+                .cmp(&other.modified)
+                .then_with(|| natural_cmp(self.path, other.path)),
+            SortOrder::ArchiveOrder => self.position.cmp(&other.position),
+        }
+    }
+}
+
+/// Sort pages in the provided order
+/// Returns the previous index of each page, so anything related to a page can follow it
+pub fn sort_pages<T: Clone>(
+    pages: &mut [T],
+    order: SortOrder,
+    key: impl Fn(&T) -> SortKey<'_>,
+) -> Vec<usize> {
+    let mut previous_indexes = (0..pages.len()).collect::<Vec<_>>();
+
+    previous_indexes.sort_by(|&a, &b| key(&pages[a]).cmp(&key(&pages[b]), order));
+
+    let sorted = previous_indexes
+        .iter()
+        .map(|&index| pages[index].clone())
+        .collect::<Vec<_>>();
+
+    pages.clone_from_slice(&sorted);
+
+    previous_indexes
+}
+
+/// Sort pages in the provided order, inside of their chapter so chapters stay the same
+/// Returns the previous index of each page, like [`sort_pages`]
+pub fn sort_pages_by_chapter<T: Clone>(
+    pages: &mut [T],
+    chapters: &[Chapter],
+    order: SortOrder,
+    key: impl Fn(&T) -> SortKey<'_> + Copy,
+) -> Vec<usize> {
+    let mut previous_indexes = Vec::with_capacity(pages.len());
+
+    let mut chapter_starts = chapters
+        .iter()
+        .map(|chapter| chapter.first_page)
+        .filter(|&first_page| first_page > 0)
+        .collect::<Vec<_>>();
+
+    chapter_starts.push(pages.len());
+
+    let mut start = 0;
+
+    for end in chapter_starts {
+        previous_indexes.extend(
+            sort_pages(&mut pages[start..end], order, key)
+                .into_iter()
+                .map(|index| start + index),
+        );
+
+        start = end;
+    }
+
+    previous_indexes
+}
@@ -0,0 +1,194 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{bail, Context, Result};
+use hayro::{
+    hayro_interpret::InterpreterSettings,
+    hayro_syntax::{
+        content::ops::TypedInstruction,
+        object::{dict::keys, Name},
+        page::Page,
+        Filter, LoadPdfError, Pdf,
+    },
+    vello_cpu::color::palette::css::WHITE,
+    PixmapSettings, RenderCache, RenderSettings,
+};
+
+use crate::errors::PageError;
+
+use super::{ImageSource, Probe, SkippedPages, SourceFactory};
+
+/// Resolution PDF pages are rasterised at, when they aren't made of a single image
+/// Set from the application's settings
+pub static PDF_RENDER_DPI: AtomicUsize = AtomicUsize::new(150);
+
+/// PDF document handler
+/// Pages made of a single JPEG image are served as-is, others are rasterised
+pub struct PdfFile {
+    pdf: Pdf,
+}
+
+/// Factory for [`PdfFile`]
+pub struct PdfFileFactory;
+
+impl SourceFactory for PdfFileFactory {
+    fn name(&self) -> &'static str {
+        "PDF document"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["pdf"]
+    }
+
+    fn probe(&self, path: &Path) -> Probe {
+        if !path.is_file() {
+            return Probe::Decline("not a file".to_owned());
+        }
+
+        let Some(ext) = path.extension() else {
+            return Probe::Decline("file has no extension".to_owned());
+        };
+
+        if self
+            .extensions()
+            .iter()
+            .any(|c_ext| ext.eq_ignore_ascii_case(c_ext))
+        {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline(format!(
+                "extension is not one of: {}",
+                self.extensions().join(", ")
+            ))
+        }
+    }
+
+    fn probe_content(&self, header: &[u8]) -> Probe {
+        if header.starts_with(b"%PDF-") {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline("not a PDF document".to_owned())
+        }
+    }
+
+    fn load(&self, path: &Path) -> Result<Box<dyn ImageSource>> {
+        Ok(Box::new(PdfFile::load(path)?))
+    }
+}
+
+impl PdfFile {
+    /// Load a PDF document
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read(path).context("Failed to read PDF file")?;
+
+        let pdf = match Pdf::new(Arc::new(data)) {
+            Ok(pdf) => pdf,
+            Err(LoadPdfError::Decryption(_)) => {
+                bail!("PDF document is encrypted, which is not supported")
+            }
+            Err(LoadPdfError::Invalid) => bail!("Failed to parse PDF document"),
+        };
+
+        Ok(Self { pdf })
+    }
+}
+
+impl ImageSource for PdfFile {
+    fn total_pages(&self) -> usize {
+        self.pdf.pages().len()
+    }
+
+    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError> {
+        let pdf_page = self
+            .pdf
+            .pages()
+            .get(page)
+            .ok_or(PageError::OutOfRange { page })?;
+
+        if let Some(jpeg) = single_jpeg_image(pdf_page) {
+            return Ok((PathBuf::from(format!("page-{:04}.jpg", page + 1)), jpeg));
+        }
+
+        let scale = PDF_RENDER_DPI.load(Ordering::Relaxed) as f32 / 72.0;
+
+        let pixmap = hayro::render(
+            pdf_page,
+            &RenderCache::new(),
+            &InterpreterSettings::default(),
+            &RenderSettings::default(),
+            &PixmapSettings {
+                x_scale: scale,
+                y_scale: scale,
+                bg_color: WHITE,
+            },
+        );
+
+        // The rasterised page is served as a binary PPM image, as it doesn't require any encoding
+        // As the background is opaque, pixels are not premultiplied and the alpha can be dropped
+        let mut ppm = format!("P6\n{} {}\n255\n", pixmap.width(), pixmap.height()).into_bytes();
+
+        ppm.extend(
+            pixmap
+                .data_as_u8_slice()
+                .chunks_exact(4)
+                .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]),
+        );
+
+        Ok((PathBuf::from(format!("page-{:04}.ppm", page + 1)), ppm))
+    }
+
+    fn quick_clone(&self) -> Result<Box<dyn ImageSource>> {
+        // The document's bytes are shared, but it is parsed again so each clone is independent
+        let pdf = Pdf::new(self.pdf.data().clone())
+            .map_err(|_| anyhow::anyhow!("Failed to parse PDF document"))?;
+
+        Ok(Box::new(Self { pdf }))
+    }
+
+    fn skipped_pages(&self) -> SkippedPages {
+        SkippedPages::default()
+    }
+
+    fn page_name(&self, page: usize) -> Option<String> {
+        // Pages have no name, so they are named after their number like the served images
+        (page < self.total_pages()).then(|| format!("page-{:04}", page + 1))
+    }
+}
+
+/// Get the content of a page made of a single JPEG image (as is common for scanned books)
+/// Returns `None` if the page contains anything else
+fn single_jpeg_image(page: &Page) -> Option<Vec<u8>> {
+    let mut image = None;
+    let mut operations = page.typed_operations();
+
+    while let Some(instruction) = operations.next() {
+        match instruction {
+            // The image may be positioned and scaled
+            TypedInstruction::SaveState(_)
+            | TypedInstruction::RestoreState(_)
+            | TypedInstruction::Transform(_) => {}
+
+            TypedInstruction::XObject(x_object) if image.is_none() => {
+                image = Some(page.resources().get_x_object(x_object.0)?);
+            }
+
+            _ => return None,
+        }
+    }
+
+    let image = image?;
+
+    if image.dict().get::<Name>(keys::SUBTYPE)?.as_ref() != keys::IMAGE
+        || image.filters().as_slice() != [Filter::DctDecode]
+    {
+        return None;
+    }
+
+    Some(image.raw_data().into_owned())
+}
@@ -0,0 +1,178 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::errors::PageError;
+
+use super::{
+    load_image_source, natural_sort::natural_cmp, Chapter, ImageSource, SkippedPages, SortOrder,
+};
+
+/// Several sources read one after the other, as a single book
+/// Each source is a volume, displayed as a chapter
+pub struct Playlist {
+    volumes: Vec<Volume>,
+    total_pages: usize,
+}
+
+/// Source that is part of a playlist
+struct Volume {
+    source: Box<dyn ImageSource>,
+    name: String,
+    first_page: usize,
+}
+
+impl Playlist {
+    /// Load several items as a playlist, ordered by their (natural) name
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut paths = paths.iter().collect::<Vec<_>>();
+        paths.sort_by(|a, b| natural_cmp(a, b));
+
+        let mut volumes = vec![];
+        let mut total_pages = 0;
+
+        for path in paths {
+            let source = load_image_source(path, None)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+
+            let pages = source.total_pages();
+
+            volumes.push(Volume {
+                source,
+                name: volume_name(path),
+                first_page: total_pages,
+            });
+
+            total_pages += pages;
+        }
+
+        Ok(Self {
+            volumes,
+            total_pages,
+        })
+    }
+
+    /// Get the index of the volume a page is in
+    fn volume_index(&self, page: usize) -> Option<usize> {
+        if page >= self.total_pages {
+            return None;
+        }
+
+        self.volumes
+            .iter()
+            .rposition(|volume| volume.first_page <= page)
+    }
+
+    /// Get the volume a page is in
+    fn volume_of(&mut self, page: usize) -> Option<&mut Volume> {
+        let index = self.volume_index(page)?;
+        Some(&mut self.volumes[index])
+    }
+}
+
+impl ImageSource for Playlist {
+    fn total_pages(&self) -> usize {
+        self.total_pages
+    }
+
+    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError> {
+        let volume = self.volume_of(page).ok_or(PageError::OutOfRange { page })?;
+
+        volume.source.load_page(page - volume.first_page)
+    }
+
+    fn quick_clone(&self) -> Result<Box<dyn ImageSource>> {
+        let volumes = self
+            .volumes
+            .iter()
+            .map(|volume| {
+                Ok(Volume {
+                    source: volume.source.quick_clone()?,
+                    name: volume.name.clone(),
+                    first_page: volume.first_page,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Box::new(Self {
+            volumes,
+            total_pages: self.total_pages,
+        }))
+    }
+
+    fn skipped_pages(&self) -> SkippedPages {
+        let mut skipped_pages = SkippedPages::default();
+
+        for volume in &self.volumes {
+            skipped_pages.merge(&volume.source.skipped_pages());
+        }
+
+        skipped_pages
+    }
+
+    fn page_name(&self, page: usize) -> Option<String> {
+        let volume = &self.volumes[self.volume_index(page)?];
+
+        volume.source.page_name(page - volume.first_page)
+    }
+
+    fn page_size_hint(&self, page: usize) -> Option<u64> {
+        let volume = &self.volumes[self.volume_index(page)?];
+
+        volume.source.page_size_hint(page - volume.first_page)
+    }
+
+    fn chapters(&self) -> Vec<Chapter> {
+        // Volumes without any page have the same first page as the next one
+        self.volumes
+            .iter()
+            .enumerate()
+            .filter(|(_, volume)| volume.source.total_pages() > 0)
+            .map(|(i, volume)| Chapter {
+                first_page: volume.first_page,
+                name: format!("vol {} ({})", i + 1, volume.name),
+            })
+            .collect()
+    }
+
+    fn warning(&self) -> Option<String> {
+        let warnings = self
+            .volumes
+            .iter()
+            .filter_map(|volume| {
+                let warning = volume.source.warning()?;
+                Some(format!("{}: {warning}", volume.name))
+            })
+            .collect::<Vec<_>>();
+
+        (!warnings.is_empty()).then(|| warnings.join("\n"))
+    }
+
+    fn sort_pages(&mut self, order: SortOrder) -> Option<Vec<usize>> {
+        let mut previous_indexes = Vec::with_capacity(self.total_pages);
+
+        // Volumes stay in the same order, only their own pages are sorted
+        for volume in &mut self.volumes {
+            let first_page = volume.first_page;
+
+            match volume.source.sort_pages(order) {
+                Some(indexes) => {
+                    previous_indexes.extend(indexes.into_iter().map(|index| first_page + index))
+                }
+                None => {
+                    previous_indexes.extend(first_page..first_page + volume.source.total_pages())
+                }
+            }
+        }
+
+        Some(previous_indexes)
+    }
+}
+
+/// Get the name to display for a volume
+fn volume_name(path: &Path) -> String {
+    path.file_stem()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,178 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use unrar::{
+    error::{Code, UnrarError},
+    Archive,
+};
+
+use crate::{
+    decoders::{is_image_supported, unsupported_image_format},
+    errors::PageError,
+};
+
+use super::{
+    is_junk_file, natural_sort::natural_cmp, ImageSource, Probe, SkippedPages, SourceFactory,
+};
+
+/// RAR archive handler (both RAR4 and RAR5)
+///
+/// The archive is re-opened for each page, as its handles can't be shared between threads
+#[derive(Clone)]
+pub struct RarFile {
+    path: PathBuf,
+    page_files: Vec<PathBuf>,
+    solid: bool,
+    skipped_pages: SkippedPages,
+}
+
+/// Factory for [`RarFile`]
+pub struct RarFileFactory;
+
+impl SourceFactory for RarFileFactory {
+    fn name(&self) -> &'static str {
+        "RAR archive"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["rar", "cbr"]
+    }
+
+    fn probe(&self, path: &Path) -> Probe {
+        if !path.is_file() {
+            return Probe::Decline("not a file".to_owned());
+        }
+
+        let Some(ext) = path.extension() else {
+            return Probe::Decline("file has no extension".to_owned());
+        };
+
+        if self
+            .extensions()
+            .iter()
+            .any(|c_ext| ext.eq_ignore_ascii_case(c_ext))
+        {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline(format!(
+                "extension is not one of: {}",
+                self.extensions().join(", ")
+            ))
+        }
+    }
+
+    fn probe_content(&self, header: &[u8]) -> Probe {
+        // Both RAR4 and RAR5 signatures start the same way
+        if header.starts_with(b"Rar!\x1A\x07") {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline("not a RAR archive".to_owned())
+        }
+    }
+
+    fn load(&self, path: &Path) -> Result<Box<dyn ImageSource>> {
+        Ok(Box::new(RarFile::load(path)?))
+    }
+}
+
+impl RarFile {
+    /// Load a RAR archive
+    pub fn load(path: &Path) -> Result<Self> {
+        let archive = Archive::new(path)
+            .open_for_listing()
+            .context("Failed to open archive")?;
+
+        let solid = archive.is_solid();
+
+        let mut page_files = vec![];
+        let mut skipped_pages = SkippedPages::default();
+
+        for entry in archive {
+            let entry = entry.context("Failed to read file in archive")?;
+
+            if !entry.is_file() || is_junk_file(&entry.filename) {
+                continue;
+            }
+
+            if is_image_supported(&entry.filename) {
+                page_files.push(entry.filename);
+            } else if let Some(format) = unsupported_image_format(&entry.filename) {
+                skipped_pages.add(format);
+            }
+        }
+
+        page_files.sort_by(|a, b| natural_cmp(a, b));
+
+        Ok(Self {
+            path: path.to_owned(),
+            page_files,
+            solid,
+            skipped_pages,
+        })
+    }
+}
+
+impl ImageSource for RarFile {
+    fn total_pages(&self) -> usize {
+        self.page_files.len()
+    }
+
+    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError> {
+        let filename = self
+            .page_files
+            .get(page)
+            .ok_or(PageError::OutOfRange { page })?;
+
+        let mut archive = Archive::new(&self.path)
+            .open_for_processing()
+            .map_err(convert_error)?;
+
+        // Entries can't be accessed directly, so we go through the archive until we find the page
+        // For solid archives, this requires decompressing all the entries before it
+        loop {
+            let Some(entry) = archive.read_header().map_err(convert_error)? else {
+                return Err(PageError::Corrupted {
+                    detail: format!("file {} is missing from archive", filename.display()),
+                });
+            };
+
+            if entry.entry().filename == *filename {
+                let (bytes, _) = entry.read().map_err(convert_error)?;
+                return Ok((filename.clone(), bytes));
+            }
+
+            archive = entry.skip().map_err(convert_error)?;
+        }
+    }
+
+    fn quick_clone(&self) -> Result<Box<dyn ImageSource>> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn skipped_pages(&self) -> SkippedPages {
+        self.skipped_pages.clone()
+    }
+
+    fn page_name(&self, page: usize) -> Option<String> {
+        let filename = self.page_files.get(page)?;
+        Some(filename.to_string_lossy().into_owned())
+    }
+
+    fn warning(&self) -> Option<String> {
+        self.solid.then(|| {
+            "This archive is solid, so its pages can only be extracted sequentially (loading may be slow)"
+                .to_owned()
+        })
+    }
+}
+
+fn convert_error(err: UnrarError) -> PageError {
+    match err.code {
+        Code::EOpen | Code::ERead | Code::EClose => PageError::Io {
+            detail: format!("failed to read archive: {err}"),
+        },
+        _ => PageError::Corrupted {
+            detail: format!("failed to read file in archive: {err}"),
+        },
+    }
+}
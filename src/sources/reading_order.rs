@@ -0,0 +1,103 @@
+use std::{collections::HashMap, path::Path};
+
+/// Names of the files listing the pages of a directory or an archive in their reading order
+/// Each line is the path of a page, relative to the directory or archive (or only its name)
+pub const READING_ORDER_FILE_NAMES: &[&str] = &["reading-order.txt", ".pages.txt"];
+
+/// Reading order manifest applied to the pages of a directory or an archive
+#[derive(Clone)]
+pub struct ReadingOrder {
+    /// Name of the manifest
+    pub file_name: String,
+
+    /// Paths listed in the manifest that don't match any page
+    pub missing_pages: Vec<String>,
+}
+
+impl ReadingOrder {
+    /// Order pages following a manifest's content
+    /// Pages that aren't listed in the manifest are kept at the end, in their current order
+    pub fn apply<T: Clone>(
+        file_name: &str,
+        content: &str,
+        pages: &mut Vec<T>,
+        page_path: impl Fn(&T) -> &Path,
+    ) -> Self {
+        let mut by_path = HashMap::new();
+        let mut by_name = HashMap::new();
+
+        for (index, page) in pages.iter().enumerate() {
+            let path = page_path(page);
+
+            by_path
+                .entry(normalize(&path.to_string_lossy()))
+                .or_insert(index);
+
+            if let Some(name) = path.file_name() {
+                by_name
+                    .entry(name.to_string_lossy().into_owned())
+                    .or_insert(index);
+            }
+        }
+
+        let mut used = vec![false; pages.len()];
+        let mut ordered = Vec::with_capacity(pages.len());
+        let mut missing_pages = vec![];
+
+        // Empty lines and comments are ignored
+        for line in content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            let index = by_path
+                .get(&normalize(line))
+                .or_else(|| by_name.get(line))
+                .copied();
+
+            match index {
+                Some(index) if !used[index] => {
+                    used[index] = true;
+                    ordered.push(pages[index].clone());
+                }
+                // Pages listed several times are only displayed once
+                Some(_) => {}
+                None => missing_pages.push(line.to_owned()),
+            }
+        }
+
+        for (index, page) in pages.iter().enumerate() {
+            if !used[index] {
+                ordered.push(page.clone());
+            }
+        }
+
+        *pages = ordered;
+
+        Self {
+            file_name: file_name.to_owned(),
+            missing_pages,
+        }
+    }
+
+    /// Get a message to display to the user if some listed pages weren't found
+    pub fn warning(&self) -> Option<String> {
+        match self.missing_pages.as_slice() {
+            [] => None,
+            [page] => Some(format!(
+                "Page '{page}' listed in {} was not found",
+                self.file_name
+            )),
+            pages => Some(format!(
+                "{} pages listed in {} were not found",
+                pages.len(),
+                self.file_name
+            )),
+        }
+    }
+}
+
+/// Normalize a page's path so paths written with different separators match
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_owned()
+}
@@ -0,0 +1,284 @@
+use std::{
+    fmt,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+#[cfg(feature = "pdf")]
+use super::pdf_file::PdfFileFactory;
+#[cfg(feature = "rar")]
+use super::rar_file::RarFileFactory;
+#[cfg(feature = "remote")]
+use super::remote_zip::RemoteZipFactory;
+use super::{
+    epub_file::EpubFileFactory, image_directory::ImageDirectoryFactory, tar_file::TarFileFactory,
+    zip_file::ZipFileFactory, ImageSource,
+};
+
+/// Number of bytes read from the beginning of a file to recognize its content
+/// TAR archives have their signature after the header of their first file
+const CONTENT_HEADER_LEN: u64 = 512;
+
+/// Signatures of archive formats no source supports, to tell the user what the file is
+/// This includes the formats whose source is disabled in this build
+const UNSUPPORTED_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"7z\xBC\xAF\x27\x1C", "7z archive"),
+    #[cfg(not(feature = "rar"))]
+    (b"Rar!\x1A\x07", "RAR archive (not supported by this build)"),
+    #[cfg(not(feature = "pdf"))]
+    (b"%PDF-", "PDF document (not supported by this build)"),
+];
+
+/// Outcome of probing a path with a [`SourceFactory`]
+pub enum Probe {
+    /// The source can handle the path, with a confidence from 0 to 100
+    /// When several sources accept a path, the most confident one is used
+    Accept(u8),
+
+    /// The source can't handle the path, for the provided reason
+    Decline(String),
+}
+
+/// Factory able to create an image source from a path
+pub trait SourceFactory: Send + Sync {
+    /// Human-readable name of the source (e.g. "ZIP archive")
+    fn name(&self) -> &'static str;
+
+    /// File extensions handled by the source (used for the "open file" dialog's filter)
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Check if a path can be handled by the source
+    /// e.g. is it a file with a specific extension, etc.
+    fn probe(&self, path: &Path) -> Probe;
+
+    /// Check if a file can be handled by the source from its first bytes
+    /// Only used when no source accepts the file from its path (e.g. it has no extension)
+    fn probe_content(&self, _header: &[u8]) -> Probe {
+        Probe::Decline("content is not recognized".to_owned())
+    }
+
+    /// Load an image set from a path
+    /// Should come after a successful [`SourceFactory::probe`]
+    fn load(&self, path: &Path) -> Result<Box<dyn ImageSource>>;
+
+    /// Load an image set protected by a password
+    /// Sources that don't support passwords ignore it
+    fn load_with_password(&self, path: &Path, _password: &str) -> Result<Box<dyn ImageSource>> {
+        self.load(path)
+    }
+}
+
+/// Registered source, which can be disabled at runtime
+struct RegisteredSource {
+    factory: Box<dyn SourceFactory>,
+    enabled: bool,
+}
+
+/// List of all sources images can be loaded from
+pub struct SourceRegistry {
+    sources: Vec<RegisteredSource>,
+}
+
+impl SourceRegistry {
+    /// Create a registry with all the sources supported by this build
+    pub fn with_builtin_sources() -> Self {
+        let mut registry = Self { sources: vec![] };
+
+        registry.register(Box::new(ImageDirectoryFactory));
+        registry.register(Box::new(ZipFileFactory));
+        #[cfg(feature = "rar")]
+        registry.register(Box::new(RarFileFactory));
+        registry.register(Box::new(TarFileFactory));
+        #[cfg(feature = "pdf")]
+        registry.register(Box::new(PdfFileFactory));
+        registry.register(Box::new(EpubFileFactory));
+        #[cfg(feature = "remote")]
+        registry.register(Box::new(RemoteZipFactory));
+
+        registry
+    }
+
+    /// Register a new source
+    /// In case of equal confidence, sources registered first take precedence
+    pub fn register(&mut self, factory: Box<dyn SourceFactory>) {
+        self.sources.push(RegisteredSource {
+            factory,
+            enabled: true,
+        });
+    }
+
+    /// Enable or disable the sources with the provided name
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        for source in &mut self.sources {
+            if source.factory.name() == name {
+                source.enabled = enabled;
+            }
+        }
+    }
+
+    /// Get the extensions handled by all enabled sources
+    pub fn extensions(&self) -> Vec<&'static str> {
+        self.enabled_sources()
+            .flat_map(|factory| factory.extensions().iter().copied())
+            .collect()
+    }
+
+    /// Get the formats supported by all enabled sources, with their extensions
+    pub fn supported_formats(&self) -> Vec<String> {
+        self.enabled_sources()
+            .map(|factory| match factory.extensions() {
+                [] => factory.name().to_owned(),
+                extensions => format!("{} ({})", factory.name(), extensions.join(", ")),
+            })
+            .collect()
+    }
+
+    /// Load a path with the most confident source, using a password if provided
+    /// If no source accepts the path, files are recognized from their content instead
+    /// If this fails too, the returned error lists the supported formats and the reason
+    /// each source declined the path
+    pub fn load(&self, path: &Path, password: Option<&str>) -> Result<Box<dyn ImageSource>> {
+        let (mut best, declines) = self.most_confident(|factory| factory.probe(path));
+
+        let header = (best.is_none() && path.is_file())
+            .then(|| read_header(path))
+            .flatten();
+
+        if let Some(header) = &header {
+            (best, _) = self.most_confident(|factory| factory.probe_content(header));
+        }
+
+        let Some(factory) = best else {
+            return Err(UnsupportedItem {
+                path: path.to_owned(),
+                detected: describe_item(path, header.as_deref()),
+                supported_formats: self.supported_formats(),
+                declines,
+            }
+            .into());
+        };
+
+        let source = match password {
+            Some(password) => factory.load_with_password(path, password),
+            None => factory.load(path),
+        };
+
+        source.with_context(|| format!("Failed to open item with the '{}' source", factory.name()))
+    }
+
+    /// Get the most confident of the enabled sources for the provided probe,
+    /// as well as the reason of each source that declined
+    fn most_confident(
+        &self,
+        probe: impl Fn(&dyn SourceFactory) -> Probe,
+    ) -> (Option<&dyn SourceFactory>, Vec<String>) {
+        let mut best: Option<(u8, &dyn SourceFactory)> = None;
+        let mut declines = vec![];
+
+        for factory in self.enabled_sources() {
+            match probe(factory) {
+                Probe::Accept(confidence) => {
+                    if best.is_none_or(|(best_confidence, _)| confidence > best_confidence) {
+                        best = Some((confidence, factory));
+                    }
+                }
+
+                Probe::Decline(reason) => declines.push(format!("* {}: {reason}", factory.name())),
+            }
+        }
+
+        (best.map(|(_, factory)| factory), declines)
+    }
+
+    fn enabled_sources(&self) -> impl Iterator<Item = &dyn SourceFactory> {
+        self.sources
+            .iter()
+            .filter(|source| source.enabled)
+            .map(|source| source.factory.as_ref())
+    }
+}
+
+/// Error returned when no source can handle an item
+#[derive(Debug)]
+pub struct UnsupportedItem {
+    pub path: PathBuf,
+    /// What the item was detected to be (e.g. "a file with the .7z extension")
+    pub detected: String,
+    /// Formats supported by the enabled sources
+    pub supported_formats: Vec<String>,
+    /// Reason each source declined the item for
+    pub declines: Vec<String>,
+}
+
+impl fmt::Display for UnsupportedItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Cannot open '{}': it is {}, which is not supported",
+            self.path.display(),
+            self.detected
+        )?;
+
+        writeln!(f, "\nSupported items:")?;
+
+        for format in &self.supported_formats {
+            writeln!(f, "* {format}")?;
+        }
+
+        writeln!(f, "\nDetails:")?;
+        write!(f, "{}", self.declines.join("\n"))
+    }
+}
+
+impl std::error::Error for UnsupportedItem {}
+
+/// Describe what an item that couldn't be opened is, from its path and the first bytes
+/// of its content (for files)
+fn describe_item(path: &Path, header: Option<&[u8]>) -> String {
+    if path.is_dir() {
+        return "a directory".to_owned();
+    }
+
+    if path
+        .to_str()
+        .is_some_and(|path| path.starts_with("http://") || path.starts_with("https://"))
+    {
+        return "a URL".to_owned();
+    }
+
+    if !path.exists() {
+        return "nothing (the item doesn't exist)".to_owned();
+    }
+
+    let file = match path.extension() {
+        Some(ext) => format!("a file with the .{} extension", ext.to_string_lossy()),
+        None => "a file without extension".to_owned(),
+    };
+
+    let content = header.and_then(|header| {
+        UNSUPPORTED_SIGNATURES
+            .iter()
+            .find(|(signature, _)| header.starts_with(signature))
+    });
+
+    match content {
+        Some((_, format)) => format!("{file}, whose content is a {format}"),
+        None => format!("{file}, whose content isn't recognized"),
+    }
+}
+
+/// Read the first bytes of a file, to recognize its content
+fn read_header(path: &Path) -> Option<Vec<u8>> {
+    let mut header = vec![];
+
+    File::open(path)
+        .ok()?
+        .take(CONTENT_HEADER_LEN)
+        .read_to_end(&mut header)
+        .ok()?;
+
+    Some(header)
+}
@@ -0,0 +1,277 @@
+use std::{
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use ureq::{Agent, AgentBuilder};
+use zip_next::ZipArchive;
+
+use crate::errors::PageError;
+
+use super::{
+    shared_file::ReaderError,
+    zip_file::{file_path, list_files, read_file},
+    ImageSource, Probe, SkippedPages, SourceFactory,
+};
+
+/// Size of the blocks remote files are read by
+/// ZIP archives are read with lots of small reads, which would each require a request otherwise
+const BLOCK_SIZE: usize = 256 * 1024;
+
+/// Remote ZIP archive handler, reading the archive through HTTP(S) range requests
+/// Only the archive's central directory and the pages being displayed are downloaded
+pub struct RemoteZip {
+    archive: ZipArchive<HttpReader>,
+    page_files: Vec<usize>,
+    page_names: Vec<PathBuf>,
+    skipped_pages: SkippedPages,
+}
+
+/// Factory for [`RemoteZip`]
+pub struct RemoteZipFactory;
+
+impl SourceFactory for RemoteZipFactory {
+    fn name(&self) -> &'static str {
+        "Remote ZIP archive"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        // URLs can't be picked from the file dialog
+        &[]
+    }
+
+    fn probe(&self, path: &Path) -> Probe {
+        let Some(url) = path.to_str().filter(|url| is_http_url(url)) else {
+            return Probe::Decline("not an HTTP(S) URL".to_owned());
+        };
+
+        let url = url.split(['?', '#']).next().unwrap_or_default();
+
+        if [".zip", ".cbz"]
+            .iter()
+            .any(|ext| url.to_ascii_lowercase().ends_with(ext))
+        {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline("URL doesn't point to a ZIP archive".to_owned())
+        }
+    }
+
+    fn load(&self, path: &Path) -> Result<Box<dyn ImageSource>> {
+        Ok(Box::new(RemoteZip::load(
+            path.to_str().context("Invalid URL")?,
+        )?))
+    }
+}
+
+impl RemoteZip {
+    /// Load a remote ZIP archive
+    pub fn load(url: &str) -> Result<Self> {
+        let reader = HttpReader::open(url)?;
+
+        let mut archive = ZipArchive::new(reader).context("Failed to open archive content")?;
+
+        let mut skipped_pages = SkippedPages::default();
+
+        // Nested archives would need to be downloaded entirely, so they are ignored
+        let (page_files, _) = list_files(&mut archive, &mut skipped_pages)?;
+
+        // Names are kept so they don't need to be read from the archive again
+        let page_names = page_files
+            .iter()
+            .map(|&index| {
+                let file = archive
+                    .by_index_raw(index)
+                    .context("Failed to read file in archive")?;
+
+                Ok(file_path(file.name_raw(), file.name()).unwrap_or_else(|| file.mangled_name()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            archive,
+            page_files,
+            page_names,
+            skipped_pages,
+        })
+    }
+}
+
+impl ImageSource for RemoteZip {
+    fn total_pages(&self) -> usize {
+        self.page_files.len()
+    }
+
+    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError> {
+        let index = *self
+            .page_files
+            .get(page)
+            .ok_or(PageError::OutOfRange { page })?;
+
+        // Network errors are reported as I/O ones
+        read_file(&mut self.archive, index, None)
+    }
+
+    fn quick_clone(&self) -> Result<Box<dyn ImageSource>> {
+        // The archive's central directory is shared, and each clone uses its own connection
+        Ok(Box::new(Self {
+            archive: self.archive.clone(),
+            page_files: self.page_files.clone(),
+            page_names: self.page_names.clone(),
+            skipped_pages: self.skipped_pages.clone(),
+        }))
+    }
+
+    fn skipped_pages(&self) -> SkippedPages {
+        self.skipped_pages.clone()
+    }
+
+    fn page_name(&self, page: usize) -> Option<String> {
+        let name = self.page_names.get(page)?;
+        Some(name.to_string_lossy().into_owned())
+    }
+}
+
+/// Check if a string is an HTTP(S) URL
+fn is_http_url(url: &str) -> bool {
+    ["http://", "https://"].iter().any(|scheme| {
+        url.get(..scheme.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// Reader of a remote file, using HTTP range requests
+struct HttpReader {
+    agent: Agent,
+    url: String,
+    len: u64,
+    position: u64,
+
+    /// Last block read from the file, starting at `buffer_start`
+    buffer: Vec<u8>,
+    buffer_start: u64,
+}
+
+impl HttpReader {
+    /// Open a remote file, which must support range requests
+    fn open(url: &str) -> Result<Self> {
+        let agent = AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .timeout_read(Duration::from_secs(30))
+            .build();
+
+        let response = agent
+            .head(url)
+            .call()
+            .context("Failed to connect to the server")?;
+
+        if response.header("Accept-Ranges") == Some("none") {
+            bail!("Server doesn't support partial downloads, which are required to read remote archives");
+        }
+
+        let len = response
+            .header("Content-Length")
+            .and_then(|len| len.parse().ok())
+            .context("Server didn't provide the archive's size")?;
+
+        Ok(Self {
+            agent,
+            url: url.to_owned(),
+            len,
+            position: 0,
+            buffer: vec![],
+            buffer_start: 0,
+        })
+    }
+
+    /// Download a block of the file, starting at the current position
+    fn fetch_block(&mut self, min_len: usize) -> io::Result<()> {
+        let len = u64::try_from(min_len.max(BLOCK_SIZE)).unwrap();
+        let end = (self.position + len).min(self.len);
+
+        let response = self
+            .agent
+            .get(&self.url)
+            .set("Range", &format!("bytes={}-{}", self.position, end - 1))
+            .call()
+            .map_err(io::Error::other)?;
+
+        // Servers ignoring the range would send the whole file
+        if response.status() != 206 {
+            return Err(io::Error::other(format!(
+                "server answered with status {} instead of partial content",
+                response.status()
+            )));
+        }
+
+        self.buffer.clear();
+
+        response
+            .into_reader()
+            .take(end - self.position)
+            .read_to_end(&mut self.buffer)?;
+
+        self.buffer_start = self.position;
+
+        Ok(())
+    }
+}
+
+impl Clone for HttpReader {
+    fn clone(&self) -> Self {
+        // The buffer isn't shared, as clones read different parts of the file
+        Self {
+            agent: self.agent.clone(),
+            url: self.url.clone(),
+            len: self.len,
+            position: self.position,
+            buffer: vec![],
+            buffer_start: 0,
+        }
+    }
+}
+
+impl Read for HttpReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+
+        let buffer_end = self.buffer_start + self.buffer.len() as u64;
+
+        if self.position < self.buffer_start || self.position >= buffer_end {
+            self.fetch_block(buf.len()).map_err(ReaderError::wrap)?;
+        }
+
+        let offset = usize::try_from(self.position - self.buffer_start).unwrap();
+        let available = &self.buffer[offset..];
+
+        let read = available.len().min(buf.len());
+        buf[..read].copy_from_slice(&available[..read]);
+
+        self.position += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl Seek for HttpReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )
+        })?;
+
+        Ok(self.position)
+    }
+}
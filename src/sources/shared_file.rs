@@ -0,0 +1,102 @@
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    sync::Arc,
+};
+
+/// File handle shared by several readers, each having its own position
+///
+/// Reads are positional, so they don't depend on the handle's cursor and readers running in
+/// different threads don't interfere with each other. Cloning a reader doesn't open the file again.
+#[derive(Clone)]
+pub struct SharedFile {
+    file: Arc<File>,
+    len: u64,
+    position: u64,
+}
+
+impl SharedFile {
+    /// Open a file
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+
+        Ok(Self {
+            file: Arc::new(file),
+            len,
+            position: 0,
+        })
+    }
+}
+
+impl Read for SharedFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = read_at(&self.file, buf, self.position).map_err(ReaderError::wrap)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for SharedFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )
+        })?;
+
+        Ok(self.position)
+    }
+}
+
+/// Error of the file (or connection) an item is read from, as opposed to the errors due to its content
+///
+/// Readers wrap their errors in it, so they can be told apart from the ones of the libraries
+/// reading the item (e.g. invalid compressed data), as they may go away when reading it again
+#[derive(Debug)]
+pub struct ReaderError(io::Error);
+
+impl ReaderError {
+    /// Wrap an error of a reader, keeping its kind
+    pub fn wrap(err: io::Error) -> io::Error {
+        io::Error::new(err.kind(), Self(err))
+    }
+
+    /// Check if an error comes from a reader
+    pub fn is_cause_of(err: &io::Error) -> bool {
+        err.get_ref().is_some_and(|inner| inner.is::<Self>())
+    }
+}
+
+impl fmt::Display for ReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for ReaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    // This moves the handle's cursor as well, but it is never used
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
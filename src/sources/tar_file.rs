@@ -0,0 +1,185 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use tar::Archive;
+
+use crate::{
+    decoders::{is_image_supported, unsupported_image_format},
+    errors::PageError,
+};
+
+use super::{
+    is_junk_file, natural_sort::natural_cmp, ImageSource, Probe, SkippedPages, SourceFactory,
+};
+
+/// TAR archive handler
+/// Files in nested directories are all part of the same pages list
+pub struct TarFile {
+    path: PathBuf,
+    file: File,
+    page_files: Vec<TarEntry>,
+    skipped_pages: SkippedPages,
+}
+
+/// Location of a file's content inside a TAR archive
+#[derive(Clone)]
+struct TarEntry {
+    path: PathBuf,
+    offset: u64,
+    size: u64,
+}
+
+/// Factory for [`TarFile`]
+pub struct TarFileFactory;
+
+impl SourceFactory for TarFileFactory {
+    fn name(&self) -> &'static str {
+        "TAR archive"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["tar", "cbt"]
+    }
+
+    fn probe(&self, path: &Path) -> Probe {
+        if !path.is_file() {
+            return Probe::Decline("not a file".to_owned());
+        }
+
+        let Some(ext) = path.extension() else {
+            return Probe::Decline("file has no extension".to_owned());
+        };
+
+        if self
+            .extensions()
+            .iter()
+            .any(|c_ext| ext.eq_ignore_ascii_case(c_ext))
+        {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline(format!(
+                "extension is not one of: {}",
+                self.extensions().join(", ")
+            ))
+        }
+    }
+
+    fn probe_content(&self, header: &[u8]) -> Probe {
+        // The signature is part of the header of the archive's first file
+        if header.get(257..262) == Some(b"ustar") {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline("not a TAR archive".to_owned())
+        }
+    }
+
+    fn load(&self, path: &Path) -> Result<Box<dyn ImageSource>> {
+        Ok(Box::new(TarFile::load(path)?))
+    }
+}
+
+impl TarFile {
+    /// Load a TAR archive
+    /// The position of each page's content is indexed, so pages can be read without
+    /// scanning the archive again
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).context("Failed to open archive file")?;
+
+        let mut archive = Archive::new(BufReader::new(
+            file.try_clone().context("Failed to open archive file")?,
+        ));
+
+        let mut page_files = vec![];
+        let mut skipped_pages = SkippedPages::default();
+
+        for entry in archive
+            .entries_with_seek()
+            .context("Failed to open archive content")?
+        {
+            let entry = entry.context("Failed to read file in archive")?;
+
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let item_path = entry
+                .path()
+                .context("Failed to read file's path in archive")?;
+
+            if is_junk_file(&item_path) {
+                continue;
+            }
+
+            if is_image_supported(&item_path) {
+                page_files.push(TarEntry {
+                    path: item_path.into_owned(),
+                    offset: entry.raw_file_position(),
+                    size: entry.size(),
+                });
+            } else if let Some(format) = unsupported_image_format(&item_path) {
+                skipped_pages.add(format);
+            }
+        }
+
+        page_files.sort_by(|a, b| natural_cmp(&a.path, &b.path));
+
+        Ok(Self {
+            path: path.to_owned(),
+            file,
+            page_files,
+            skipped_pages,
+        })
+    }
+}
+
+impl ImageSource for TarFile {
+    fn total_pages(&self) -> usize {
+        self.page_files.len()
+    }
+
+    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError> {
+        let entry = self
+            .page_files
+            .get(page)
+            .ok_or(PageError::OutOfRange { page })?;
+
+        let mut out = vec![0; usize::try_from(entry.size).unwrap()];
+
+        self.file
+            .seek(SeekFrom::Start(entry.offset))
+            .and_then(|_| self.file.read_exact(&mut out))
+            .map_err(|err| PageError::Io {
+                detail: format!("failed to read file in archive: {err}"),
+            })?;
+
+        Ok((entry.path.clone(), out))
+    }
+
+    fn quick_clone(&self) -> Result<Box<dyn ImageSource>> {
+        let clone = Self {
+            path: self.path.clone(),
+            file: File::open(&self.path)?,
+            page_files: self.page_files.clone(),
+            skipped_pages: self.skipped_pages.clone(),
+        };
+
+        Ok(Box::new(clone))
+    }
+
+    fn skipped_pages(&self) -> SkippedPages {
+        self.skipped_pages.clone()
+    }
+
+    fn page_name(&self, page: usize) -> Option<String> {
+        let entry = self.page_files.get(page)?;
+        Some(entry.path.to_string_lossy().into_owned())
+    }
+
+    fn page_size_hint(&self, page: usize) -> Option<u64> {
+        Some(self.page_files.get(page)?.size)
+    }
+}
@@ -1,110 +1,720 @@
 use std::{
-    fs::File,
-    io::{self, BufReader},
-    path::{Path, PathBuf},
+    borrow::Cow,
+    io::{self, Cursor, Read, Seek},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, RwLock},
 };
 
 use anyhow::{Context, Result};
-use zip_next::ZipArchive;
+use encoding_rs::{GBK, SHIFT_JIS};
+use serde::{Deserialize, Serialize};
+use zip_next::{result::ZipError, ZipArchive};
 
-use crate::decoders::is_image_supported;
+use crate::{
+    decoders::{
+        is_image_content_supported, is_image_supported, unsupported_image_format, IMAGE_HEADER_LEN,
+    },
+    errors::PageError,
+};
+
+use super::{
+    comic_info::{ComicInfo, COMIC_INFO_FILE_NAME},
+    is_junk_file,
+    natural_sort::natural_cmp,
+    page_order::{sort_pages_by_chapter, SortKey},
+    reading_order::{ReadingOrder, READING_ORDER_FILE_NAMES},
+    shared_file::{ReaderError, SharedFile},
+    Chapter, ImageSource, Metadata, PasswordRequired, Probe, SkippedPages, SortOrder,
+    SourceFactory,
+};
+
+/// Maximum total size of the nested archives, as they are extracted to memory
+const NESTED_ARCHIVES_MAX_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Extensions of the archives that can be opened when nested inside another one
+const NESTED_ARCHIVES_EXTENSIONS: &[&str] = &["zip", "cbz"];
+
+/// Encoding the files' names are decoded with, when they aren't encoded in UTF-8
+/// Set from the application's settings
+pub static ZIP_NAMES_ENCODING: RwLock<ZipNamesEncoding> = RwLock::new(ZipNamesEncoding::Cp437);
 
-use super::ImageSource;
+/// Encoding of the files' names in ZIP archives which don't use UTF-8
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ZipNamesEncoding {
+    /// Encoding specified by the ZIP format
+    Cp437,
+    /// Used by Japanese systems
+    ShiftJis,
+    /// Used by Chinese systems
+    Gbk,
+}
 
 /// ZIP archive handler
+///
+/// When an archive doesn't contain any image but contains other archives (e.g. several volumes),
+/// these are extracted to memory and each of them becomes a chapter
+///
+/// The archive's central directory is only parsed once: clones share it, as well as the file's
+/// handle which is read at the position of each page (see [`SharedFile`])
 pub struct ZipFile {
+    archive: ZipArchive<SharedFile>,
+    nested_archives: Vec<NestedArchive>,
+    page_files: Vec<PageFile>,
+    chapters: Vec<Chapter>,
+    skipped_pages: SkippedPages,
+    skipped_nested_archives: usize,
+    password: Option<Vec<u8>>,
+    fixed_order: bool,
+    metadata: Metadata,
+    reading_order: Option<ReadingOrder>,
+}
+
+/// Archive stored inside the main one
+#[derive(Clone)]
+struct NestedArchive {
     path: PathBuf,
-    archive: ZipArchive<BufReader<File>>,
-    page_file_indexes: Vec<usize>,
+    archive: ZipArchive<Cursor<Arc<[u8]>>>,
 }
 
-impl ImageSource for ZipFile {
-    fn item_matches(path: &Path) -> bool
-    where
-        Self: Sized,
-    {
+/// Archive file found inside another archive, not opened yet
+pub struct NestedArchiveFile {
+    index: usize,
+    path: PathBuf,
+    size: u64,
+}
+
+/// Location of a page
+#[derive(Clone)]
+struct PageFile {
+    /// Index of the nested archive the page is in (if any)
+    nested_archive: Option<usize>,
+
+    /// Index of the page's file in its archive
+    index: usize,
+
+    /// Path of the page's file in its archive
+    path: PathBuf,
+
+    /// Modification time of the page's file, as an ordered value
+    modified: u32,
+
+    /// Compressed size of the page's file
+    size: u64,
+}
+
+impl PageFile {
+    /// Get the information about a page from its archive
+    fn new<R: Read + Seek>(
+        archive: &mut ZipArchive<R>,
+        nested_archive: Option<usize>,
+        index: usize,
+    ) -> Result<Self> {
+        let file = archive
+            .by_index_raw(index)
+            .context("Failed to read file in archive")?;
+
+        let modified = file.last_modified();
+
+        Ok(Self {
+            nested_archive,
+            index,
+            path: file_path(file.name_raw(), file.name()).unwrap_or_else(|| file.mangled_name()),
+            modified: (u32::from(modified.datepart()) << 16) | u32::from(modified.timepart()),
+            size: file.compressed_size(),
+        })
+    }
+
+    fn sort_key(&self) -> SortKey<'_> {
+        SortKey {
+            path: &self.path,
+            modified: Some(u64::from(self.modified)),
+            position: self.index,
+        }
+    }
+}
+
+/// Factory for [`ZipFile`]
+pub struct ZipFileFactory;
+
+impl SourceFactory for ZipFileFactory {
+    fn name(&self) -> &'static str {
+        "ZIP archive"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["zip", "cbz"]
+    }
+
+    fn probe(&self, path: &Path) -> Probe {
         if !path.is_file() {
-            return false;
+            return Probe::Decline("not a file".to_owned());
         }
 
         let Some(ext) = path.extension() else {
-            return false;
+            return Probe::Decline("file has no extension".to_owned());
         };
 
-        let lower_ext = ext.to_ascii_lowercase();
+        if self
+            .extensions()
+            .iter()
+            .any(|c_ext| ext.eq_ignore_ascii_case(c_ext))
+        {
+            Probe::Accept(100)
+        } else {
+            Probe::Decline(format!(
+                "extension is not one of: {}",
+                self.extensions().join(", ")
+            ))
+        }
+    }
+
+    fn probe_content(&self, header: &[u8]) -> Probe {
+        // Archives without any file only have their end of central directory record
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            Probe::Accept(50)
+        } else {
+            Probe::Decline("not a ZIP archive".to_owned())
+        }
+    }
+
+    fn load(&self, path: &Path) -> Result<Box<dyn ImageSource>> {
+        Ok(Box::new(ZipFile::load(path, None)?))
+    }
 
-        lower_ext == "zip" || lower_ext == "cbz"
+    fn load_with_password(&self, path: &Path, password: &str) -> Result<Box<dyn ImageSource>> {
+        Ok(Box::new(ZipFile::load(path, Some(password))?))
     }
+}
 
-    fn load(path: &Path) -> Result<Self>
-    where
-        Self: Sized,
-    {
-        assert!(Self::item_matches(path));
+impl ZipFile {
+    /// Load a ZIP archive, decrypting its files with the provided password if they are encrypted
+    pub fn load(path: &Path, password: Option<&str>) -> Result<Self> {
+        let file = SharedFile::open(path).context("Failed to open archive file")?;
 
-        let file = File::open(path).context("Failed to open archive file")?;
-        let buf = BufReader::new(file);
+        let mut archive = ZipArchive::new(file).context("Failed to open archive content")?;
 
-        let mut archive = ZipArchive::new(buf).context("Failed to open archive content")?;
+        let mut skipped_pages = SkippedPages::default();
 
-        let mut page_files = vec![];
+        let (page_file_indexes, nested_archive_files) =
+            list_files(&mut archive, &mut skipped_pages)?;
 
-        for i in 0..archive.len() {
-            let item = archive
-                .by_index_raw(i)
-                .context("Failed to read file in archive")?;
+        let mut zip = Self::with_pages(archive, page_file_indexes, skipped_pages)?;
 
-            if !item.is_file() {
-                continue;
-            }
+        zip.fixed_order = false;
 
-            let Some(item_path) = item.enclosed_name() else {
-                continue;
-            };
+        zip.password = password.map(|password| password.as_bytes().to_vec());
+        zip.check_password()?;
+
+        if zip.page_files.is_empty() {
+            zip.load_nested_archives(nested_archive_files)?;
+        } else {
+            zip.load_comic_info();
+            zip.load_reading_order();
 
-            if is_image_supported(item_path) {
-                page_files.push((i, item_path.to_path_buf()));
+            // Pages listed in a specific order can't be grouped by folder
+            if !zip.fixed_order {
+                zip.load_folder_chapters(path);
             }
         }
 
-        page_files.sort_by(|(_, a), (_, b)| a.cmp(b));
+        Ok(zip)
+    }
+
+    /// Use an already opened archive, with the pages being the files at the provided indexes
+    /// The pages are kept in the provided order, even when asked to sort them
+    pub fn with_pages(
+        mut archive: ZipArchive<SharedFile>,
+        page_file_indexes: Vec<usize>,
+        skipped_pages: SkippedPages,
+    ) -> Result<Self> {
+        let page_files = page_file_indexes
+            .into_iter()
+            .map(|index| PageFile::new(&mut archive, None, index))
+            .collect::<Result<_>>()?;
 
         Ok(Self {
-            path: path.to_owned(),
             archive,
-            page_file_indexes: page_files.into_iter().map(|(i, _)| i).collect(),
+            nested_archives: vec![],
+            page_files,
+            chapters: vec![],
+            skipped_pages,
+            skipped_nested_archives: 0,
+            password: None,
+            fixed_order: true,
+            metadata: Metadata::default(),
+            reading_order: None,
         })
     }
 
-    fn total_pages(&self) -> usize {
-        self.page_file_indexes.len()
+    /// Ensure the pages can be read, with the provided password if they are encrypted
+    fn check_password(&mut self) -> Result<()> {
+        let Some(first_page) = self.page_files.first() else {
+            return Ok(());
+        };
+
+        let opened = match &self.password {
+            Some(password) => self
+                .archive
+                .by_index_decrypt(first_page.index, password)
+                .map(|file| file.is_ok()),
+            None => self.archive.by_index(first_page.index).map(|_| true),
+        };
+
+        match opened {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(PasswordRequired {
+                wrong_password: true,
+            }
+            .into()),
+            Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED)) => {
+                Err(PasswordRequired {
+                    wrong_password: false,
+                }
+                .into())
+            }
+            // Other errors will be reported when loading the page
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Use the archive's `ComicInfo.xml` file (if any) for the book's metadata and pages' order
+    /// Invalid files are ignored, as they don't prevent reading the book
+    fn load_comic_info(&mut self) {
+        let Some(comic_info) = self
+            .read_text_file(&[COMIC_INFO_FILE_NAME])
+            .and_then(|(_, content)| ComicInfo::parse(&content))
+        else {
+            return;
+        };
+
+        let ComicInfo {
+            metadata,
+            pages,
+            deleted_pages,
+        } = comic_info;
+
+        let mut used = vec![false; self.page_files.len()];
+
+        for page in deleted_pages {
+            if let Some(used) = used.get_mut(page) {
+                *used = true;
+            }
+        }
+
+        let mut order = Vec::with_capacity(self.page_files.len());
+
+        // Pages missing from the specified order are kept at the end
+        for page in pages.into_iter().flatten().chain(0..self.page_files.len()) {
+            if used.get(page) == Some(&false) {
+                used[page] = true;
+                order.push(page);
+            }
+        }
+
+        // If the pages' order is specified, it must be kept when sorting the pages
+        // Many tagging tools list all the pages in their natural order, which isn't a specific one
+        self.fixed_order = order.windows(2).any(|pages| pages[0] > pages[1]);
+
+        self.page_files = order
+            .into_iter()
+            .map(|page| self.page_files[page].clone())
+            .collect();
+        self.metadata = metadata;
     }
 
-    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), String> {
-        let mut file = self
+    /// Use the archive's reading order manifest (if any) for the pages' order
+    /// It takes precedence over the order specified by `ComicInfo.xml`
+    fn load_reading_order(&mut self) {
+        let Some((name, content)) = self.read_text_file(READING_ORDER_FILE_NAMES) else {
+            return;
+        };
+
+        let reading_order =
+            ReadingOrder::apply(&name, &content, &mut self.page_files, |page_file| {
+                &page_file.path
+            });
+
+        self.fixed_order = true;
+        self.metadata.reading_order = Some(name);
+        self.reading_order = Some(reading_order);
+    }
+
+    /// Read the first text file of the archive with one of the provided names (ignoring case)
+    /// Returns the file's actual name and its content
+    fn read_text_file(&mut self, names: &[&str]) -> Option<(String, String)> {
+        let name = self
             .archive
-            .by_index(self.page_file_indexes[page])
-            .map_err(|err| format!("Failed to read file in archive for page {page}: {err}"))?;
+            .file_names()
+            .find(|name| names.iter().any(|c_name| name.eq_ignore_ascii_case(c_name)))
+            .map(str::to_owned)?;
 
-        let mut out = vec![];
+        let file = match &self.password {
+            Some(password) => self
+                .archive
+                .by_name_decrypt(&name, password)
+                .ok()
+                .and_then(Result::ok),
+            None => self.archive.by_name(&name).ok(),
+        };
 
-        io::copy(&mut file, &mut out).map_err(|err| {
-            format!("Failed to read page file's content from archive for page {page}: {err}")
-        })?;
+        let content = io::read_to_string(file?).ok()?;
 
-        Ok((file.mangled_name(), out))
+        Some((name, content))
     }
 
-    fn quick_clone(&self) -> Result<Box<dyn ImageSource>>
-    where
-        Self: Sized,
-    {
+    /// Group the pages by the folder they are in inside the archive, each folder being a chapter
+    /// Pages directly at the root of the archive are named after it
+    fn load_folder_chapters(&mut self, path: &Path) {
+        fn folder(page_file: &PageFile) -> &Path {
+            page_file.path.parent().unwrap_or(Path::new(""))
+        }
+
+        // The order of pages inside each folder is kept
+        self.page_files
+            .sort_by(|a, b| natural_cmp(folder(a), folder(b)));
+
+        for (page, page_file) in self.page_files.iter().enumerate() {
+            let page_folder = folder(page_file);
+
+            if page > 0 && page_folder == folder(&self.page_files[page - 1]) {
+                continue;
+            }
+
+            let name = if page_folder.as_os_str().is_empty() {
+                path.file_stem().unwrap_or(path.as_os_str())
+            } else {
+                page_folder.as_os_str()
+            };
+
+            self.chapters.push(Chapter {
+                first_page: page,
+                name: name.to_string_lossy().into_owned(),
+            });
+        }
+
+        // An archive without any folder doesn't have chapters
+        if self.chapters.len() == 1 {
+            self.chapters.clear();
+        }
+    }
+
+    /// Extract nested archives to memory and add their pages, each archive being a chapter
+    fn load_nested_archives(&mut self, files: Vec<NestedArchiveFile>) -> Result<()> {
+        let mut total_size = 0;
+
+        for NestedArchiveFile { index, path, size } in files {
+            if total_size + size > NESTED_ARCHIVES_MAX_SIZE {
+                self.skipped_nested_archives += 1;
+                continue;
+            }
+
+            total_size += size;
+
+            let mut bytes = Vec::with_capacity(usize::try_from(size).unwrap_or_default());
+
+            io::copy(
+                &mut self
+                    .archive
+                    .by_index(index)
+                    .context("Failed to read nested archive")?,
+                &mut bytes,
+            )
+            .with_context(|| format!("Failed to extract nested archive {}", path.display()))?;
+
+            let mut archive = ZipArchive::new(Cursor::new(Arc::from(bytes)))
+                .with_context(|| format!("Failed to open nested archive {}", path.display()))?;
+
+            // Archives nested any deeper are ignored
+            let (page_file_indexes, _) = list_files(&mut archive, &mut self.skipped_pages)?;
+
+            if page_file_indexes.is_empty() {
+                continue;
+            }
+
+            self.chapters.push(Chapter {
+                first_page: self.page_files.len(),
+                name: path
+                    .file_stem()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .into_owned(),
+            });
+
+            let nested_archive = self.nested_archives.len();
+
+            for index in page_file_indexes {
+                self.page_files
+                    .push(PageFile::new(&mut archive, Some(nested_archive), index)?);
+            }
+
+            self.nested_archives.push(NestedArchive { path, archive });
+        }
+
+        Ok(())
+    }
+}
+
+impl ImageSource for ZipFile {
+    fn total_pages(&self) -> usize {
+        self.page_files.len()
+    }
+
+    fn load_page(&mut self, page: usize) -> Result<(PathBuf, Vec<u8>), PageError> {
+        let &PageFile {
+            nested_archive,
+            index,
+            ..
+        } = self
+            .page_files
+            .get(page)
+            .ok_or(PageError::OutOfRange { page })?;
+
+        match nested_archive {
+            None => read_file(&mut self.archive, index, self.password.as_deref()),
+            Some(nested_archive) => {
+                let NestedArchive { path, archive } = &mut self.nested_archives[nested_archive];
+                let (filename, bytes) = read_file(archive, index, None)?;
+
+                Ok((path.join(filename), bytes))
+            }
+        }
+    }
+
+    fn quick_clone(&self) -> Result<Box<dyn ImageSource>> {
         let clone = Self {
-            path: self.path.clone(),
-            archive: ZipArchive::new(BufReader::new(File::open(&self.path)?))?,
-            page_file_indexes: self.page_file_indexes.clone(),
+            // The file handle and the archive's central directory are shared
+            archive: self.archive.clone(),
+            // Nested archives share their content, which is already in memory
+            nested_archives: self.nested_archives.clone(),
+            page_files: self.page_files.clone(),
+            chapters: self.chapters.clone(),
+            skipped_pages: self.skipped_pages.clone(),
+            skipped_nested_archives: self.skipped_nested_archives,
+            password: self.password.clone(),
+            fixed_order: self.fixed_order,
+            metadata: self.metadata.clone(),
+            reading_order: self.reading_order.clone(),
         };
 
         Ok(Box::new(clone))
     }
+
+    fn skipped_pages(&self) -> SkippedPages {
+        self.skipped_pages.clone()
+    }
+
+    fn page_name(&self, page: usize) -> Option<String> {
+        let page_file = self.page_files.get(page)?;
+
+        let path = match page_file.nested_archive {
+            None => page_file.path.clone(),
+            Some(nested_archive) => self.nested_archives[nested_archive]
+                .path
+                .join(&page_file.path),
+        };
+
+        Some(path.to_string_lossy().into_owned())
+    }
+
+    fn page_size_hint(&self, page: usize) -> Option<u64> {
+        Some(self.page_files.get(page)?.size)
+    }
+
+    fn chapters(&self) -> Vec<Chapter> {
+        self.chapters.clone()
+    }
+
+    fn warning(&self) -> Option<String> {
+        if let Some(warning) = self.reading_order.as_ref().and_then(ReadingOrder::warning) {
+            return Some(warning);
+        }
+
+        match self.skipped_nested_archives {
+            0 => None,
+            count => Some(format!(
+                "{count} nested archive{} skipped: too large to be opened",
+                if count > 1 { "s" } else { "" }
+            )),
+        }
+    }
+
+    fn metadata(&self) -> Metadata {
+        self.metadata.clone()
+    }
+
+    fn sort_pages(&mut self, order: SortOrder) -> Option<Vec<usize>> {
+        if self.fixed_order {
+            return None;
+        }
+
+        Some(sort_pages_by_chapter(
+            &mut self.page_files,
+            &self.chapters,
+            order,
+            PageFile::sort_key,
+        ))
+    }
+}
+
+/// List the images of an archive (naturally sorted by path) as well as the archives nested inside it
+pub fn list_files<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    skipped_pages: &mut SkippedPages,
+) -> Result<(Vec<usize>, Vec<NestedArchiveFile>)> {
+    let mut page_files = vec![];
+    let mut nested_archive_files = vec![];
+    let mut unknown_files = vec![];
+
+    for i in 0..archive.len() {
+        let item = archive
+            .by_index_raw(i)
+            .context("Failed to read file in archive")?;
+
+        if !item.is_file() {
+            continue;
+        }
+
+        let Some(item_path) = file_path(item.name_raw(), item.name()) else {
+            continue;
+        };
+
+        if is_junk_file(&item_path) {
+            continue;
+        }
+
+        if is_image_supported(&item_path) {
+            page_files.push((i, item_path));
+        } else if let Some(format) = unsupported_image_format(&item_path) {
+            skipped_pages.add(format);
+        } else if item_path.extension().is_some_and(|ext| {
+            NESTED_ARCHIVES_EXTENSIONS
+                .iter()
+                .any(|c_ext| ext.eq_ignore_ascii_case(c_ext))
+        }) {
+            nested_archive_files.push(NestedArchiveFile {
+                index: i,
+                path: item_path,
+                size: item.size(),
+            });
+        } else {
+            unknown_files.push((i, item_path));
+        }
+    }
+
+    // Some archives have pages without any extension (e.g. "0001"), so if no page was found
+    // we look for files starting like an image instead
+    // This is only done as a fallback, as reading the beginning of each file is costly
+    if page_files.is_empty() && nested_archive_files.is_empty() {
+        for (i, item_path) in unknown_files {
+            let mut header = Vec::with_capacity(IMAGE_HEADER_LEN);
+
+            // Encrypted files can't be read here, so they are ignored
+            let Ok(item) = archive.by_index(i) else {
+                continue;
+            };
+
+            if item
+                .take(IMAGE_HEADER_LEN as u64)
+                .read_to_end(&mut header)
+                .is_ok()
+                && is_image_content_supported(&header)
+            {
+                page_files.push((i, item_path));
+            }
+        }
+    }
+
+    page_files.sort_by(|(_, a), (_, b)| natural_cmp(a, b));
+    nested_archive_files.sort_by(|a, b| natural_cmp(&a.path, &b.path));
+
+    Ok((
+        page_files.into_iter().map(|(i, _)| i).collect(),
+        nested_archive_files,
+    ))
+}
+
+/// Read a file from an archive, decrypting it with the provided password if it is encrypted
+pub fn read_file<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    index: usize,
+    password: Option<&[u8]>,
+) -> Result<(PathBuf, Vec<u8>), PageError> {
+    let file = match password {
+        Some(password) => archive.by_index_decrypt(index, password),
+        None => archive.by_index(index).map(Ok),
+    };
+
+    let mut file = file
+        .map_err(|err| match err {
+            ZipError::Io(err) => PageError::Io {
+                detail: format!("failed to read file in archive: {err}"),
+            },
+            err => PageError::Corrupted {
+                detail: format!("failed to read file in archive: {err}"),
+            },
+        })?
+        .map_err(|_| PageError::Corrupted {
+            detail: "wrong password for file in archive".to_owned(),
+        })?;
+
+    let path = file_path(file.name_raw(), file.name()).unwrap_or_else(|| file.mangled_name());
+
+    let mut out = vec![];
+
+    // Errors happening here are mostly due to invalid compressed data,
+    // except for the ones of the archive's file itself (e.g. a lost connection)
+    io::copy(&mut file, &mut out).map_err(|err| {
+        let detail = format!(
+            "failed to read content of file '{}' from archive: {err}",
+            path.display()
+        );
+
+        if ReaderError::is_cause_of(&err) {
+            PageError::Io { detail }
+        } else {
+            PageError::Corrupted { detail }
+        }
+    })?;
+
+    Ok((path, out))
+}
+
+/// Get the path of a file inside an archive from its name, decoded with [`ZIP_NAMES_ENCODING`]
+/// if it isn't valid UTF-8 (the name decoded by the ZIP library is used for CP437)
+/// Returns `None` if the path is absolute or goes outside of the archive
+pub fn file_path(raw_name: &[u8], name: &str) -> Option<PathBuf> {
+    // Some archivers use UTF-8 without indicating it, so valid UTF-8 names are always used as-is
+    let name = match std::str::from_utf8(raw_name) {
+        Ok(name) => Cow::Borrowed(name),
+        Err(_) => match *ZIP_NAMES_ENCODING.read().unwrap() {
+            ZipNamesEncoding::Cp437 => Cow::Borrowed(name),
+            ZipNamesEncoding::ShiftJis => SHIFT_JIS.decode_without_bom_handling(raw_name).0,
+            ZipNamesEncoding::Gbk => GBK.decode_without_bom_handling(raw_name).0,
+        },
+    };
+
+    if name.contains('\0') {
+        return None;
+    }
+
+    // Both separators may be used, whatever the system the archive was created on
+    // This must be done after decoding, as the second byte of Shift-JIS characters may be a backslash
+    let name = name.replace('\\', "/");
+
+    let mut path = PathBuf::new();
+
+    for component in Path::new(&name).components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => return None,
+            Component::ParentDir => {
+                if !path.pop() {
+                    return None;
+                }
+            }
+            Component::CurDir => {}
+            Component::Normal(component) => path.push(component),
+        }
+    }
+
+    Some(path)
 }
@@ -0,0 +1,209 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Number of page turns used to compute the reading cadence
+const CADENCE_SAMPLES: usize = 20;
+
+/// Minimum number of page turns before the cadence is considered meaningful
+const CADENCE_MIN_SAMPLES: usize = 5;
+
+/// Bounds of the duration of a single page turn
+/// Anything outside of these is clamped, to avoid e.g. a quick flip through
+/// a few pages or a long pause ruining the estimate
+const MIN_TURN_DURATION: Duration = Duration::from_millis(500);
+const MAX_TURN_DURATION: Duration = Duration::from_secs(180);
+
+/// Time without any input after which the user is considered to not be reading anymore
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Reading statistics of an item, across all the times it was opened
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub struct ItemStats {
+    /// Time actively spent reading the item, in seconds
+    pub reading_time: u64,
+
+    /// Number of pages turned forward
+    pub pages_turned: u64,
+
+    /// Number of times the item was opened
+    pub sessions: u32,
+}
+
+impl ItemStats {
+    /// Average time spent on a single page
+    /// Returns `None` if no page was turned yet
+    pub fn time_per_page(&self) -> Option<Duration> {
+        (self.pages_turned > 0).then(|| {
+            Duration::from_secs(self.reading_time)
+                / u32::try_from(self.pages_turned).unwrap_or(u32::MAX)
+        })
+    }
+
+    /// Describe the statistics in a short human-readable way
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "read for {} ({} session{})",
+            format_duration(Duration::from_secs(self.reading_time)),
+            self.sessions,
+            if self.sessions > 1 { "s" } else { "" }
+        );
+
+        if let Some(time_per_page) = self.time_per_page() {
+            summary.push_str(&format!(", {} s per page", time_per_page.as_secs()));
+        }
+
+        summary
+    }
+}
+
+/// Measures the time actively spent reading, from the user's inputs
+/// Time is only measured when an input happens, so nothing needs to be done while the user is idle
+pub struct ActivityTimer {
+    /// Time of the last input, and the fraction of a second not counted yet
+    last_input: Option<(Instant, Duration)>,
+}
+
+impl ActivityTimer {
+    pub fn new() -> Self {
+        Self { last_input: None }
+    }
+
+    /// Register an input, returning the number of seconds spent reading since the previous one
+    /// Pauses longer than [`IDLE_TIMEOUT`] are only counted up to it
+    pub fn record_input(&mut self) -> u64 {
+        let now = Instant::now();
+
+        let Some((last_input, remainder)) = self.last_input else {
+            self.last_input = Some((now, Duration::ZERO));
+            return 0;
+        };
+
+        let spent = (now - last_input).min(IDLE_TIMEOUT) + remainder;
+        let seconds = spent.as_secs();
+
+        self.last_input = Some((now, spent - Duration::from_secs(seconds)));
+
+        seconds
+    }
+
+    /// Forget about the last input, e.g. when another item is opened
+    pub fn reset(&mut self) {
+        self.last_input = None;
+    }
+}
+
+/// Tracks the page-turn cadence of the user during the current session
+pub struct ReadingCadence {
+    /// Most recent page turns, as (time spent, number of pages advanced)
+    samples: VecDeque<(Duration, usize)>,
+
+    /// Last time the page changed
+    last_turn: Option<Instant>,
+
+    /// Time spent with the application unfocused since the last page turn
+    unfocused_time: Duration,
+
+    /// Moment the application lost focus (if currently unfocused)
+    unfocused_since: Option<Instant>,
+}
+
+impl ReadingCadence {
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::with_capacity(CADENCE_SAMPLES),
+            last_turn: None,
+            unfocused_time: Duration::ZERO,
+            unfocused_since: None,
+        }
+    }
+
+    /// Indicate if the application is currently focused
+    /// Time spent unfocused is not taken into account in the cadence
+    pub fn set_focused(&mut self, focused: bool) {
+        match (focused, self.unfocused_since) {
+            (false, None) => self.unfocused_since = Some(Instant::now()),
+            (true, Some(since)) => {
+                self.unfocused_time += since.elapsed();
+                self.unfocused_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Register a page turn, advancing by the provided number of pages
+    pub fn record_turn(&mut self, pages: usize) {
+        let now = Instant::now();
+
+        if let Some(last_turn) = self.last_turn {
+            let spent = (now - last_turn).saturating_sub(self.unfocused_time);
+
+            if self.samples.len() == CADENCE_SAMPLES {
+                self.samples.pop_front();
+            }
+
+            self.samples
+                .push_back((spent.clamp(MIN_TURN_DURATION, MAX_TURN_DURATION), pages));
+        }
+
+        self.last_turn = Some(now);
+        self.unfocused_time = Duration::ZERO;
+
+        if self.unfocused_since.is_some() {
+            self.unfocused_since = Some(now);
+        }
+    }
+
+    /// Average time spent on a single page
+    /// Returns `None` if not enough page turns were recorded yet
+    pub fn time_per_page(&self) -> Option<Duration> {
+        if self.samples.len() < CADENCE_MIN_SAMPLES {
+            return None;
+        }
+
+        let total_time = self.samples.iter().map(|(time, _)| *time).sum::<Duration>();
+        let total_pages = self.samples.iter().map(|(_, pages)| *pages).sum::<usize>();
+
+        Some(total_time / u32::try_from(total_pages).ok()?.max(1))
+    }
+
+    /// Estimate the time required to read the provided number of pages
+    pub fn estimate(&self, pages: usize) -> Option<Duration> {
+        Some(self.time_per_page()? * u32::try_from(pages).ok()?)
+    }
+}
+
+/// Format a size in bytes in a short human-readable way (e.g. "512 B", "1.5 MiB")
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Format a duration in a short human-readable way (e.g. "1h05", "12 min", "< 1 min")
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+
+    if minutes == 0 {
+        "< 1 min".to_owned()
+    } else if minutes < 60 {
+        format!("{minutes} min")
+    } else {
+        format!("{}h{:02}", minutes / 60, minutes % 60)
+    }
+}
@@ -0,0 +1,74 @@
+use std::{path::Path, process::Command};
+
+use anyhow::{bail, Context, Result};
+
+/// Move a file or a directory to the platform's trash
+pub fn trash(path: &Path) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Failed to find {}", path.display()))?;
+
+    move_to_trash(&path).with_context(|| format!("Failed to move {} to the trash", path.display()))
+}
+
+/// The item is passed through an environment variable, so it doesn't need to be escaped
+#[cfg(target_os = "windows")]
+fn move_to_trash(path: &Path) -> Result<()> {
+    const SCRIPT: &str = "Add-Type -AssemblyName Microsoft.VisualBasic; \
+        $item = $env:READER_TRASHED_ITEM; \
+        if (Test-Path -LiteralPath $item -PathType Container) { \
+            [Microsoft.VisualBasic.FileIO.FileSystem]::DeleteDirectory($item, 'OnlyErrorDialogs', 'SendToRecycleBin') \
+        } else { \
+            [Microsoft.VisualBasic.FileIO.FileSystem]::DeleteFile($item, 'OnlyErrorDialogs', 'SendToRecycleBin') \
+        }";
+
+    run(Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("READER_TRASHED_ITEM", path))
+}
+
+#[cfg(target_os = "macos")]
+fn move_to_trash(path: &Path) -> Result<()> {
+    run(Command::new("osascript")
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "tell application \"Finder\" to delete POSIX file (item 1 of argv)",
+            "-e",
+            "end run",
+        ])
+        .arg(path))
+}
+
+/// The trash is handled by the desktop environment, through one of the tools below
+#[cfg(all(unix, not(target_os = "macos")))]
+fn move_to_trash(path: &Path) -> Result<()> {
+    for (program, args) in [("gio", &["trash"][..]), ("trash-put", &[][..])] {
+        match run(Command::new(program).args(args).arg(path)) {
+            Err(err)
+                if err
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(|err| err.kind() == std::io::ErrorKind::NotFound) => {}
+            result => return result,
+        }
+    }
+
+    bail!("no tool to move items to the trash was found (gio or trash-put)")
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn move_to_trash(_path: &Path) -> Result<()> {
+    bail!("the trash is not supported on this platform")
+}
+
+/// Run a command, failing with its error output if it doesn't succeed
+fn run(command: &mut Command) -> Result<()> {
+    let output = command.output()?;
+
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(())
+}
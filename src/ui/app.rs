@@ -1,25 +1,119 @@
 use std::{
+    collections::HashMap,
     fs,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering, AtomicUsize},
-        Arc, RwLock,
+        Arc, Mutex, RwLock,
     },
-    thread::JoinHandle, cell::RefCell,
+    thread::JoinHandle, cell::{Cell, RefCell},
+    time::{Duration, Instant, SystemTime},
 };
 
 use anyhow::{anyhow, bail, Context as _, Result};
-use egui::{Context, InputState, RichText, Color32, Label, Area, Align2, Vec2, Key, CentralPanel, Frame, Window, Ui, Layout, Align, Spinner,  TextureOptions, ColorImage, vec2, TextureHandle};
+use egui::{Context, InputState, RichText, Color32, Label, Area, Align2, Vec2, Key, CentralPanel, Frame, Window, Ui, Layout, Align, Spinner, TextEdit, ProgressBar, ScrollArea, Pos2, Rect, pos2, Order, Event, TopBottomPanel, CursorIcon, PointerButton, TextureOptions, TouchId, TouchPhase};
+#[cfg(feature = "gamepad")]
+use gilrs::Button;
 use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "gamepad")]
+use std::sync::mpsc::Receiver;
 
 use crate::{
+    errors::PageError,
     gap_vec::GapVec,
-    sources::{load_image_source, ImageSource, EmptySource},
-    settings::Settings,
-    show_err_dialog, LOGICAL_CORES, decoders::{decode_image, DecodedImage},
+    metrics::Metrics,
+    sources::{load_if_changed, load_image_source, load_playlist, series_items, ChangedSource, Chapter, ImageSource, EmptySource, Metadata, PasswordRequired, SourceFingerprint, SOURCE_REGISTRY, ZIP_NAMES_ENCODING},
+    settings::{next_background, Corner, Settings, PagesCounter, FitMode, ScalingFilter, WindowGeometry},
+    stats::{ActivityTimer, ItemStats, ReadingCadence, format_duration, format_size},
+    keybindings::Action,
+    reveal::reveal, trash::trash, watcher::spawn_watcher, LOGICAL_CORES, decoders::{apply_filters, decode_image, decode_preview, encode_png, supported_image_extensions, find_margins, image_dimensions, side_by_side, trim, ColorFilters, DecodeOptions, DecodedImage, Margins, PagePreview},
 };
 
-type PageLoadingResult = Result<(PathBuf, Vec<u8>), String>;
+use super::{
+    bookmarks::{Bookmark, BookmarkAction, BookmarksWindow},
+    cache_progress::show_cache_progress,
+    debug_hud::show_debug_hud,
+    help::show_help,
+    key_bindings::KeyBindingsWindow,
+    page::{DisplayablePage, PageHalf, Rotation},
+    recent_items::{RecentItem, RecentItemsAction, RecentItemsWindow},
+    seek_bar::show_seek_bar,
+    status_bar::show_status_bar,
+    thumbnails::{show_overview, show_strip, Thumbnails},
+    toasts::Toasts,
+    undo::{ItemChange, UndoStack},
+    welcome::{show_welcome, WelcomeAction},
+};
+
+pub(super) type PageLoadingResult = Result<(PathBuf, Vec<u8>), PageError>;
+
+/// Key the recently opened items are saved under in the application's storage
+const RECENT_ITEMS_KEY: &str = "recent_items";
+
+/// Key the rotation of each item is saved under in the application's storage
+const ROTATIONS_KEY: &str = "rotations";
+
+/// Key the bookmarks of each item are saved under in the application's storage
+const BOOKMARKS_KEY: &str = "bookmarks";
+
+/// Key the last read page of each item is saved under in the application's storage
+const READING_POSITIONS_KEY: &str = "reading_positions";
+
+/// Maximum number of items to remember the last read page of
+const MAX_READING_POSITIONS: usize = 500;
+
+/// Maximum number of recently opened items to remember
+const MAX_RECENT_ITEMS: usize = 20;
+
+/// Maximum number of pages to remember to go back to after jumps
+const MAX_PAGE_HISTORY: usize = 50;
+
+/// Jumps happening this close to each other (e.g. when dragging the seek bar) are considered a single one
+const JUMPS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of pages after the current one for which loading threads generate a preview
+const PREVIEWED_PAGES_AHEAD: usize = 8;
+
+/// Distance the pages are moved by when panning them with the arrow keys
+const PAN_STEP: f32 = 120.0;
+
+/// Minimum delay between two page turns when holding a page-turning key
+const KEY_REPEAT_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Factor the zoom is multiplied or divided by when pressing the +/- keys
+const ZOOM_STEP: f32 = 1.25;
+
+/// Minimum horizontal distance and speed (in points per second) of a swipe turning the page
+const SWIPE_MIN_DISTANCE: f32 = 80.0;
+const SWIPE_MIN_SPEED: f32 = 300.0;
+
+/// Pause ending a scrolling gesture, after which scrolling can turn the page again
+const SCROLL_GESTURE_END: Duration = Duration::from_millis(200);
+
+/// Scale above which pages are scaled with the nearest-neighbour filter, with the automatic scaling filter
+const NEAREST_FILTER_MIN_UPSCALE: f32 = 2.0;
+
+/// Time the pages counter is displayed for after a page change, when it's hidden automatically,
+/// and the time it then takes to fade out
+const COUNTER_AUTO_HIDE_DELAY: Duration = Duration::from_secs(2);
+const COUNTER_FADE_DURATION: Duration = Duration::from_millis(500);
+
+/// Brightness levels of the pages which can be cycled through
+const BRIGHTNESS_LEVELS: &[f32] = &[1.0, 0.8, 0.6, 0.4];
+
+/// Bounds of the zoom factor
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 8.0;
+
+/// Title of the window when nothing is opened, and suffix of its title otherwise
+const APP_TITLE: &str = "reader";
+
+/// Minimum delay between two updates of the window's title, so turning pages quickly doesn't flood the window system
+const TITLE_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Size of the normal window, the first time it's displayed
+const DEFAULT_WINDOW_SIZE: Vec2 = Vec2::new(1280.0, 800.0);
 
 pub struct ReaderApp {
     /// [`egui`]'s context
@@ -35,28 +129,293 @@ pub struct ReaderApp {
     /// Application settings
     settings: Arc<RwLock<Settings>>,
 
+    /// Currently opened image source (each loading thread uses its own clone of it)
+    img_source: Box<dyn ImageSource>,
+
     /// Path of the currently opened file or directory (None = no file is opened)
     path: Option<PathBuf>,
 
+    /// Indicates if the current source is made of several items (e.g. volumes dropped at once)
+    /// In this case, each item is a chapter
+    is_playlist: bool,
+
+    /// Position of the current item in its series (= the archives of its directory), and the
+    /// number of archives in it, if there are several of them
+    series_position: Option<(usize, usize)>,
+
+    /// Fingerprint of the currently opened file or directory, used to detect re-openings
+    fingerprint: Option<SourceFingerprint>,
+
     /// Total number of pages in the current file
     total_pages: usize,
 
+    /// Chapters of the current file (if any)
+    chapters: Vec<Chapter>,
+
+    /// Information about the current file, as provided by its source
+    metadata: Metadata,
+
+    /// Reading direction of the current file as specified by its metadata, which takes precedence
+    /// over the settings until the user changes it
+    book_right_to_left: Option<bool>,
+
+    /// Rotation of the current item's pages
+    rotation: Rotation,
+
+    /// Set if the current item's pages are paired with an offset of one in double page mode
+    /// (e.g. when an extra page before the story splits its double-page spreads)
+    pairing_offset: bool,
+
+    /// Rotation of the items the user rotated, so they are displayed the same the next time
+    rotations: HashMap<PathBuf, Rotation>,
+
+    /// Pages bookmarked by the user, for each item
+    bookmarks: HashMap<PathBuf, Vec<Bookmark>>,
+
+    /// Contains the state of the bookmarks list (if opened)
+    bookmarks_window: Option<BookmarksWindow>,
+
+    /// Last page read in each item, to resume reading there when it's opened again
+    reading_positions: HashMap<PathBuf, ReadingPosition>,
+
+    /// Estimated size of each page, used to display the loading progress
+    page_sizes: Vec<Option<u64>>,
+
     /// All loaded pages (as bytes)
     loaded_pages: Arc<RwLock<GapVec<PageLoadingResult>>>,
 
+    /// Low-resolution previews of the pages close to the current one
+    /// They are displayed while the full page is being decoded
+    previews: Arc<RwLock<GapVec<DecodedImage>>>,
+
+    /// Pages close to the current one which were fully decoded instead of being previewed,
+    /// as their format can't be previewed quickly
+    /// They are displayed as soon as they are needed, instead of being decoded again
+    decoded_pages: Arc<RwLock<GapVec<DecodedPage>>>,
+
+    /// Pages to load again (e.g. after a reading error), picked by the first loading thread which sees them
+    retry_queue: Arc<Mutex<Vec<usize>>>,
+
+    /// Width and height of the loaded pages, to lay out the spreads in double page mode before the pages are decoded
+    page_dimensions: Arc<RwLock<GapVec<(usize, usize)>>>,
+
+    /// Timings of the pages' loading, decoding and upload
+    metrics: Arc<RwLock<Metrics>>,
+
     // This is used to allow a rendering closure to store result of the only two
     // pages we may be interested in: the left and right one (in double mode)
     //
     // When the computable image is displayed, we store it here to avoid having to
     // re-compute it on each frame
-    retained_odd_page_image: RefCell<Option<(usize, TextureHandle, Vec2)>>,
-    retained_even_page_image: RefCell<Option<(usize, TextureHandle, Vec2)>>,
+    //
+    // The boolean indicates if the image is only a preview, in which case it will
+    // be replaced by the full one on the next frame
+    retained_odd_page_image: RefCell<Option<(usize, DisplayablePage, bool)>>,
+    retained_even_page_image: RefCell<Option<(usize, DisplayablePage, bool)>>,
+
+    /// Margins detected around each page (when they are trimmed), so they are detected only once
+    /// and the full page is trimmed the same as its preview
+    page_margins: RefCell<HashMap<usize, Option<Margins>>>,
 
     /// Current page number
     current_page: Arc<AtomicUsize>,
 
+    /// Wide page split in two whose second half is displayed (the first one being displayed for the other pages)
+    second_half_of: Option<usize>,
+
+    /// Page number displayed during the previous frame (used to detect page changes)
+    last_seen_page: usize,
+
+    /// When the page last changed, to hide the pages counter a moment after
+    page_changed_at: Instant,
+
     /// Contains the "jump to page" modal's prompt (if opened)
-    page_prompt: Option<String>,
+    page_prompt: Option<PagePrompt>,
+
+    /// Pages displayed before jumping to other ones (e.g. to the first page, or a bookmark), to go back to them
+    page_history: Vec<usize>,
+
+    /// When the last jump happened
+    last_jump_at: Option<Instant>,
+
+    /// Indicates the next page change goes back to a page of the history, so it mustn't be added to it
+    going_back: bool,
+
+    /// Changes made to the current item's bookmarks, rotation, pairing offset and page, to undo them
+    undo: UndoStack,
+
+    /// Contains the password modal's prompt (if opened)
+    password_prompt: Option<PasswordPrompt>,
+
+    /// Set while the user is asked to confirm exiting the application
+    exit_prompt: bool,
+
+    /// Item the user is asked to confirm moving to the trash
+    trash_prompt: Option<PathBuf>,
+
+    /// Set while the keyboard shortcuts are displayed
+    help_shown: bool,
+
+    /// Set once the user asked to exit the application
+    exit_requested: bool,
+
+    /// Set when the window must be switched to the mode from the settings (normal or borderless)
+    window_mode_changed: bool,
+
+    /// Last title given to the window, and when it was
+    window_title: String,
+    window_title_set_at: Instant,
+
+    /// Non-blocking notifications
+    toasts: Toasts,
+
+    /// Page-turn cadence of the user during this session
+    cadence: ReadingCadence,
+
+    /// Reading statistics of the current item
+    item_stats: ItemStats,
+
+    /// Measures the time spent reading the current item
+    activity: ActivityTimer,
+
+    /// Set when the current item changed on disk, so it is reloaded
+    reload_requested: Arc<AtomicBool>,
+
+    /// Recently opened items, the most recent first
+    recent_items: Vec<RecentItem>,
+
+    /// Contains the state of the recent items list (if opened)
+    recent_items_window: Option<RecentItemsWindow>,
+
+    /// Contains the state of the key bindings editor (if opened)
+    key_bindings_window: Option<KeyBindingsWindow>,
+
+    /// Buttons pressed on the gamepads, sent by the thread listening to them
+    #[cfg(feature = "gamepad")]
+    gamepad: Receiver<Result<Button, String>>,
+
+    /// Gamepad buttons pressed since the last frame
+    #[cfg(feature = "gamepad")]
+    gamepad_presses: Vec<Button>,
+
+    /// Summary of the inspection of the current item's pages, once it's finished
+    inspection_summary: Arc<RwLock<Option<String>>>,
+
+    /// Zoom factor applied to the displayed pages (1.0 scales them following the fit mode)
+    zoom: f32,
+
+    /// Offset the displayed pages are moved by, from zooming around the cursor and panning
+    pan_offset: Vec2,
+
+    /// Area covered by the pages displayed during the previous frame, without the offset
+    pages_rect: Option<Rect>,
+
+    /// Area of the window the pages are displayed in (e.g. below the thumbnail strip)
+    pages_area: Rect,
+
+    /// Set when the pages changed, to show their start once they're rendered
+    pan_to_start: bool,
+
+    /// When a page was last turned by holding a page-turning key
+    last_repeated_turn: Instant,
+
+    /// When the slideshow will turn the page (if it's running)
+    slideshow_turn_at: Option<Instant>,
+
+    /// When the mouse was last moved or used, to hide the cursor when it's unused
+    last_pointer_activity: Instant,
+
+    /// Set while the pages are being dragged (as opposed to a window or an overlay, e.g. the seek bar)
+    dragging_pages: bool,
+
+    /// Swipe gesture in progress on a touchscreen
+    swipe: Option<Swipe>,
+
+    /// Scrolling gesture in progress, to turn a single page per gesture
+    scroll_gesture: Option<ScrollGesture>,
+
+    /// Thumbnails of the current item's pages
+    thumbnails: Thumbnails,
+
+    /// Set while the overview (= grid of thumbnails of all pages) is displayed instead of the pages
+    overview: bool,
+
+    /// Last page the overview was scrolled to, so it is only scrolled when the current page changes
+    overview_scrolled_to: Option<usize>,
+
+    /// Pages displayed during the previous frame, in reading order
+    displayed_pages: (Option<usize>, Option<usize>),
+}
+
+/// Last page read in an item
+#[derive(Serialize, Deserialize)]
+struct ReadingPosition {
+    page: usize,
+
+    /// Set if the pages were paired with an offset of one
+    #[serde(default)]
+    pairing_offset: bool,
+
+    /// Reading statistics of the item
+    #[serde(default)]
+    stats: ItemStats,
+
+    /// When the page was read (in seconds since the Unix epoch), to forget the oldest items first
+    read_at: u64,
+}
+
+/// Page fully decoded by a loading thread, before it's displayed
+struct DecodedPage {
+    image: DecodedImage,
+
+    /// Settings the page was decoded with, it must be decoded again if they changed since
+    color_manage: bool,
+    max_dimension: Option<usize>,
+
+    /// Time the decoding took
+    decode_time: Duration,
+}
+
+/// Scrolling gesture, made of the scrolls happening without a pause
+struct ScrollGesture {
+    /// Distance scrolled without panning the pages
+    delta: Vec2,
+    last_scroll_at: Instant,
+
+    /// Set once the gesture turned the page, as it only turns a single one
+    turned: bool,
+}
+
+/// Swipe gesture on a touchscreen, made with a single finger
+struct Swipe {
+    touch_id: TouchId,
+    start: Pos2,
+    started_at: Instant,
+
+    /// Set if the pages were panned during the gesture, in which case it doesn't turn the page
+    panned: bool,
+}
+
+/// Prompt for the page to jump to
+#[derive(Default)]
+struct PagePrompt {
+    /// Page typed so far
+    input: String,
+
+    /// Indicates why the previously submitted page is invalid
+    error: Option<String>,
+}
+
+/// Prompt for the password of an item that couldn't be opened without it
+struct PasswordPrompt {
+    /// Path of the item to open
+    path: PathBuf,
+
+    /// Password typed so far
+    password: String,
+
+    /// Indicates if the previously provided password was wrong
+    wrong_password: bool,
 }
 
 impl ReaderApp {
@@ -71,33 +430,149 @@ impl ReaderApp {
             None => Settings::default(),
         };
 
-        Ok(Self::create(
-            cc.egui_ctx.clone(),
-            match path {
-                Some(ref path) => load_image_source(path)?,
-                // If no path was provided, load a dummy empty source
-                None => Box::new(EmptySource::new())
-            },
-            path,
-            Arc::new(RwLock::new(settings)),
-        ))
+        let recent_items = cc.storage.and_then(|storage| eframe::get_value(storage, RECENT_ITEMS_KEY)).unwrap_or_default();
+        let rotations = cc.storage.and_then(|storage| eframe::get_value(storage, ROTATIONS_KEY)).unwrap_or_default();
+        let bookmarks = cc.storage.and_then(|storage| eframe::get_value(storage, BOOKMARKS_KEY)).unwrap_or_default();
+        let reading_positions = cc.storage.and_then(|storage| eframe::get_value(storage, READING_POSITIONS_KEY)).unwrap_or_default();
+
+        // Disable the image sources the user doesn't want to use
+        for name in &settings.disabled_sources {
+            SOURCE_REGISTRY.write().unwrap().set_enabled(name, false);
+        }
+
+        #[cfg(feature = "pdf")]
+        crate::sources::PDF_RENDER_DPI.store(settings.pdf_render_dpi, Ordering::Relaxed);
+        *ZIP_NAMES_ENCODING.write().unwrap() = settings.zip_names_encoding;
+
+        // The window is always created borderless, so a normal one is only set up afterwards
+        let windowed = settings.windowed;
+
+        let mut app = Self {
+            ctx: cc.egui_ctx.clone(),
+            thread_handles: vec![],
+            threads_stop_signal: Arc::new(AtomicBool::new(false)),
+            settings: Arc::new(RwLock::new(settings)),
+            img_source: Box::new(EmptySource::new()),
+            path: None,
+            is_playlist: false,
+            series_position: None,
+            fingerprint: None,
+            total_pages: 0,
+            chapters: vec![],
+            metadata: Metadata::default(),
+            book_right_to_left: None,
+            rotation: Rotation::None,
+            pairing_offset: false,
+            rotations,
+            bookmarks,
+            bookmarks_window: None,
+            reading_positions,
+            page_sizes: vec![],
+            loaded_pages: Arc::new(RwLock::new(GapVec::new(0))),
+            previews: Arc::new(RwLock::new(GapVec::new(0))),
+            decoded_pages: Arc::new(RwLock::new(GapVec::new(0))),
+            retry_queue: Arc::new(Mutex::new(vec![])),
+            page_dimensions: Arc::new(RwLock::new(GapVec::new(0))),
+            metrics: Arc::new(RwLock::new(Metrics::new())),
+            retained_odd_page_image: RefCell::new(None),
+            retained_even_page_image: RefCell::new(None),
+            page_margins: RefCell::new(HashMap::new()),
+            current_page: Arc::new(AtomicUsize::new(0)),
+            second_half_of: None,
+            last_seen_page: 0,
+            page_changed_at: Instant::now(),
+            page_prompt: None,
+            page_history: vec![],
+            last_jump_at: None,
+            going_back: false,
+            undo: UndoStack::default(),
+            password_prompt: None,
+            exit_prompt: false,
+            trash_prompt: None,
+            help_shown: false,
+            exit_requested: false,
+            window_mode_changed: windowed,
+            window_title: APP_TITLE.to_owned(),
+            window_title_set_at: Instant::now(),
+            toasts: Toasts::default(),
+            cadence: ReadingCadence::new(),
+            item_stats: ItemStats::default(),
+            activity: ActivityTimer::new(),
+            reload_requested: Arc::new(AtomicBool::new(false)),
+            recent_items,
+            recent_items_window: None,
+            key_bindings_window: None,
+            #[cfg(feature = "gamepad")]
+            gamepad: crate::gamepad::spawn_gamepad_listener(cc.egui_ctx.clone()),
+            #[cfg(feature = "gamepad")]
+            gamepad_presses: vec![],
+            inspection_summary: Arc::new(RwLock::new(None)),
+            zoom: 1.0,
+            pan_offset: Vec2::ZERO,
+            pages_rect: None,
+            pages_area: Rect::NOTHING,
+            pan_to_start: false,
+            last_repeated_turn: Instant::now(),
+            slideshow_turn_at: None,
+            last_pointer_activity: Instant::now(),
+            dragging_pages: false,
+            swipe: None,
+            scroll_gesture: None,
+            thumbnails: Thumbnails::new(0),
+            overview: false,
+            overview_scrolled_to: None,
+            displayed_pages: (None, None),
+        };
+
+        for (combo, action, other) in app.settings.read().unwrap().key_bindings.conflicts() {
+            app.toasts.push(format!("{combo} is bound to both \"{}\" and \"{}\" (Ctrl+K to change the key bindings)", action.description(), other.description()));
+        }
+
+        // Start with a dummy empty source, which is replaced if a path was provided
+        app.open_source(Box::new(EmptySource::new()), None, None);
+
+        if let Some(path) = path {
+            app.load_path(path)?;
+        }
+
+        Ok(app)
     }
 
-    /// Create an application with all the required data
-    fn create(
-        ctx: Context,
-        img_source: Box<dyn ImageSource>,
-        path: Option<PathBuf>,
-        settings: Arc<RwLock<Settings>>,
-    ) -> Self {
+    /// Set up all the data related to an image source, replacing the previous one
+    /// All loading threads must have been stopped beforehand
+    fn open_source(&mut self, img_source: Box<dyn ImageSource>, path: Option<PathBuf>, fingerprint: Option<SourceFingerprint>) {
         let total_pages = img_source.total_pages();
-        let loaded_pages = Arc::new(RwLock::new(GapVec::new(img_source.total_pages())));
-        let threads_stop_signal = Arc::new(AtomicBool::new(false));
-        let current_page = Arc::new(AtomicUsize::new(0));
 
-        // We collect here the list of all threads that we'll need to close when e.g.
-        // loading another file
-        let mut thread_handles = vec![];
+        self.open_source_with_pages(img_source, path, fingerprint, GapVec::new(total_pages), GapVec::new(total_pages), GapVec::new(total_pages));
+    }
+
+    /// Set up all the data related to an image source, with some of its pages already loaded
+    /// Only the missing pages are loaded by the loading threads
+    fn open_source_with_pages(&mut self, img_source: Box<dyn ImageSource>, path: Option<PathBuf>, fingerprint: Option<SourceFingerprint>, loaded_pages: GapVec<PageLoadingResult>, previews: GapVec<DecodedImage>, decoded_pages: GapVec<DecodedPage>) {
+        assert!(self.thread_handles.is_empty());
+
+        let total_pages = img_source.total_pages();
+        let chapters = img_source.chapters();
+        let metadata = img_source.metadata();
+        let page_sizes = (0..total_pages).map(|page| img_source.page_size_hint(page)).collect();
+        let missing_pages = (0..total_pages).filter(|&page| loaded_pages.get(page).is_none()).collect::<Vec<_>>();
+        let page_dimensions = Arc::new(RwLock::new(GapVec::new(total_pages)));
+        let retry_queue = Arc::new(Mutex::new(vec![]));
+
+        // The dimensions of the pages which are already loaded are read right away, as no thread will load them
+        for page in 0..total_pages {
+            if let Some(result) = loaded_pages.get(page) {
+                record_dimensions(&page_dimensions, page, result, &self.settings);
+            }
+        }
+
+        let loaded_pages = Arc::new(RwLock::new(loaded_pages));
+        let previews = Arc::new(RwLock::new(previews));
+        let decoded_pages = Arc::new(RwLock::new(decoded_pages));
+        let metrics = Arc::new(RwLock::new(Metrics::new()));
+        let threads_stop_signal = Arc::new(AtomicBool::new(false));
+        let initial_page = img_source.initial_page();
+        let current_page = Arc::new(AtomicUsize::new(initial_page));
 
         // How many loading threads to use
         let threads_count = std::cmp::min(*LOGICAL_CORES, 16);
@@ -106,10 +581,19 @@ impl ReaderApp {
         for thread_num in 0..threads_count {
             let mut img_source = img_source.quick_clone().unwrap();
 
-            let ctx = ctx.clone();
+            // We setup the pages to load here, this is useful when changing priorities below
+            let mut pages_to_load = missing_pages.iter().copied().filter(|i| i % threads_count == thread_num).collect::<Vec<_>>();
+
+            let ctx = self.ctx.clone();
             let thread_stop_signal = Arc::clone(&threads_stop_signal);
             let loaded_pages = Arc::clone(&loaded_pages);
+            let previews = Arc::clone(&previews);
+            let decoded_pages = Arc::clone(&decoded_pages);
+            let page_dimensions = Arc::clone(&page_dimensions);
+            let retry_queue = Arc::clone(&retry_queue);
+            let metrics = Arc::clone(&metrics);
             let current_page = Arc::clone(&current_page);
+            let settings = Arc::clone(&self.settings);
 
             // Each thread loads a part of the pages, depending on its number
             // The loaded pages are (total_threads * n) + thread_number
@@ -117,12 +601,28 @@ impl ReaderApp {
             // For instance, given 8 threads:
             // Thread n°4 will load pages 4, 12, 20, etc.
             // Thread n°6 will load pages 6, 14, 22, etc.
-            thread_handles.push(std::thread::spawn(move || {
-                // We setup the pages to load here, this is useful when changing priorities below
-                let mut pages_to_load = (0..total_pages).filter(|i| i % threads_count == thread_num).collect::<Vec<_>>();
-
+            //
+            // We collect here the list of all threads that we'll need to close when e.g.
+            // loading another file
+            self.thread_handles.push(std::thread::spawn(move || {
                 // Load remaining pages
-                while !pages_to_load.is_empty() {
+                loop {
+                    // Pages to load again are shared between the threads, one at a time
+                    if let Some(page) = retry_queue.lock().unwrap().pop() {
+                        pages_to_load.push(page);
+                    }
+
+                    // Once all pages are loaded, wait for pages to load again or for the application to stop
+                    if pages_to_load.is_empty() {
+                        std::thread::park();
+
+                        if thread_stop_signal.load(Ordering::Acquire) {
+                            return;
+                        }
+
+                        continue;
+                    }
+
                     // The priority is always to load the pages the user is looking at first,
                     // and then the next ones in the image set.
                     // So before loading a page, we always get the first one greater than or equal to
@@ -135,8 +635,20 @@ impl ReaderApp {
                     // ...to remove it and retrieve it
                     let page = pages_to_load.remove(page_index_in_vec);
 
+                    // The page may have been loaded in the meantime (e.g. when inspecting the item's pages)
+                    if loaded_pages.read().unwrap().get(page).is_some() {
+                        continue;
+                    }
+
                     // We load the image from the source
+                    let started = Instant::now();
                     let img = img_source.load_page(page);
+                    metrics.write().unwrap().record_load(page, started.elapsed());
+
+                    let preview_source = (page >= prioritize_loading_from && page - prioritize_loading_from <= PREVIEWED_PAGES_AHEAD).then(|| img.clone());
+
+                    // The page's dimensions are needed to know if it's displayed alone, before it's displayed
+                    record_dimensions(&page_dimensions, page, &img, &settings);
 
                     // Then we save it to the list of loaded pages
                     // Note that the lock is acquired in a single condition, meaning the lock
@@ -147,6 +659,50 @@ impl ReaderApp {
                     // into account the fact we now have new pages data available)
                     ctx.request_repaint();
 
+                    // If the user is about to see this page, we generate a preview for it,
+                    // so there is something to display while the full image is decoded
+                    //
+                    // This is done after the page itself is made available, so a page the user
+                    // is currently waiting for doesn't have to wait for its preview first
+                    if let Some(Ok((filename, bytes))) = &preview_source {
+                        let options = {
+                            let settings = settings.read().unwrap();
+
+                            DecodeOptions {
+                                color_manage: settings.color_manage,
+                                max_dimension: settings.max_decode_dimension,
+                                // Previews have their own size
+                                raster_height: None,
+                                // Only used if the page can't be previewed and is fully decoded instead
+                                threads: settings.decode_threads(),
+                            }
+                        };
+
+                        let started = Instant::now();
+
+                        // Failing to generate a preview is not a problem, the error will be
+                        // reported when decoding the full image
+                        match decode_preview(filename, bytes, options) {
+                            Ok(PagePreview::Preview(preview)) => previews.write().unwrap().set(page, preview),
+                            Ok(PagePreview::Full(image)) => {
+                                let mut decoded_pages = decoded_pages.write().unwrap();
+
+                                // Pages the user went past without displaying them won't be needed anymore
+                                let current_page = current_page.load(Ordering::Acquire);
+
+                                for other in 0..total_pages {
+                                    if other < current_page || other > current_page + PREVIEWED_PAGES_AHEAD {
+                                        decoded_pages.clear(other);
+                                    }
+                                }
+
+                                decoded_pages.set(page, DecodedPage { image, color_manage: options.color_manage, max_dimension: options.max_dimension, decode_time: started.elapsed() });
+                                ctx.request_repaint();
+                            },
+                            Err(_) => {},
+                        }
+                    }
+
                     // If the application indicates it's trying to stop...
                     if thread_stop_signal.load(Ordering::Acquire) {
                         // Just quit the thread!
@@ -155,335 +711,2515 @@ impl ReaderApp {
                 }
             }));
         }
-        
-        Self {
-            ctx,
-            thread_handles,
-            threads_stop_signal,
-            path,
-            settings,
-            total_pages,
-            loaded_pages,
-            retained_odd_page_image: RefCell::new(None),
-            retained_even_page_image: RefCell::new(None),
-            current_page,
-            page_prompt: None,
+
+        // Watch the item for changes, to reload it automatically (if enabled in the settings)
+        if let Some(path) = &path {
+            self.reload_requested.store(false, Ordering::Release);
+
+            self.thread_handles.push(spawn_watcher(
+                path.clone(),
+                Arc::clone(&self.settings),
+                Arc::clone(&threads_stop_signal),
+                Arc::clone(&self.reload_requested),
+                self.ctx.clone(),
+            ));
+        }
+
+        self.threads_stop_signal = threads_stop_signal;
+        self.retry_queue = retry_queue;
+        self.img_source = img_source;
+        self.path = path;
+        self.fingerprint = fingerprint;
+        self.total_pages = total_pages;
+        self.chapters = chapters;
+        self.page_sizes = page_sizes;
+        self.metadata = metadata;
+        self.loaded_pages = loaded_pages;
+        self.previews = previews;
+        self.decoded_pages = decoded_pages;
+        self.page_dimensions = page_dimensions;
+        self.metrics = metrics;
+        self.retained_odd_page_image = RefCell::new(None);
+        self.retained_even_page_image = RefCell::new(None);
+        self.page_margins = RefCell::new(HashMap::new());
+        self.current_page = current_page;
+        self.second_half_of = None;
+        self.last_seen_page = initial_page;
+        self.page_prompt = None;
+        self.page_history.clear();
+        self.last_jump_at = None;
+        self.going_back = false;
+        self.undo.clear();
+        self.inspection_summary = Arc::new(RwLock::new(None));
+        self.thumbnails = Thumbnails::new(total_pages);
+        self.overview = false;
+        self.bookmarks_window = None;
+        self.reset_zoom();
+
+        if self.settings.read().unwrap().inspect_on_open && total_pages > 0 {
+            self.inspect_pages();
         }
     }
 
-    /// Load a new file or directory
-    fn load_path(&mut self, path: PathBuf) -> Result<()> {
-        // Load the image source (to ensure it's valid)
-        let img_source = load_image_source(&path)?;
+    /// Check all the pages of the current item in the background, to tell the user which ones
+    /// are corrupted (e.g. their checksum doesn't match in an archive)
+    /// The pages are loaded like the loading threads do, so the ones already loaded are reused
+    fn inspect_pages(&mut self) {
+        let Ok(mut img_source) = self.img_source.quick_clone() else {
+            self.toasts.push("Failed to inspect the pages");
+            return;
+        };
 
-        // Then indicate all threads they must stop as soon as possible
-        self.threads_stop_signal.store(true, Ordering::Release);
+        let ctx = self.ctx.clone();
+        let thread_stop_signal = Arc::clone(&self.threads_stop_signal);
+        let loaded_pages = Arc::clone(&self.loaded_pages);
+        let inspection_summary = Arc::clone(&self.inspection_summary);
+        let total_pages = self.total_pages;
 
-        // Wait for all threads to finish properly
-        while let Some(thread_handle) = self.thread_handles.pop() {
-            thread_handle.join().map_err(|_| anyhow!("Internal error: failed to join thread"))?;
-        }
+        self.toasts.push("Inspecting pages...");
 
-        // Then re-create the application (which will set up new threads)
-        // NOTE: it's crucial that this function call doesn't fail (e.g. not return an error)
-        //       otherwise, we'd be let with an inconsistent state (no thread to load pages)
-        *self = Self::create(
-            self.ctx.clone(),
-            img_source,
-            Some(path),
-            Arc::clone(&self.settings),
-        );
+        self.thread_handles.push(std::thread::spawn(move || {
+            let mut corrupted_pages = vec![];
 
-        Ok(())
-    }
+            for page in 0..total_pages {
+                if thread_stop_signal.load(Ordering::Acquire) {
+                    return;
+                }
 
-    /// Jump to a neighbour file
-    fn relative_file_change(&mut self, relative: isize) -> Result<()> {
-        assert!(relative == -1 || relative == 1);
+                let loaded = loaded_pages.read().unwrap().get(page).map(|result| result.as_ref().map(|_| ()).map_err(Clone::clone));
 
-        // If there is no open file, we cannot get the list of neighbour ones
-        // So we don't do anything
-        let Some(path) = &self.path else {
-            return Ok(());
-        };
+                let result = match loaded {
+                    Some(result) => result,
+                    None => {
+                        let result = img_source.load_page(page);
+                        let checked = result.as_ref().map(|_| ()).map_err(Clone::clone);
 
-        // Same goes if the opened file doesn't have a parent
-        // (e.g. we opened the root directory)
-        let Some(parent) = path.parent() else {
-            return Ok(())
-        };
+                        // The page may have been loaded by a loading thread in the meantime,
+                        // in which case the result is the same
+                        loaded_pages.write().unwrap().set(page, result);
 
-        // Get all items in the current file's parent directory
-        let items = fs::read_dir(parent)?.collect::<Result<Vec<_>, _>>()?;
+                        checked
+                    }
+                };
 
-        // Find it in the list
-        // Note that it may have been moved between the moment it was opened and now
-        let index = items
-            .iter()
-            .position(|c| &c.path() == path)
-            .context("File not found in parent directory")?;
+                if let Err(PageError::Corrupted { .. }) = result {
+                    corrupted_pages.push(page);
+                }
+            }
 
-        // Check if we can do the jump
-        if -relative > isize::try_from(index).unwrap() {
-            bail!("No previous file in parent directory");
-        }
+            let summary = match corrupted_pages.as_slice() {
+                [] => format!("All {total_pages} pages are valid"),
+                pages => format!(
+                    "{} of {total_pages} pages {} corrupted (page{} {})",
+                    pages.len(),
+                    if pages.len() > 1 { "are" } else { "is" },
+                    if pages.len() > 1 { "s" } else { "" },
+                    pages.iter().map(|page| (page + 1).to_string()).collect::<Vec<_>>().join(", ")
+                ),
+            };
 
-        let index = usize::try_from(isize::try_from(index).unwrap() + relative).unwrap();
+            *inspection_summary.write().unwrap() = Some(summary);
 
-        if index >= items.len() {
-            bail!("No next file in parent directory");
-        }
+            ctx.request_repaint();
+        }));
+    }
 
-        // Jump!
-        self.load_path(items[index].path())
+    /// Load a page again after it failed to be read
+    fn retry_page(&self, page: usize) {
+        self.loaded_pages.write().unwrap().clear(page);
+        self.load_again(&[page]);
     }
 
-    /// Perform a relative page change
-    fn relative_page_change(&mut self, mut inc: isize, shift: bool) {
-        assert!(inc == -1 || inc == 1);
+    /// Discard all loaded pages, their previews and the displayed ones, and load them again
+    /// from the source, starting from the current page
+    fn discard_cache(&mut self) {
+        let mut loaded_pages = self.loaded_pages.write().unwrap();
+        let discarded = loaded_pages.loaded_count();
 
-        let settings = self.settings.read().unwrap();
+        for page in 0..self.total_pages {
+            loaded_pages.clear(page);
+        }
 
-        let current_page = self.current_page.load(Ordering::Acquire);
+        drop(loaded_pages);
 
-        if settings.double_page && !shift && (current_page != 0 || !settings.display_first_page_in_single_mode) {
-            inc *= 2;
+        let mut previews = self.previews.write().unwrap();
+
+        for page in 0..self.total_pages {
+            previews.clear(page);
         }
 
-        // if settings.right_to_left {
-        //     inc *= -1;
-        // }
+        drop(previews);
 
-        if inc < 0 {
-            let dec = usize::try_from(-inc).unwrap();
-            self.current_page.store(if dec >= current_page { 0 } else { current_page - dec }, Ordering::Release);
-        } else {
-            let c_page = current_page + usize::try_from(inc).unwrap();
-            let max_page = if self.total_pages == 0 {
-                0
-            } else {
-                self.total_pages - 1
-            };
+        let mut decoded_pages = self.decoded_pages.write().unwrap();
 
-             self.current_page.store(std::cmp::min(c_page, max_page), Ordering::Release);
+        for page in 0..self.total_pages {
+            decoded_pages.clear(page);
         }
+
+        drop(decoded_pages);
+
+        *self.retained_odd_page_image.borrow_mut() = None;
+        *self.retained_even_page_image.borrow_mut() = None;
+        self.page_margins.borrow_mut().clear();
+
+        // Pages are taken from the end of the queue, so the current one comes last
+        let current_page = self.current_page.load(Ordering::Acquire);
+        let pages = (current_page..self.total_pages).chain(0..current_page).rev().collect::<Vec<_>>();
+
+        self.load_again(&pages);
+
+        self.toasts.push(format!("Discarded {discarded} loaded page{}", if discarded > 1 { "s" } else { "" }));
     }
 
-    /// Handle inputs (keyboard, mouse, etc.) from the UI thread
-    fn handle_inputs(&mut self, i: &InputState) {
-        if i.key_pressed(Key::Home) {
-            self.current_page.store(0, Ordering::Release);
-        }
+    /// Make the loading threads load pages again, in reverse order
+    fn load_again(&self, pages: &[usize]) {
+        self.retry_queue.lock().unwrap().extend_from_slice(pages);
 
-        if i.key_pressed(Key::End) {
-            self.current_page.store(if self.total_pages <= 1 {
-                0
-            } else if self.settings.read().unwrap().double_page {
-                self.total_pages - 2
-            } else {
-                self.total_pages - 1
-            }, Ordering::Release);
+        // Loading threads wait for pages to load again once they're done with the others
+        for thread_handle in &self.thread_handles {
+            thread_handle.thread().unpark();
         }
+    }
 
-        if i.key_pressed(Key::ArrowLeft) || i.scroll_delta.x >= 50.0 || i.scroll_delta.y >= 50.0 {
-            if i.modifiers.ctrl {
-                if let Err(err) = self.relative_file_change(-1) {
-                    show_err_dialog(err);
-                }
+    /// Check if a page was found to be corrupted when loading it
+    fn is_corrupted(&self, page: usize) -> bool {
+        matches!(self.loaded_pages.read().unwrap().get(page), Some(Err(PageError::Corrupted { .. })))
+    }
+
+    /// Get the page a number of pages away from the provided one, skipping corrupted pages
+    /// Stops at the first or last page of the item
+    fn skip_readable_pages(&self, from: usize, count: usize, forward: bool) -> usize {
+        let mut page = from;
+        let mut remaining = count;
+
+        while remaining > 0 {
+            let next = if forward {
+                page + 1
             } else {
-                self.relative_page_change(-1, i.modifiers.shift);
+                match page.checked_sub(1) {
+                    Some(next) => next,
+                    None => break,
+                }
+            };
+
+            if next >= self.total_pages {
+                break;
             }
-        }
 
-        if i.key_pressed(Key::ArrowRight) || i.key_pressed(Key::Space) || i.scroll_delta.x <= -50.0 || i.scroll_delta.y <= -50.0 {
-            if i.modifiers.ctrl {
-                if let Err(err) = self.relative_file_change(1) {
-                    show_err_dialog(err);
-                }
-            } else {
-                self.relative_page_change(1, i.modifiers.shift);
+            page = next;
+
+            if !self.is_corrupted(page) {
+                remaining -= 1;
             }
         }
 
-        if i.key_pressed(Key::O) && i.modifiers.ctrl {
-            let mut dialog = FileDialog::new().add_filter("comics", &["zip", "cbz"]);
+        page
+    }
 
-            if let Some(parent_dir) = self.path.as_ref().and_then(|path| path.parent()) {
-                dialog = dialog.set_directory(parent_dir);
-            }
+    /// Load a new file or directory
+    fn load_path(&mut self, path: PathBuf) -> Result<()> {
+        self.load_path_with_password(path, None)
+    }
 
-            let item = if i.modifiers.shift {
-                dialog.pick_folder()
-            } else {
-                dialog.pick_file()
-            };
+    /// Load a new file or directory, using a password if provided
+    /// If the item requires a (different) password, the password prompt is opened instead
+    fn load_path_with_password(&mut self, path: PathBuf, password: Option<&str>) -> Result<()> {
+        // If the book that's being opened is the one already opened and it didn't change
+        // on disk, we can keep everything loaded so far instead of starting from scratch
+        let loaded = load_if_changed(&path, self.fingerprint.as_ref(), || load_image_source(&path, password));
 
-            if let Some(item) = item {
-                if let Err(err) = self.load_path(item) {
-                    show_err_dialog(err);
+        // Load the image source (to ensure it's valid)
+        let ChangedSource { mut img_source, fingerprint } = match loaded {
+            Ok(Some(changed)) => changed,
+            Ok(None) => {
+                self.password_prompt = None;
+                self.page_prompt = None;
+                return Ok(());
+            }
+            Err(err) => match err.downcast_ref::<PasswordRequired>() {
+                Some(&PasswordRequired { wrong_password }) => {
+                    self.password_prompt = Some(PasswordPrompt { path, password: String::new(), wrong_password });
+                    return Ok(());
                 }
+                None => return Err(err),
             }
-        }
+        };
 
-        if i.key_pressed(Key::D) {
-            let mut settings = self.settings.write().unwrap();
-            settings.double_page = !settings.double_page;
-        }
+        self.password_prompt = None;
 
-        if i.key_pressed(Key::R) {
-            let mut settings = self.settings.write().unwrap();
-            settings.right_to_left = !settings.right_to_left;
+        // A directory without any image but with archives is a series, which starts at its first volume
+        if img_source.total_pages() == 0 && path.is_dir() {
+            if let Some(first_volume) = series_items(&path)?.into_iter().find(|item| item.is_file()) {
+                return self.load_path(first_volume);
+            }
         }
 
-        if i.key_pressed(Key::I) {
-            let mut settings = self.settings.write().unwrap();
-            settings.display_pages_number = !settings.display_pages_number;
-        }
+        img_source.sort_pages(self.settings.read().unwrap().page_sort);
 
-        if i.key_pressed(Key::Escape) {
-            std::process::exit(0);
+        // Tell the user if some pages couldn't be included in the book
+        if let Some(summary) = img_source.skipped_pages().summary() {
+            self.toasts.push(summary);
         }
 
-        if i.key_pressed(Key::G) {
-            self.page_prompt = Some(String::new());
+        if let Some(warning) = img_source.warning() {
+            self.toasts.push(warning);
         }
-    }
 
-    /// Handle file drops from other applications
-    fn handle_file_drops(&mut self, i: &InputState) {
-        let files = &i.raw.dropped_files;
+        // The time spent on the previous item since the last page change is kept
+        self.remember_reading_position();
+
+        self.stop_threads()?;
+
+        self.book_right_to_left = img_source.metadata().right_to_left;
+        self.is_playlist = false;
+        self.series_position = series_position(&path);
+
+        self.remember_recent_item(&path);
+
+        // Then set up the new source (which will set up new threads)
+        // NOTE: it's crucial that this function call doesn't fail (e.g. not return an error)
+        //       otherwise, we'd be let with an inconsistent state (no thread to load pages)
+        self.open_source(img_source, Some(path), fingerprint);
+
+        self.rotation = self.saved_rotation();
+        self.pairing_offset = self.path.as_ref().and_then(|path| self.reading_positions.get(&item_key(path))).is_some_and(|position| position.pairing_offset);
+
+        self.item_stats = self.path.as_ref().and_then(|path| self.reading_positions.get(&item_key(path))).map(|position| position.stats).unwrap_or_default();
+        self.item_stats.sessions += 1;
+        self.activity.reset();
+        self.resume_reading();
+
+        Ok(())
+    }
+
+    /// Load several files or directories, to read them one after the other
+    fn load_playlist(&mut self, paths: Vec<PathBuf>) -> Result<()> {
+        let mut img_source = load_playlist(&paths)?;
+
+        img_source.sort_pages(self.settings.read().unwrap().page_sort);
+
+        if let Some(summary) = img_source.skipped_pages().summary() {
+            self.toasts.push(summary);
+        }
+
+        if let Some(warning) = img_source.warning() {
+            self.toasts.push(warning);
+        }
+
+        self.stop_threads()?;
+
+        self.book_right_to_left = None;
+        self.is_playlist = true;
+        self.series_position = None;
+        self.rotation = Rotation::None;
+        self.pairing_offset = false;
+
+        // There is no single path to a playlist
+        self.open_source(img_source, None, None);
+
+        Ok(())
+    }
+
+    /// Reload the current item from disk, to take changes into account (e.g. new pages)
+    /// Pages that are still part of it are kept instead of being read again, and the
+    /// current page stays the same even if its number changed
+    fn reload(&mut self) -> Result<()> {
+        // Playlists don't have a single path to reload from
+        let Some(path) = self.path.clone() else {
+            return Ok(());
+        };
+
+        // The fingerprint is computed before loading the source, so any change happening in-between is detected the next time
+        let fingerprint = SourceFingerprint::compute(&path).ok();
+
+        let mut img_source = match load_image_source(&path, None) {
+            Ok(img_source) => img_source,
+            // The password is not kept, so it must be provided again
+            Err(err) if err.is::<PasswordRequired>() => return self.load_path(path),
+            Err(err) => return Err(err),
+        };
+
+        img_source.sort_pages(self.settings.read().unwrap().page_sort);
+
+        self.stop_threads()?;
+
+        // Pages are matched by name, as their numbers change when pages are added or removed
+        let previous_pages = (0..self.total_pages)
+            .filter_map(|page| Some((self.img_source.page_name(page)?, page)))
+            .collect::<HashMap<_, _>>();
+
+        let previous_page_num = self.current_page.load(Ordering::Acquire);
+        let previous_page = self.img_source.page_name(previous_page_num);
+
+        let mut loaded_pages = GapVec::new(img_source.total_pages());
+
+        // If the current page was removed, the same page number is kept
+        let mut current_page = std::cmp::min(previous_page_num, img_source.total_pages().saturating_sub(1));
+
+        {
+            let previous_loaded_pages = self.loaded_pages.read().unwrap();
+
+            for page in 0..img_source.total_pages() {
+                let Some(name) = img_source.page_name(page) else {
+                    continue;
+                };
+
+                if previous_page.as_ref() == Some(&name) {
+                    current_page = page;
+                }
+
+                // Pages that failed to load are read again, in case they were fixed
+                if let Some(Ok(loaded)) = previous_pages.get(&name).and_then(|&previous| previous_loaded_pages.get(previous)) {
+                    loaded_pages.set(page, Ok(loaded.clone()));
+                }
+            }
+        }
+
+        let added_pages = img_source.total_pages().saturating_sub(self.total_pages);
+
+        let total_pages = img_source.total_pages();
+
+        self.open_source_with_pages(img_source, Some(path), fingerprint, loaded_pages, GapVec::new(total_pages), GapVec::new(total_pages));
+
+        self.current_page.store(current_page, Ordering::Release);
+        self.last_seen_page = current_page;
+
+        self.toasts.push(match added_pages {
+            0 => "Reloaded".to_owned(),
+            1 => "Reloaded: 1 new page".to_owned(),
+            count => format!("Reloaded: {count} new pages"),
+        });
+
+        Ok(())
+    }
+
+    /// Add an item at the top of the recently opened ones
+    fn remember_recent_item(&mut self, path: &Path) {
+        let path = item_key(path);
+
+        self.recent_items.retain(|item| item.path != path);
+        self.recent_items.insert(0, RecentItem { path, opened_at: unix_time() });
+        self.recent_items.truncate(MAX_RECENT_ITEMS);
+    }
+
+    /// Go to the last page read in the current item, if it was opened before
+    /// This isn't done when a specific page was opened (e.g. an image in a directory)
+    fn resume_reading(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        if self.img_source.initial_page() != 0 {
+            return;
+        }
+
+        let Some(position) = self.reading_positions.get(&item_key(path)) else {
+            return;
+        };
+
+        if position.page == 0 || position.page >= self.total_pages {
+            return;
+        }
+
+        self.current_page.store(position.page, Ordering::Release);
+        self.last_seen_page = position.page;
+
+        self.toasts.push(format!("Resumed at page {} (Home to go back to the first page)", position.page + 1));
+    }
+
+    /// Remember the page the user is reading in the current item
+    fn remember_reading_position(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let read_at = unix_time();
+        let page = self.current_page.load(Ordering::Acquire);
+
+        self.reading_positions.insert(item_key(path), ReadingPosition { page, pairing_offset: self.pairing_offset, stats: self.item_stats, read_at });
+
+        // Forget about the items that weren't read for the longest time
+        if self.reading_positions.len() > MAX_READING_POSITIONS {
+            if let Some(oldest) = self.reading_positions.iter().min_by_key(|(_, position)| position.read_at).map(|(path, _)| path.clone()) {
+                self.reading_positions.remove(&oldest);
+            }
+        }
+    }
+
+    /// Get the rotation the user chose for the current item the last time it was opened
+    fn saved_rotation(&self) -> Rotation {
+        self.path.as_ref().and_then(|path| self.rotations.get(&item_key(path))).copied().unwrap_or_default()
+    }
+
+    /// Rotate the current item's pages, and remember it for the next time it's opened
+    fn set_rotation(&mut self, rotation: Rotation) {
+        self.change_item(ItemChange::Rotation(rotation), "rotation of the pages".to_owned());
+    }
+
+    /// Change the state of the current item, so the change can be undone
+    fn change_item(&mut self, change: ItemChange, description: String) {
+        let inverse = self.apply_change(change);
+        self.undo.push(inverse, description);
+    }
+
+    /// Apply a change to the state of the current item, returning the change reverting it
+    fn apply_change(&mut self, change: ItemChange) -> ItemChange {
+        match change {
+            ItemChange::Bookmarks(bookmarks) => {
+                let Some(path) = &self.path else {
+                    return ItemChange::Bookmarks(vec![]);
+                };
+
+                // Items without bookmarks are forgotten
+                let previous = if bookmarks.is_empty() {
+                    self.bookmarks.remove(&item_key(path))
+                } else {
+                    self.bookmarks.insert(item_key(path), bookmarks)
+                };
+
+                ItemChange::Bookmarks(previous.unwrap_or_default())
+            },
+
+            ItemChange::Rotation(rotation) => {
+                let previous = self.rotation;
+
+                // Pages are rotated when drawn, so the retained ones don't need to be decoded again
+                self.rotation = rotation;
+                self.pan_to_start = true;
+
+                if let Some(path) = &self.path {
+                    if rotation == Rotation::None {
+                        self.rotations.remove(&item_key(path));
+                    } else {
+                        self.rotations.insert(item_key(path), rotation);
+                    }
+                }
+
+                ItemChange::Rotation(previous)
+            },
+
+            ItemChange::PairingOffset(pairing_offset) => {
+                let previous = self.pairing_offset;
+                self.pairing_offset = pairing_offset;
+
+                // The current page is kept on screen
+                let current_page = self.current_page.load(Ordering::Acquire);
+                let spread_start = self.spread_start(current_page, &self.settings.read().unwrap());
+
+                self.current_page.store(spread_start, Ordering::Release);
+                self.remember_reading_position();
+
+                ItemChange::PairingOffset(previous)
+            },
+
+            ItemChange::Page(page) => {
+                let previous = self.current_page.load(Ordering::Acquire);
+
+                // Like when going back, the page we come from isn't added to the history
+                self.current_page.store(page.min(self.total_pages.saturating_sub(1)), Ordering::Release);
+                self.going_back = true;
+
+                ItemChange::Page(previous)
+            },
+        }
+    }
+
+    /// Revert the last change made to the current item
+    fn undo(&mut self) {
+        let Some((inverse, description)) = self.undo.undo() else {
+            self.toasts.push("Nothing to undo");
+            return;
+        };
+
+        let change = self.apply_change(inverse);
+        self.toasts.push(format!("Undid the {description} (Ctrl+Shift+Z to redo it)"));
+        self.undo.push_undone(change, description);
+    }
+
+    /// Make the last undone change again
+    fn redo(&mut self) {
+        let Some((change, description)) = self.undo.redo() else {
+            self.toasts.push("Nothing to redo");
+            return;
+        };
+
+        let inverse = self.apply_change(change);
+        self.toasts.push(format!("Redid the {description}"));
+        self.undo.push_redone(inverse, description);
+    }
+
+    /// Save the current page's file as is (in its original format), to a location picked by the user
+    fn export_page(&mut self) -> Result<()> {
+        let page = self.current_page.load(Ordering::Acquire);
+
+        let (filename, bytes) = match self.loaded_pages.read().unwrap().get(page) {
+            Some(Ok((filename, bytes))) => (filename.clone(), bytes.clone()),
+            Some(Err(err)) => bail!("Page {} couldn't be loaded: {err}", page + 1),
+            None => bail!("Page {} is not loaded yet", page + 1),
+        };
+
+        let extension = filename.extension().and_then(|extension| extension.to_str()).unwrap_or("img");
+
+        let Some(path) = self.pick_export_path(&format!("page {}", page + 1), extension) else {
+            return Ok(());
+        };
+
+        fs::write(&path, &*bytes).with_context(|| format!("Failed to save page {} to {}", page + 1, path.display()))?;
+
+        self.toasts.push(format!("Saved page {} to {}", page + 1, path.display()));
+
+        Ok(())
+    }
+
+    /// Save the displayed pages (both pages of the spread in double page mode) as a single PNG image,
+    /// to a location picked by the user
+    /// The display's adjustments (e.g. colour filters, rotation) are not applied
+    fn export_spread(&mut self) -> Result<()> {
+        let (Some(first_page), second_page) = self.displayed_pages else {
+            bail!("No page is displayed");
+        };
+
+        let settings = self.settings.read().unwrap();
+
+        let options = DecodeOptions {
+            color_manage: settings.color_manage,
+            max_dimension: settings.max_decode_dimension,
+            raster_height: None,
+            threads: settings.decode_threads(),
+        };
+
+        let right_to_left = self.book_right_to_left.unwrap_or(settings.right_to_left);
+        let background = settings.background;
+
+        drop(settings);
+
+        let decode = |page: usize| -> Result<DecodedImage> {
+            match self.loaded_pages.read().unwrap().get(page) {
+                Some(Ok((filename, bytes))) => decode_image(filename, bytes, options).with_context(|| format!("Failed to decode page {}", page + 1)),
+                Some(Err(err)) => bail!("Page {} couldn't be loaded: {err}", page + 1),
+                None => bail!("Page {} is not loaded yet", page + 1),
+            }
+        };
+
+        let (image, name) = match second_page {
+            None => (decode(first_page)?, format!("page {}", first_page + 1)),
+            Some(second_page) => {
+                // Right-to-left spreads start from the right
+                let (left_page, right_page) = if right_to_left { (second_page, first_page) } else { (first_page, second_page) };
+
+                (side_by_side(&decode(left_page)?, &decode(right_page)?, background), format!("pages {}-{}", first_page + 1, second_page + 1))
+            },
+        };
+
+        let Some(path) = self.pick_export_path(&name, "png") else {
+            return Ok(());
+        };
+
+        fs::write(&path, encode_png(&image)).with_context(|| format!("Failed to save {name} to {}", path.display()))?;
+
+        self.toasts.push(format!("Saved {name} to {}", path.display()));
+
+        Ok(())
+    }
+
+    /// Ask the user where to save exported pages, suggesting a file name made of the item's name and the pages' description
+    fn pick_export_path(&self, pages: &str, extension: &str) -> Option<PathBuf> {
+        let item_name = self.path.as_ref().and_then(|path| path.file_stem()).map_or_else(|| "pages".into(), |name| name.to_string_lossy());
+
+        let mut dialog = FileDialog::new().set_file_name(&format!("{item_name} - {pages}.{extension}")).add_filter(extension, &[extension]);
+
+        if let Some(parent_dir) = self.path.as_ref().and_then(|path| path.parent()) {
+            dialog = dialog.set_directory(parent_dir);
+        }
+
+        dialog.save_file()
+    }
+
+    /// Show the current item in the file manager
+    /// For folders, the current page's file is shown instead
+    fn reveal_current(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            bail!("The pages are not from a single item");
+        };
+
+        let page = self.current_page.load(Ordering::Acquire);
+
+        let page_file = path.is_dir().then(|| {
+            let loaded = match self.loaded_pages.read().unwrap().get(page) {
+                Some(Ok((filename, _))) if filename.starts_with(path) => Some(filename.clone()),
+                _ => None,
+            };
+
+            loaded.or_else(|| Some(path.join(self.img_source.page_name(page)?))).filter(|file| file.is_file())
+        }).flatten();
+
+        reveal(page_file.as_deref().unwrap_or(path))
+    }
+
+    /// Move an item to the trash, then open the next item of its directory (if any)
+    fn trash_item(&mut self, path: PathBuf) -> Result<()> {
+        let next_item = match path.parent() {
+            Some(parent) => series_items(parent)?.into_iter().skip_while(|item| *item != path).nth(1),
+            None => None,
+        };
+
+        // The item must be closed first, as it can't be moved while opened on some platforms (e.g. Windows)
+        self.stop_threads()?;
+        self.open_source(Box::new(EmptySource::new()), None, None);
+
+        if let Err(err) = trash(&path) {
+            // Reopen the item, so the user can go on reading it
+            self.load_path(path)?;
+            return Err(err);
+        }
+
+        let key = item_key(&path);
+        self.recent_items.retain(|item| item.path != key);
+
+        self.toasts.push(format!("Moved {} to the trash", path.display()));
+
+        match next_item {
+            Some(next_item) => self.load_path(next_item),
+            None => Ok(()),
+        }
+    }
+
+    /// Ask the user for a file (or a folder) to open
+    fn pick_item(&self, folder: bool) -> Option<PathBuf> {
+        // Images are opened along with the other ones of their folder
+        let mut extensions = SOURCE_REGISTRY.read().unwrap().extensions();
+        extensions.extend(supported_image_extensions());
+
+        let mut dialog = FileDialog::new().add_filter("comics and images", &extensions);
+
+        if let Some(parent_dir) = self.path.as_ref().and_then(|path| path.parent()) {
+            dialog = dialog.set_directory(parent_dir);
+        }
+
+        if folder {
+            dialog.pick_folder()
+        } else {
+            dialog.pick_file()
+        }
+    }
+
+    /// Handle an action requested from the welcome screen
+    fn handle_welcome_action(&mut self, action: WelcomeAction) {
+        let item = match action {
+            WelcomeAction::OpenFile => self.pick_item(false),
+            WelcomeAction::OpenFolder => self.pick_item(true),
+            WelcomeAction::OpenRecent(path) => Some(path),
+            WelcomeAction::ForgetRecent(path) => {
+                self.recent_items.retain(|item| item.path != path);
+                None
+            }
+        };
+
+        if let Some(item) = item {
+            if let Err(err) = self.load_path(item) {
+                self.toasts.push_error(err);
+            }
+        }
+    }
+
+    /// Stop all loading threads
+    fn stop_threads(&mut self) -> Result<()> {
+        // Indicate all threads they must stop as soon as possible
+        self.threads_stop_signal.store(true, Ordering::Release);
+
+        // Wake up the loading threads waiting for pages to load again
+        for thread_handle in &self.thread_handles {
+            thread_handle.thread().unpark();
+        }
+
+        // Wait for all threads to finish properly
+        while let Some(thread_handle) = self.thread_handles.pop() {
+            thread_handle.join().map_err(|_| anyhow!("Internal error: failed to join thread"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Exit the application, letting the loading threads finish properly and the state be saved
+    fn exit(&mut self, frame: &mut eframe::Frame) {
+        // The window is closing, so there is nowhere to display the error
+        if let Err(err) = self.stop_threads() {
+            eprintln!("{err:?}");
+        }
+
+        frame.close();
+    }
+
+    /// Switch the window to the mode from the settings, restoring the normal window's geometry
+    fn apply_window_mode(&mut self, frame: &mut eframe::Frame) {
+        let settings = self.settings.read().unwrap();
+
+        if !settings.windowed {
+            frame.set_decorations(false);
+            frame.set_maximized(true);
+            return;
+        }
+
+        frame.set_maximized(false);
+        frame.set_decorations(true);
+
+        match settings.window_geometry {
+            Some(geometry) => {
+                frame.set_window_pos(pos2(geometry.x, geometry.y));
+                frame.set_window_size(Vec2::new(geometry.width, geometry.height));
+            },
+
+            None => {
+                frame.set_window_size(DEFAULT_WINDOW_SIZE);
+                frame.set_centered();
+            },
+        }
+    }
+
+    /// Remember the geometry of the normal window, to restore it later
+    fn remember_window_geometry(&mut self, frame: &eframe::Frame) {
+        let window_info = frame.info().window_info;
+
+        // The window may not be restored yet right after switching to the normal mode
+        if !self.settings.read().unwrap().windowed || window_info.maximized {
+            return;
+        }
+
+        let Some(position) = window_info.position else {
+            return;
+        };
+
+        let geometry = Some(WindowGeometry { x: position.x, y: position.y, width: window_info.size.x, height: window_info.size.y });
+
+        if self.settings.read().unwrap().window_geometry != geometry {
+            self.settings.write().unwrap().window_geometry = geometry;
+        }
+    }
+
+    /// Update the window's title to show the opened item and the current page
+    fn update_window_title(&mut self, frame: &mut eframe::Frame) {
+        let title = if self.path.is_none() && !self.is_playlist {
+            APP_TITLE.to_owned()
+        } else {
+            let name = match (&self.metadata.title, &self.path) {
+                (Some(title), _) => title.clone(),
+                (None, Some(path)) => path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned()),
+                (None, None) => "Several items".to_owned(),
+            };
+
+            let current_page = self.current_page.load(Ordering::Acquire);
+            let (page, total_pages) = self.displayed_page_number(current_page, &self.settings.read().unwrap());
+
+            format!("{name} — {}/{} — {APP_TITLE}", (page + 1).min(total_pages), total_pages)
+        };
+
+        if title == self.window_title {
+            return;
+        }
+
+        // The last title is set once the delay is elapsed
+        let elapsed = self.window_title_set_at.elapsed();
+
+        if elapsed < TITLE_UPDATE_INTERVAL {
+            self.ctx.request_repaint_after(TITLE_UPDATE_INTERVAL - elapsed);
+            return;
+        }
+
+        frame.set_window_title(&title);
+
+        self.window_title = title;
+        self.window_title_set_at = Instant::now();
+    }
+
+    /// Sort the pages of the current source again, using the sort order from the settings
+    /// The current page stays on the same image, even if its position changes
+    fn sort_pages(&mut self) -> Result<()> {
+        let order = self.settings.read().unwrap().page_sort;
+
+        // The source's listing is kept, only the pages' order changes
+        let Some(previous_indexes) = self.img_source.sort_pages(order) else {
+            self.toasts.push(format!("Pages will be sorted by {} (except for this item, as its pages have a fixed order)", order.description()));
+            return Ok(());
+        };
+
+        self.toasts.push(format!("Pages sorted by {}", order.description()));
+
+        let current_page = self.current_page.load(Ordering::Acquire);
+
+        self.stop_threads()?;
+
+        // The pages which are already loaded are moved to their new position instead of being loaded again
+        let mut loaded_pages = std::mem::replace(&mut *self.loaded_pages.write().unwrap(), GapVec::new(0));
+        let mut previews = std::mem::replace(&mut *self.previews.write().unwrap(), GapVec::new(0));
+        let mut decoded_pages = std::mem::replace(&mut *self.decoded_pages.write().unwrap(), GapVec::new(0));
+
+        loaded_pages.reorder(&previous_indexes);
+        previews.reorder(&previous_indexes);
+        decoded_pages.reorder(&previous_indexes);
+
+        let img_source = std::mem::replace(&mut self.img_source, Box::new(EmptySource::new()));
+        let path = self.path.take();
+        let fingerprint = self.fingerprint.take();
+
+        self.open_source_with_pages(img_source, path, fingerprint, loaded_pages, previews, decoded_pages);
+
+        let new_page = sorted_page(current_page, &previous_indexes);
+        self.current_page.store(new_page, Ordering::Release);
+        self.last_seen_page = new_page;
+
+        // The bookmarks and the reading position keep naming the same pages
+        if let Some(path) = &self.path {
+            let key = item_key(path);
+
+            if let Some(bookmarks) = self.bookmarks.get_mut(&key) {
+                sort_bookmarks(bookmarks, &previous_indexes);
+            }
+
+            if let Some(position) = self.reading_positions.get_mut(&key) {
+                position.page = sorted_page(position.page, &previous_indexes);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Jump to a neighbour file
+    fn relative_file_change(&mut self, relative: isize) -> Result<()> {
+        assert!(relative == -1 || relative == 1);
+
+        // If there is no open file, we cannot get the list of neighbour ones
+        // So we don't do anything
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        // Same goes if the opened file doesn't have a parent
+        // (e.g. we opened the root directory)
+        let Some(parent) = path.parent() else {
+            return Ok(())
+        };
+
+        // Get all items in the current file's parent directory, in natural order
+        let items = series_items(parent)?;
+
+        // Find it in the list
+        // Note that it may have been moved between the moment it was opened and now
+        let index = items
+            .iter()
+            .position(|c| c == path)
+            .context("File not found in parent directory")?;
+
+        // Check if we can do the jump
+        if -relative > isize::try_from(index).unwrap() {
+            self.toasts.push("No previous file in parent directory");
+            return Ok(());
+        }
+
+        let index = usize::try_from(isize::try_from(index).unwrap() + relative).unwrap();
+
+        if index >= items.len() {
+            self.toasts.push("No next file in parent directory");
+            return Ok(());
+        }
+
+        // Jump!
+        self.load_path(items[index].clone())
+    }
+
+    /// Perform a relative page change
+    fn relative_page_change(&mut self, inc: isize, shift: bool) {
+        assert!(inc == -1 || inc == 1);
+
+        let settings = self.settings.read().unwrap();
+
+        let current_page = self.current_page.load(Ordering::Acquire);
+
+        // The direction is the reading one here, the horizontal inputs being mirrored beforehand in right-to-left mode (see `mirrored_navigation`)
+
+        // Wide pages split in two are read one half after the other
+        if self.is_split(current_page, &settings) {
+            let second_half = self.second_half_of == Some(current_page);
+
+            if inc > 0 && !second_half {
+                self.second_half_of = Some(current_page);
+                self.pan_to_start = true;
+                return;
+            }
+
+            if inc < 0 && second_half {
+                self.second_half_of = None;
+                self.pan_to_start = true;
+                return;
+            }
+        }
+
+        // Going past the last spread goes back to the first one, and the other way around
+        if settings.wrap_navigation && self.total_pages > 0 {
+            let last_spread = self.last_spread_start(&settings);
+
+            if inc > 0 && current_page >= last_spread {
+                self.current_page.store(0, Ordering::Release);
+                return;
+            }
+
+            if inc < 0 && current_page == 0 {
+                self.current_page.store(last_spread, Ordering::Release);
+                self.second_half_of = self.is_split(last_spread, &settings).then_some(last_spread);
+                return;
+            }
+        }
+
+        let double_page = settings.double_page && !shift;
+        let new_page = turned_page(current_page, inc, self.total_pages, double_page, self.first_page_single(&settings), |forward| self.neighbour_spread_start(current_page, forward, &settings));
+
+        let auto_advance = settings.auto_advance_items;
+
+        // Going backwards to a wide page split in two starts with its second half
+        if inc < 0 && new_page != current_page && self.is_split(new_page, &settings) {
+            self.second_half_of = Some(new_page);
+        }
+
+        drop(settings);
+
+        self.current_page.store(new_page, Ordering::Release);
+
+        // If we were already at the first or last page, go to the neighbour item
+        if new_page == current_page && auto_advance {
+            self.advance_to_neighbour_item(inc);
+        }
+    }
+
+    /// Open the neighbour item, when going past the first or last page of the current one
+    /// The previous item is opened at its last page, so it can be read backwards
+    fn advance_to_neighbour_item(&mut self, relative: isize) {
+        // Reaching the end of a series isn't an error
+        if let Err(err) = self.relative_file_change(relative) {
+            self.toasts.push(format!("{err:#}"));
+            return;
+        }
+
+        if relative < 0 {
+            let last_spread = self.last_spread_start(&self.settings.read().unwrap());
+
+            self.current_page.store(last_spread, Ordering::Release);
+            self.last_seen_page = last_spread;
+        }
+    }
+
+    /// Get the opacity of the pages counter when it's hidden automatically, depending on the time since the last page change
+    /// Repaints are scheduled until it's fully hidden
+    fn counter_opacity(&self) -> f32 {
+        let elapsed = self.page_changed_at.elapsed();
+
+        if elapsed < COUNTER_AUTO_HIDE_DELAY {
+            self.ctx.request_repaint_after(COUNTER_AUTO_HIDE_DELAY - elapsed);
+            return 1.0;
+        }
+
+        let fading = (elapsed - COUNTER_AUTO_HIDE_DELAY).as_secs_f32() / COUNTER_FADE_DURATION.as_secs_f32();
+
+        if fading < 1.0 {
+            self.ctx.request_repaint();
+        }
+
+        (1.0 - fading).max(0.0)
+    }
+
+    /// Get the first page of the last spread in double page mode, or the last page otherwise
+    fn last_spread_start(&self, settings: &Settings) -> usize {
+        let last_page = self.total_pages.saturating_sub(1);
+
+        if settings.double_page {
+            self.spread_start(last_page, settings)
+        } else {
+            last_page
+        }
+    }
+
+    /// Check if a page is wide enough (once rotated) to be displayed alone in double page mode
+    /// Pages whose dimensions aren't known yet are considered narrow
+    fn is_wide(&self, page: usize, settings: &Settings) -> bool {
+        let Some(ratio) = settings.wide_page_ratio else {
+            return false;
+        };
+
+        let Some(&(width, height)) = self.page_dimensions.read().unwrap().get(page) else {
+            return false;
+        };
+
+        let size = self.rotation.rotate_size(Vec2::new(width as f32, height as f32));
+
+        size.y > 0.0 && size.x / size.y > ratio
+    }
+
+    /// Check if a page is split in two halves, read one after the other
+    fn is_split(&self, page: usize, settings: &Settings) -> bool {
+        settings.split_wide_pages && !settings.double_page && self.is_wide(page, settings)
+    }
+
+    /// Get the half of a page to display, if it's split in two
+    /// The first half is the right one in right-to-left mode
+    fn displayed_half(&self, page: usize, settings: &Settings) -> Option<PageHalf> {
+        if !self.is_split(page, settings) {
+            return None;
+        }
+
+        let first_half = if self.book_right_to_left.unwrap_or(settings.right_to_left) { PageHalf::Right } else { PageHalf::Left };
+
+        Some(if self.second_half_of == Some(page) { first_half.other() } else { first_half })
+    }
+
+    /// Get the number of a page as displayed to the user, along with the total number of pages,
+    /// as wide pages split in two count as two pages
+    fn displayed_page_number(&self, page: usize, settings: &Settings) -> (usize, usize) {
+        if !settings.split_wide_pages || settings.double_page {
+            return (page, self.total_pages);
+        }
+
+        let split_before = (0..page).filter(|&other| self.is_split(other, settings)).count();
+        let split_after = (page..self.total_pages).filter(|&other| self.is_split(other, settings)).count();
+        let second_half = usize::from(self.second_half_of == Some(page));
+
+        (page + split_before + second_half, self.total_pages + split_before + split_after)
+    }
+
+    /// Check if a page is displayed alone in double page mode, because it or the next one is wide
+    fn alone_in_spread(&self, page: usize, settings: &Settings) -> bool {
+        self.is_wide(page, settings) || self.is_wide(self.skip_readable_pages(page, 1, true), settings)
+    }
+
+    /// Get the first page of the spread a page belongs to, in double page mode
+    /// Wide pages occupy a whole spread, the pages after them being paired from there
+    fn spread_start(&self, page: usize, settings: &Settings) -> usize {
+        if self.is_wide(page, settings) {
+            return page;
+        }
+
+        match (0..page).rev().find(|&previous| self.is_wide(previous, settings)) {
+            Some(wide_page) => page - (page - wide_page - 1) % 2,
+            None => spread_start(page, self.first_page_single(settings)),
+        }
+    }
+
+    /// Get the first page of the next or previous spread in double page mode
+    /// Corrupted pages are skipped, so they don't break the spreads, and wide pages occupy a whole spread
+    /// The provided page is returned if there is no spread after it
+    fn neighbour_spread_start(&self, page: usize, forward: bool, settings: &Settings) -> usize {
+        if forward {
+            let spread_end = if self.alone_in_spread(page, settings) { page } else { self.skip_readable_pages(page, 1, true) };
+            let next = self.skip_readable_pages(spread_end, 1, true);
+
+            // Only corrupted pages (if any) come after the spread
+            return if next == spread_end || self.is_corrupted(next) { page } else { next };
+        }
+
+        let previous = self.skip_readable_pages(page, 1, false);
+        let before_previous = self.skip_readable_pages(page, 2, false);
+
+        if self.is_wide(previous, settings) || self.is_wide(before_previous, settings) {
+            previous
+        } else {
+            before_previous
+        }
+    }
+
+    /// Check if the first page is displayed alone in double page mode, which determines how the pages are paired
+    fn first_page_single(&self, settings: &Settings) -> bool {
+        settings.display_first_page_in_single_mode != self.pairing_offset
+    }
+
+    /// Pair the pages with an offset of one (or stop doing so), keeping the current page on screen
+    fn toggle_pairing_offset(&mut self) {
+        self.change_item(ItemChange::PairingOffset(!self.pairing_offset), "change of the pages' pairing".to_owned());
+
+        self.toasts.push(if self.pairing_offset { "Pages paired with an offset of one" } else { "Pages paired normally" });
+    }
+
+    /// Start or stop turning the pages automatically
+    fn toggle_slideshow(&mut self) {
+        if self.slideshow_turn_at.take().is_some() {
+            self.toasts.push("Slideshow stopped");
+            return;
+        }
+
+        let delay = self.settings.read().unwrap().slideshow_delay_secs;
+
+        self.slideshow_turn_at = Some(Instant::now() + Duration::from_secs(delay));
+        self.toasts.push(format!("Slideshow started, turning the page every {delay} seconds (P to stop)"));
+    }
+
+    /// Turn the page if the slideshow is running and the current page was displayed long enough
+    fn update_slideshow(&mut self, ctx: &Context) {
+        let Some(turn_at) = self.slideshow_turn_at else {
+            return;
+        };
+
+        let delay = Duration::from_secs(self.settings.read().unwrap().slideshow_delay_secs);
+        let current_page = self.current_page.load(Ordering::Acquire);
+
+        // The countdown only starts once the page is displayed
+        // (a repaint is requested once it's loaded)
+        if current_page >= self.total_pages || self.loaded_pages.read().unwrap().get(current_page).is_none() {
+            self.slideshow_turn_at = Some(Instant::now() + delay);
+            return;
+        }
+
+        let now = Instant::now();
+
+        if now < turn_at {
+            ctx.request_repaint_after(turn_at - now);
+            return;
+        }
+
+        let path = self.path.clone();
+
+        // The next item is opened at the end of the current one, if enabled in the settings
+        self.relative_page_change(1, false);
+
+        if self.current_page.load(Ordering::Acquire) == current_page && self.path == path {
+            self.slideshow_turn_at = None;
+            self.toasts.push("Slideshow finished");
+            return;
+        }
+
+        self.slideshow_turn_at = Some(now + delay);
+    }
+
+    /// Hide the mouse cursor if it wasn't used for a while (as set in the settings)
+    /// It stays visible while a window is opened, or in the overview
+    fn update_cursor(&mut self, ctx: &Context) {
+        if ctx.input(|i| i.pointer.is_moving() || i.pointer.any_down() || i.pointer.any_click() || i.scroll_delta != Vec2::ZERO) {
+            self.last_pointer_activity = Instant::now();
+        }
+
+        let delay = self.settings.read().unwrap().hide_cursor_delay_secs;
+
+        let window_opened = self.page_prompt.is_some() || self.password_prompt.is_some() || self.bookmarks_window.is_some() || self.recent_items_window.is_some() || self.key_bindings_window.is_some() || self.exit_prompt || self.trash_prompt.is_some() || self.help_shown;
+
+        if delay == 0 || window_opened || self.overview {
+            return;
+        }
+
+        let hide_at = self.last_pointer_activity + Duration::from_secs(delay);
+        let now = Instant::now();
+
+        // The cursor is displayed again as soon as the mouse moves, as it triggers a repaint
+        if now >= hide_at {
+            ctx.set_cursor_icon(CursorIcon::None);
+        } else {
+            ctx.request_repaint_after(hide_at - now);
+        }
+    }
+
+    /// Bookmark the current page
+    fn add_bookmark(&mut self) {
+        // Bookmarks are remembered for each item, which a playlist is not
+        let Some(path) = &self.path else {
+            self.toasts.push("Pages can't be bookmarked when reading several items at once");
+            return;
+        };
+
+        let page = self.current_page.load(Ordering::Acquire);
+        let mut bookmarks = self.bookmarks.get(&item_key(path)).cloned().unwrap_or_default();
+
+        if bookmarks.iter().any(|bookmark| bookmark.page == page) {
+            self.toasts.push(format!("Page {} is already bookmarked", page + 1));
+            return;
+        }
+
+        bookmarks.push(Bookmark { page, label: String::new() });
+        bookmarks.sort_by_key(|bookmark| bookmark.page);
+
+        self.change_item(ItemChange::Bookmarks(bookmarks), format!("bookmarking of page {}", page + 1));
+
+        self.toasts.push(format!("Bookmarked page {} (Shift+B to list the bookmarks)", page + 1));
+    }
+
+    /// Handle an action requested from the bookmarks list
+    fn handle_bookmark_action(&mut self, action: BookmarkAction) {
+        let key = self.path.as_deref().map(item_key);
+        let mut bookmarks = key.as_ref().and_then(|key| self.bookmarks.get(key)).cloned().unwrap_or_default();
+
+        match action {
+            BookmarkAction::Jump(page) => {
+                self.current_page.store(page.min(self.total_pages.saturating_sub(1)), Ordering::Release);
+                self.bookmarks_window = None;
+            },
+
+            BookmarkAction::Remove(index) => {
+                if index < bookmarks.len() {
+                    let removed = bookmarks.remove(index);
+                    self.change_item(ItemChange::Bookmarks(bookmarks), format!("removal of the bookmark of page {}", removed.page + 1));
+
+                    self.toasts.push(format!("Removed the bookmark of page {} (Ctrl+Z to restore it)", removed.page + 1));
+                }
+            },
+
+            BookmarkAction::Rename(index, label) => {
+                if let Some(bookmark) = bookmarks.get_mut(index) {
+                    let page = bookmark.page;
+                    bookmark.label = label;
+
+                    self.change_item(ItemChange::Bookmarks(bookmarks), format!("renaming of the bookmark of page {}", page + 1));
+                }
+            },
+
+            BookmarkAction::Undo => self.undo(),
+
+            BookmarkAction::Close => self.bookmarks_window = None,
+        }
+    }
+
+    /// Show or hide the overview
+    fn toggle_overview(&mut self) {
+        self.overview = !self.overview;
+        self.overview_scrolled_to = None;
+
+        if self.overview {
+            self.start_thumbnails();
+        }
+    }
+
+    /// Start generating the thumbnails of the current item's pages, the first time they're needed
+    fn start_thumbnails(&mut self) {
+        if let Some(handle) = self.thumbnails.start(&self.loaded_pages, &self.previews, &self.settings, &self.threads_stop_signal, &self.ctx) {
+            self.thread_handles.push(handle);
+        }
+    }
+
+    /// Jump to the page entered in the "jump to page" modal, and close it
+    /// The prompt is kept open with an error if the page is invalid
+    fn jump_to_prompted_page(&mut self) {
+        let Some(prompt) = &mut self.page_prompt else {
+            return;
+        };
+
+        let current_page = self.current_page.load(Ordering::Acquire);
+
+        let page = match parse_page_target(&prompt.input, current_page, self.total_pages) {
+            Ok(page) => page,
+            Err(err) => {
+                prompt.error = Some(err);
+                return;
+            }
+        };
+
+        let settings = self.settings.read().unwrap();
+
+        // In double page mode, the page is snapped to the start of its spread
+        let page = if settings.double_page { self.spread_start(page, &settings) } else { page };
+
+        drop(settings);
+
+        self.current_page.store(page, Ordering::Release);
+        self.page_prompt = None;
+    }
+
+    /// Turn the page when clicking on the left or right side of the window
+    /// The middle of the window is left free for future use
+    fn handle_page_click(&mut self, pos: Pos2, pages_area: Rect) {
+        if self.total_pages == 0 {
+            return;
+        }
+
+        let position = (pos.x - pages_area.left()) / pages_area.width();
+
+        let side = if position < 0.4 {
+            -1
+        } else if position > 0.6 {
+            1
+        } else {
+            return;
+        };
+
+        // In right-to-left mode, the next page is on the left
+        self.relative_page_change(reading_direction(side, self.mirrored_navigation()), false);
+    }
+
+    /// Check if the horizontal inputs (left and right keys, horizontal scrolling, clicks and swipes) go the other way,
+    /// the pages being laid out from right to left
+    fn mirrored_navigation(&self) -> bool {
+        let settings = self.settings.read().unwrap();
+
+        mirrored_navigation(settings.invert_keys_in_rtl, self.book_right_to_left.unwrap_or(settings.right_to_left))
+    }
+
+    /// Jump to the next chapter, or to the beginning of the current one when going backwards
+    /// (or to the previous one if already at the beginning)
+    fn relative_chapter_change(&mut self, relative: isize) {
+        assert!(relative == -1 || relative == 1);
+
+        let current_page = self.current_page.load(Ordering::Acquire);
+        let current_chapter = self.chapters.iter().rposition(|chapter| chapter.first_page <= current_page);
+
+        let chapter = match current_chapter {
+            Some(chapter) if relative == 1 => Some(chapter + 1),
+            Some(chapter) if self.chapters[chapter].first_page == current_page => chapter.checked_sub(1),
+            Some(chapter) => Some(chapter),
+            None if relative == 1 => Some(0),
+            None => None,
+        };
+
+        if let Some(chapter) = chapter.and_then(|chapter| self.chapters.get(chapter)) {
+            self.current_page.store(chapter.first_page, Ordering::Release);
+        }
+    }
+
+    /// Multiply the zoom factor, keeping the displayed point under the provided position in place
+    fn zoom_around(&mut self, factor: f32, pos: Pos2, pages_area: Rect) {
+        let mut zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+
+        // Make it easy to get back to the default level with the mouse wheel
+        if (zoom - 1.0).abs() < 0.02 {
+            zoom = 1.0;
+        }
+
+        if zoom == 1.0 {
+            return self.reset_zoom();
+        }
+
+        // Pages are horizontally centered and vertically aligned on the window's top
+        let from_anchor = pos - pos2(pages_area.center().x, pages_area.top());
+
+        self.pan_offset = from_anchor - (from_anchor - self.pan_offset) * (zoom / self.zoom);
+        self.zoom = zoom;
+    }
+
+    /// Move the pages, as long as they stay on screen
+    /// Returns `false` if they couldn't be moved (e.g. an edge was already reached)
+    fn pan(&mut self, delta: Vec2, pages_area: Rect) -> bool {
+        let offset = pan_bounds(self.pages_rect, pages_area).clamp((self.pan_offset + delta).to_pos2()).to_vec2();
+        let moved = offset != self.pan_offset;
+
+        self.pan_offset = offset;
+        moved
+    }
+
+    /// Fit the pages to the window again
+    fn reset_zoom(&mut self) {
+        self.zoom = 1.0;
+        self.pan_offset = Vec2::ZERO;
+    }
+
+    /// Check if a page-turning key press should turn the page
+    /// Holding the key turns the pages at a fixed rate, instead of the (usually much faster) key-repeat one
+    fn repeated_turn_allowed(&mut self, repeat: bool) -> bool {
+        if repeat && self.last_repeated_turn.elapsed() < KEY_REPEAT_INTERVAL {
+            return false;
+        }
+
+        self.last_repeated_turn = Instant::now();
+        true
+    }
+
+    /// Handle inputs (keyboard, mouse, etc.) from the UI thread
+    fn handle_inputs(&mut self, i: &InputState) {
+        // Keys typed in the password prompt must not trigger any shortcut
+        if self.password_prompt.is_some() {
+            return;
+        }
+
+        // The help is closed with any key
+        if self.help_shown {
+            if i.events.iter().any(|event| matches!(event, Event::Key { pressed: true, .. } | Event::Text(_))) {
+                self.help_shown = false;
+            }
+
+            return;
+        }
+
+        // Enter confirms exiting the application, Escape cancels it
+        if self.exit_prompt {
+            if i.key_pressed(Key::Enter) {
+                self.exit_requested = true;
+            }
+
+            if i.key_pressed(Key::Escape) {
+                self.exit_prompt = false;
+            }
+
+            return;
+        }
+
+        // Same thing for moving the current item to the trash
+        if self.trash_prompt.is_some() {
+            if i.key_pressed(Key::Enter) {
+                let path = self.trash_prompt.take().unwrap();
+
+                if let Err(err) = self.trash_item(path) {
+                    self.toasts.push_error(err);
+                }
+            }
+
+            if i.key_pressed(Key::Escape) {
+                self.trash_prompt = None;
+            }
+
+            return;
+        }
+
+        // The bookmarks and recent items lists (and the key bindings editor) handle their own keys
+        if self.bookmarks_window.is_some() || self.recent_items_window.is_some() || self.key_bindings_window.is_some() {
+            return;
+        }
+
+        // Same thing for the "jump to page" modal, which is closed with Escape (instead of exiting the application)
+        if self.page_prompt.is_some() {
+            if i.key_pressed(Key::Escape) {
+                self.page_prompt = None;
+            }
+
+            return;
+        }
+
+        // Keyboard shortcuts are handled through the key bindings from the settings
+        let mirrored = self.mirrored_navigation();
+        let triggered = self.settings.read().unwrap().key_bindings.triggered(i, mirrored);
+
+        for (actions, repeat) in triggered {
+            self.handle_actions(actions, repeat, i);
+        }
+
+        // Gamepad buttons go through the same bindings
+        #[cfg(feature = "gamepad")]
+        for button in std::mem::take(&mut self.gamepad_presses) {
+            let actions = self.settings.read().unwrap().key_bindings.triggered_by_button(button, mirrored);
+            self.handle_actions(actions, false, i);
+        }
+
+        // The back and forward buttons of the mouse act like the left and right arrow keys, with the same modifiers
+        for (button, forward) in [(PointerButton::Extra1, false), (PointerButton::Extra2, true)] {
+            if !i.pointer.button_pressed(button) {
+                continue;
+            }
+
+            let action = match (i.modifiers.ctrl, i.modifiers.shift, forward) {
+                (true, _, false) => Action::PreviousItem,
+                (true, _, true) => Action::NextItem,
+                (false, true, false) => Action::PreviousSinglePage,
+                (false, true, true) => Action::NextSinglePage,
+                (false, false, false) => Action::PreviousPage,
+                (false, false, true) => Action::NextPage,
+            };
+
+            self.handle_actions(vec![action], false, i);
+        }
+
+        // There is no key for the question mark, so the typed text is used instead
+        if i.events.iter().any(|event| matches!(event, Event::Text(text) if text == "?")) {
+            self.help_shown = true;
+        }
+
+        let pages_area = self.pages_area;
+
+        let (scroll_threshold, invert_scroll) = {
+            let settings = self.settings.read().unwrap();
+            (settings.scroll_threshold, settings.invert_scroll)
+        };
+
+        // In the overview, the mouse wheel scrolls through the thumbnails instead
+        let scroll_delta = if self.overview { Vec2::ZERO } else if invert_scroll { -i.scroll_delta } else { i.scroll_delta };
+
+        // Pages taller than the window are scrolled through before the page is turned
+        let scrolled = scroll_delta.y != 0.0 && self.pan(Vec2::new(0.0, scroll_delta.y), pages_area);
+
+        // Scrolling horizontally follows the pages' layout, like the left and right keys
+        let scroll_x = if mirrored { -scroll_delta.x } else { scroll_delta.x };
+
+        // Trackpads scroll over many frames, so the scrolled distance is accumulated until a pause
+        // This also allows high-resolution mouse wheels, which scroll by small steps, to turn the page
+        if self.scroll_gesture.as_ref().is_some_and(|gesture| gesture.last_scroll_at.elapsed() >= SCROLL_GESTURE_END) {
+            self.scroll_gesture = None;
+        }
+
+        if scroll_delta != Vec2::ZERO {
+            let gesture = self.scroll_gesture.get_or_insert(ScrollGesture { delta: Vec2::ZERO, last_scroll_at: Instant::now(), turned: false });
+
+            gesture.last_scroll_at = Instant::now();
+            gesture.delta.x += scroll_x;
+            gesture.delta.y = if scrolled { 0.0 } else { gesture.delta.y + scroll_delta.y };
+
+            let turn = if gesture.turned {
+                None
+            } else if gesture.delta.x >= scroll_threshold || gesture.delta.y >= scroll_threshold {
+                Some(-1)
+            } else if gesture.delta.x <= -scroll_threshold || gesture.delta.y <= -scroll_threshold {
+                Some(1)
+            } else {
+                None
+            };
+
+            if let Some(inc) = turn {
+                gesture.turned = true;
+                self.relative_page_change(inc, i.modifiers.shift);
+            }
+        }
+
+        // Zoom with Ctrl+scroll, around the cursor
+        let zoom_factor = i.zoom_delta();
+
+        if zoom_factor != 1.0 {
+            self.zoom_around(zoom_factor, i.pointer.hover_pos().unwrap_or(pages_area.center()), pages_area);
+        }
+
+        // Pages can be moved around by dragging them (with the mouse or a finger)
+        if self.dragging_pages && !self.overview && i.pointer.primary_down() && self.pan(i.pointer.delta(), pages_area) {
+            if let Some(swipe) = &mut self.swipe {
+                swipe.panned = true;
+            }
+        }
+
+        self.handle_swipes(i);
+    }
+
+    /// Turn the page when swiping horizontally on a touchscreen
+    /// Pinching (which zooms) and swipes that pan the pages don't turn it
+    fn handle_swipes(&mut self, i: &InputState) {
+        for event in &i.events {
+            let Event::Touch { id, phase, pos, .. } = event else {
+                continue;
+            };
+
+            match phase {
+                // A second finger cancels the swipe, as it's a pinch
+                TouchPhase::Start => {
+                    self.swipe = match self.swipe {
+                        Some(_) => None,
+                        None => Some(Swipe { touch_id: *id, start: *pos, started_at: Instant::now(), panned: false }),
+                    };
+                },
+
+                TouchPhase::Move => {},
+
+                TouchPhase::End => {
+                    let Some(swipe) = self.swipe.take() else {
+                        continue;
+                    };
+
+                    let delta = *pos - swipe.start;
+                    let speed = delta.x.abs() / swipe.started_at.elapsed().as_secs_f32().max(0.001);
+
+                    if swipe.touch_id != *id || swipe.panned || self.overview || delta.x.abs() < SWIPE_MIN_DISTANCE || delta.x.abs() < delta.y.abs() * 2.0 || speed < SWIPE_MIN_SPEED {
+                        continue;
+                    }
+
+                    // Swiping to the left shows the page on the right
+                    let side = if delta.x < 0.0 { 1 } else { -1 };
+
+                    self.relative_page_change(reading_direction(side, self.mirrored_navigation()), false);
+                },
+
+                TouchPhase::Cancel => self.swipe = None,
+            }
+        }
+    }
+
+    /// Perform the actions bound to a key (or a gamepad button)
+    fn handle_actions(&mut self, actions: Vec<Action>, repeat: bool, i: &InputState) {
+        // Pages larger than the window are panned until their edge is reached, before the page is turned
+        let mut panned = false;
+
+        for action in actions {
+            if action.is_page_turn() && (panned || !self.repeated_turn_allowed(repeat)) {
+                continue;
+            }
+
+            panned |= self.handle_action(action, i);
+        }
+    }
+
+    /// Perform an action triggered with a keyboard shortcut (or a gamepad button)
+    /// Returns `true` if the pages were panned
+    fn handle_action(&mut self, action: Action, i: &InputState) -> bool {
+        let pages_area = self.pages_area;
+
+        match action {
+            Action::PanLeft => return self.pan(Vec2::new(PAN_STEP, 0.0), pages_area),
+            Action::PanRight => return self.pan(Vec2::new(-PAN_STEP, 0.0), pages_area),
+            Action::PanUp => return self.pan(Vec2::new(0.0, PAN_STEP), pages_area),
+            Action::PanDown => return self.pan(Vec2::new(0.0, -PAN_STEP), pages_area),
+
+            Action::PreviousPage => self.relative_page_change(-1, false),
+            Action::NextPage => self.relative_page_change(1, false),
+            Action::PreviousSinglePage => self.relative_page_change(-1, true),
+            Action::NextSinglePage => self.relative_page_change(1, true),
+
+            Action::FirstPage => self.current_page.store(0, Ordering::Release),
+
+            Action::LastPage => {
+                let last_spread = self.last_spread_start(&self.settings.read().unwrap());
+                self.current_page.store(last_spread, Ordering::Release);
+            },
+
+            Action::JumpToPage => self.page_prompt = Some(PagePrompt::default()),
+
+            Action::GoBack => match self.page_history.pop() {
+                Some(page) if page < self.total_pages => {
+                    self.current_page.store(page, Ordering::Release);
+                    self.going_back = true;
+                },
+                _ => self.toasts.push("No page to go back to"),
+            },
+
+            Action::PreviousItem | Action::NextItem => {
+                let relative = if action == Action::NextItem { 1 } else { -1 };
+
+                // Items dropped at once are the chapters of the playlist
+                if self.is_playlist {
+                    self.relative_chapter_change(relative);
+                } else if let Err(err) = self.relative_file_change(relative) {
+                    self.toasts.push_error(err);
+                }
+            },
+
+            Action::PreviousChapter => self.relative_chapter_change(-1),
+            Action::NextChapter => self.relative_chapter_change(1),
+
+            Action::ToggleOverview => self.toggle_overview(),
+
+            Action::ToggleThumbnailStrip => {
+                let mut settings = self.settings.write().unwrap();
+                settings.thumbnail_strip = !settings.thumbnail_strip;
+            },
+
+            Action::AddBookmark => self.add_bookmark(),
+
+            Action::ListBookmarks => {
+                self.bookmarks_window = Some(BookmarksWindow::default());
+                self.start_thumbnails();
+            },
+
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+
+            Action::ToggleDoublePage => {
+                let mut settings = self.settings.write().unwrap();
+                settings.double_page = !settings.double_page;
+            },
+
+            Action::ShiftPairing => self.toggle_pairing_offset(),
+
+            Action::ToggleRightToLeft => {
+                let mut settings = self.settings.write().unwrap();
+                settings.right_to_left = !self.book_right_to_left.take().unwrap_or(settings.right_to_left);
+            },
+
+            Action::RotateClockwise => self.set_rotation(self.rotation.clockwise()),
+            Action::RotateCounterClockwise => self.set_rotation(self.rotation.counter_clockwise()),
+
+            Action::CycleFitMode => {
+                let mut settings = self.settings.write().unwrap();
+                settings.fit_mode = settings.fit_mode.next();
+
+                self.pan_to_start = true;
+            },
+
+            Action::CycleScalingFilter => {
+                let mut settings = self.settings.write().unwrap();
+                settings.scaling_filter = settings.scaling_filter.next();
+
+                self.toasts.push(format!("Pages scaled with the {} filter", settings.scaling_filter.description()));
+
+                // Displayed pages must be uploaded again for the change to be visible
+                *self.retained_odd_page_image.borrow_mut() = None;
+                *self.retained_even_page_image.borrow_mut() = None;
+            },
+
+            // Zoom around the cursor (or the window's center)
+            Action::ZoomIn | Action::ZoomOut => {
+                let factor = if action == Action::ZoomIn { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+                self.zoom_around(factor, i.pointer.hover_pos().unwrap_or(pages_area.center()), pages_area);
+            },
+
+            Action::ResetZoom => self.reset_zoom(),
+
+            Action::CyclePagesCounter => {
+                let mut settings = self.settings.write().unwrap();
+                settings.pages_counter = settings.pages_counter.next();
+            },
+
+            Action::ToggleColorManagement => {
+                let mut settings = self.settings.write().unwrap();
+                settings.color_manage = !settings.color_manage;
+
+                // Displayed pages must be decoded again for the change to be visible
+                *self.retained_odd_page_image.borrow_mut() = None;
+                *self.retained_even_page_image.borrow_mut() = None;
+            },
+
+            Action::ToggleTrimMargins => {
+                let mut settings = self.settings.write().unwrap();
+                settings.trim_margins = !settings.trim_margins;
+
+                // Displayed pages must be decoded again for the change to be visible
+                *self.retained_odd_page_image.borrow_mut() = None;
+                *self.retained_even_page_image.borrow_mut() = None;
+
+                self.toasts.push(if settings.trim_margins { "Margins trimmed" } else { "Margins kept" });
+            },
+
+            Action::CycleBackground => {
+                let mut settings = self.settings.write().unwrap();
+                let (name, background) = next_background(settings.background);
+
+                settings.background = background;
+                self.toasts.push(format!("Background set to {name}"));
+            },
+
+            Action::ToggleInvertColors => {
+                let mut settings = self.settings.write().unwrap();
+                settings.invert_colors = !settings.invert_colors;
+
+                // Displayed pages must be decoded again for the change to be visible
+                *self.retained_odd_page_image.borrow_mut() = None;
+                *self.retained_even_page_image.borrow_mut() = None;
+            },
+
+            Action::CycleBrightness => {
+                let mut settings = self.settings.write().unwrap();
+
+                // Custom levels are followed by the first one
+                let index = BRIGHTNESS_LEVELS.iter().position(|&level| level == settings.brightness).map_or(0, |index| (index + 1) % BRIGHTNESS_LEVELS.len());
+                settings.brightness = BRIGHTNESS_LEVELS[index];
+
+                self.toasts.push(format!("Brightness set to {:.0}%", settings.brightness * 100.0));
+
+                *self.retained_odd_page_image.borrow_mut() = None;
+                *self.retained_even_page_image.borrow_mut() = None;
+            },
+
+            Action::CycleSortOrder => {
+                let mut settings = self.settings.write().unwrap();
+                settings.page_sort = settings.page_sort.next();
+                drop(settings);
+
+                if let Err(err) = self.sort_pages() {
+                    self.toasts.push_error(err);
+                }
+            },
+
+            Action::ToggleSlideshow => self.toggle_slideshow(),
+
+            Action::InspectPages => self.inspect_pages(),
+
+            Action::ExportPage => {
+                if let Err(err) = self.export_page() {
+                    self.toasts.push_error(err);
+                }
+            },
+
+            Action::ExportSpread => {
+                if let Err(err) = self.export_spread() {
+                    self.toasts.push_error(err);
+                }
+            },
+
+            Action::ToggleWatchChanges => {
+                let mut settings = self.settings.write().unwrap();
+                settings.watch_changes = !settings.watch_changes;
+
+                self.toasts.push(if settings.watch_changes {
+                    "Items will be reloaded when they change on disk"
+                } else {
+                    "Items will not be reloaded when they change on disk anymore"
+                }.to_owned());
+            },
+
+            Action::Reload => {
+                if let Err(err) = self.reload() {
+                    self.toasts.push_error(err);
+                }
+            },
+
+            Action::DiscardCache => self.discard_cache(),
+
+            Action::ToggleDebugOverlay => {
+                let mut settings = self.settings.write().unwrap();
+                settings.debug_overlay = !settings.debug_overlay;
+            },
+
+            Action::ToggleWindowed => {
+                let mut settings = self.settings.write().unwrap();
+                settings.windowed = !settings.windowed;
+                self.window_mode_changed = true;
+            },
+
+            Action::OpenFile | Action::OpenFolder => {
+                if let Some(item) = self.pick_item(action == Action::OpenFolder) {
+                    if let Err(err) = self.load_path(item) {
+                        self.toasts.push_error(err);
+                    }
+                }
+            },
+
+            Action::RecentItems => self.recent_items_window = Some(RecentItemsWindow::default()),
+
+            Action::TrashItem => match &self.path {
+                Some(path) => self.trash_prompt = Some(path.clone()),
+                None => self.toasts.push("The pages are not from a single item"),
+            },
+
+            Action::RevealItem => {
+                if let Err(err) = self.reveal_current() {
+                    self.toasts.push_error(err);
+                }
+            },
+
+            Action::EditKeyBindings => self.key_bindings_window = Some(KeyBindingsWindow::default()),
+
+            Action::Help => self.help_shown = true,
+
+            Action::Exit => {
+                // Escape goes back to the pages from the overview
+                if self.overview {
+                    self.overview = false;
+                } else if self.settings.read().unwrap().confirm_exit && self.total_pages > 0 {
+                    self.exit_prompt = true;
+                } else {
+                    // The application isn't exited right away, so its state is saved
+                    self.exit_requested = true;
+                }
+            },
+        }
+
+        false
+    }
+
+    /// Handle file drops from other applications
+    fn handle_file_drops(&mut self, i: &InputState) {
+        let files = &i.raw.dropped_files;
 
         if files.is_empty() {
            return; 
         }
 
-        if files.len() > 1 {
-            return show_err_dialog(anyhow!("Please drop only one item"));
-        }
+        let Some(paths) = files.iter().map(|file| file.path.clone()).collect::<Option<Vec<_>>>() else {
+            return self.toasts.push_error(anyhow!("Dropped files must be stored on disk"));
+        };
+
+        // Several items dropped at once are read one after the other
+        let result = if paths.len() > 1 {
+            self.load_playlist(paths)
+        } else {
+            self.load_path(paths.into_iter().next().unwrap())
+        };
+
+        if let Err(err) = result {
+            self.toasts.push_error(err);
+        }
+    }
+
+    /// Compute a displayable image for a given page
+    fn compute_displayable_page(&self, page: usize) -> Result<Option<DisplayablePage>, PageError> {
+        let Some(result) = self.loaded_pages.read().unwrap().get(page).cloned() else {
+            return Ok(None);
+        };
+
+        let (filename, bytes) = result?;
+
+        let options = {
+            let settings = self.settings.read().unwrap();
+
+            DecodeOptions {
+                color_manage: settings.color_manage,
+                max_dimension: settings.max_decode_dimension,
+                // Vector images are rasterised at twice the window's height, so they remain sharp
+                // NOTE: they are not rasterised again when the window is resized
+                raster_height: Some((self.ctx.screen_rect().height() * self.ctx.pixels_per_point() * 2.0) as usize),
+                threads: settings.decode_threads(),
+            }
+        };
+
+        // The page may already have been decoded by a loading thread, with the current settings
+        let decoded = self.decoded_pages.write().unwrap().take(page).filter(|decoded| decoded.color_manage == options.color_manage && decoded.max_dimension == options.max_dimension);
+
+        let image = match decoded {
+            Some(decoded) => {
+                self.metrics.write().unwrap().record_decode(page, decoded.decode_time);
+
+                decoded.image
+            },
+            None => {
+                let started = Instant::now();
+
+                let image = decode_image(&filename, &bytes, options).inspect_err(|err| {
+                    println!("> Failed to decode page {page} ({}, {} bytes): {err}", filename.display(), bytes.len());
+                })?;
+
+                self.metrics.write().unwrap().record_decode(page, started.elapsed());
+
+                image
+            },
+        };
+
+        let image = self.post_process(page, image, options.threads);
+
+        let started = Instant::now();
+        let texture_options = self.texture_options(&image);
+        let displayable = DisplayablePage::upload(&self.ctx, &format!("{}:[page-{page}]", filename.to_string_lossy()), image, texture_options, options.threads);
+        self.metrics.write().unwrap().record_upload(page, started.elapsed());
+
+        Ok(Some(displayable))
+    }
+
+    /// Describe a page and the file it comes from, to help identifying it when it fails to display
+    /// For archives, this is the name of the file inside the archive
+    fn describe_page(&self, page: usize) -> String {
+        match self.loaded_pages.read().unwrap().get(page) {
+            Some(Ok((filename, bytes))) => format!("Page {}: {} ({} bytes)", page + 1, filename.display(), bytes.len()),
+            // The file couldn't be loaded, but the source still knows its name
+            Some(Err(_)) | None => match self.img_source.page_name(page) {
+                Some(name) => format!("Page {}: {name}", page + 1),
+                None => format!("Page {}", page + 1),
+            },
+        }
+    }
+
+    /// Get the number of bytes loaded so far and the total number of bytes to load,
+    /// estimated from the pages' size (if the source provides them)
+    fn loading_progress(&self) -> Option<(u64, u64)> {
+        let loaded_pages = self.loaded_pages.read().unwrap();
+
+        let mut loaded = 0;
+        let mut total = 0;
+
+        for (page, size) in self.page_sizes.iter().enumerate() {
+            // Pages of unknown size are not taken into account
+            let Some(size) = size else { continue };
+
+            total += size;
+
+            if loaded_pages.get(page).is_some() {
+                loaded += size;
+            }
+        }
+
+        (total > 0).then_some((loaded, total))
+    }
+
+    /// Compute a displayable image for the preview of a given page (if any)
+    fn compute_displayable_preview(&self, page: usize) -> Option<DisplayablePage> {
+        let preview = self.previews.read().unwrap().get(page).cloned()?;
+        let preview = self.post_process(page, preview, 1);
+
+        // Previews are always enlarged a lot, so they are always smoothed
+        Some(DisplayablePage::upload(&self.ctx, &format!("[preview-{page}]"), preview, TextureOptions::LINEAR, 1))
+    }
+
+    /// Get the options a decoded page is scaled with, depending on the scaling filter from the settings
+    fn texture_options(&self, image: &DecodedImage) -> TextureOptions {
+        let settings = self.settings.read().unwrap();
+
+        match settings.scaling_filter {
+            ScalingFilter::Linear => TextureOptions::LINEAR,
+            ScalingFilter::Nearest => TextureOptions::NEAREST,
+            // The page's scale is estimated from the current fit mode, zoom and window size
+            // NOTE: the page isn't uploaded again when one of them changes
+            ScalingFilter::Auto => {
+                let size = self.rotation.rotate_size(Vec2::new(image.width as f32, image.height as f32));
+                let available = if settings.double_page { Vec2::new(self.pages_area.width() / 2.0, self.pages_area.height()) } else { self.pages_area.size() };
+
+                let pixels_per_point = self.ctx.pixels_per_point();
+                let upscale = fit_scale(settings.fit_mode, size, available, pixels_per_point) * self.zoom * pixels_per_point;
+
+                if upscale > NEAREST_FILTER_MIN_UPSCALE { TextureOptions::NEAREST } else { TextureOptions::LINEAR }
+            },
+        }
+    }
+
+    /// Transform a decoded page before it's uploaded, as enabled in the settings: remove its margins and filter its colours
+    /// The displayed pages are retained, so this is only done again when the settings change
+    fn post_process(&self, page: usize, image: DecodedImage, threads: usize) -> DecodedImage {
+        let (trim_margins, trim_threshold, filters) = {
+            let settings = self.settings.read().unwrap();
+            (settings.trim_margins, settings.trim_threshold, ColorFilters { invert: settings.invert_colors, brightness: settings.brightness })
+        };
+
+        let margins = trim_margins.then(|| *self.page_margins.borrow_mut().entry(page).or_insert_with(|| find_margins(&image, trim_threshold))).flatten();
+
+        let mut image = match margins {
+            Some(margins) => trim(image, margins),
+            None => image,
+        };
+
+        apply_filters(&mut image, filters, threads);
+
+        image
+    }
+}
+
+impl eframe::App for ReaderApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.remember_reading_position();
+
+        // Save settings
+        eframe::set_value(storage, eframe::APP_KEY, &*self.settings.read().unwrap());
+        eframe::set_value(storage, RECENT_ITEMS_KEY, &self.recent_items);
+        eframe::set_value(storage, ROTATIONS_KEY, &self.rotations);
+        eframe::set_value(storage, BOOKMARKS_KEY, &self.bookmarks);
+        eframe::set_value(storage, READING_POSITIONS_KEY, &self.reading_positions);
+    }
+
+    // The main rendering function, which computes the UI in immediate mode
+    fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
+        // Actions from the welcome screen are handled once it's rendered
+        let mut welcome_action = None;
+
+        if self.window_mode_changed {
+            self.window_mode_changed = false;
+            self.apply_window_mode(frame);
+        }
+
+        self.remember_window_geometry(frame);
+
+        // The thumbnail strip is displayed above the pages, if enabled in the settings
+        if self.settings.read().unwrap().thumbnail_strip && self.total_pages > 0 && !self.overview {
+            self.start_thumbnails();
+
+            let current_page = self.current_page.load(Ordering::Acquire);
+            let right_to_left = self.book_right_to_left.unwrap_or(self.settings.read().unwrap().right_to_left);
+
+            let clicked = TopBottomPanel::top("thumbnail_strip")
+                .frame(Frame::none().fill(Color32::from_gray(20)))
+                .show(ctx, |ui| show_strip(ui, &mut self.thumbnails, current_page, self.total_pages, right_to_left, self.rotation))
+                .inner;
+
+            if let Some(page) = clicked {
+                self.current_page.store(page, Ordering::Release);
+            }
+        }
+
+        let [red, green, blue] = self.settings.read().unwrap().background;
+
+        // We first need a central panel to display everything inside
+        CentralPanel::default()
+            .frame(Frame::none().fill(Color32::from_rgb(red, green, blue)))
+            .show(ctx, |ui| {
+                if let Some(summary) = self.inspection_summary.write().unwrap().take() {
+                    self.toasts.push(summary);
+                }
+
+                // Reload the current item if it changed on disk
+                if self.reload_requested.swap(false, Ordering::AcqRel) {
+                    if let Err(err) = self.reload() {
+                        self.toasts.push_error(err);
+                    }
+                }
+
+                self.pages_area = ui.max_rect();
+
+                // Pages are only moved when the drag started on them, so using the seek bar doesn't move them
+                self.dragging_pages = ctx.input(|i| i.pointer.press_origin()).is_some_and(|pos| is_over_pages(ctx, self.pages_area, pos));
+
+                // Collect the gamepad buttons pressed since the last frame
+                #[cfg(feature = "gamepad")]
+                {
+                    self.gamepad_presses.clear();
+
+                    while let Ok(press) = self.gamepad.try_recv() {
+                        match press {
+                            Ok(button) => self.gamepad_presses.push(button),
+                            Err(err) => self.toasts.push(err),
+                        }
+                    }
+                }
+
+                // We start by handling user inputs
+                // this may impact the current page number, opened file, etc.
+                ctx.input(|i| {
+                    // Time spent reading is measured from the inputs, page changes (e.g. from a gamepad) are handled below
+                    if !i.events.is_empty() {
+                        self.item_stats.reading_time += self.activity.record_input();
+                    }
+
+                    self.handle_inputs(i);
+                    self.handle_file_drops(i);
+                    self.cadence.set_focused(i.focused);
+                });
+
+                if self.exit_requested {
+                    self.exit(frame);
+                }
+
+                self.update_cursor(ctx);
+
+                // Clicks made on the pages (not on a window or an overlay) may turn them
+                if let Some(pos) = ctx.input(|i| i.pointer.primary_clicked().then(|| i.pointer.interact_pos()).flatten()) {
+                    if self.page_prompt.is_none() && !self.overview && is_over_pages(ctx, self.pages_area, pos) {
+                        self.handle_page_click(pos, self.pages_area);
+                    }
+                }
+
+                self.update_slideshow(ctx);
+
+                // Keep track of the reading cadence
+                // Only regular page turns are taken into account, jumps are ignored
+                let current_page = self.current_page.load(Ordering::Acquire);
+
+                if current_page != self.last_seen_page {
+                    // Pages split in two are displayed from their first half, unless they were reached backwards
+                    if self.second_half_of.is_some_and(|page| page != current_page) {
+                        self.second_half_of = None;
+                    }
+
+                    if current_page > self.last_seen_page && current_page - self.last_seen_page <= 2 {
+                        self.cadence.record_turn(current_page - self.last_seen_page);
+                        self.item_stats.pages_turned += (current_page - self.last_seen_page) as u64;
+                    }
+
+                    self.item_stats.reading_time += self.activity.record_input();
+
+                    // Pages displayed before a jump are remembered to go back to them, unlike the ones simply turned
+                    // Consecutive jumps (e.g. when dragging the seek bar) only remember the page they started from
+                    let going_back = std::mem::take(&mut self.going_back);
+
+                    if current_page.abs_diff(self.last_seen_page) > 2 && !going_back {
+                        if self.last_jump_at.is_none_or(|jumped_at| jumped_at.elapsed() > JUMPS_INTERVAL) {
+                            self.page_history.push(self.last_seen_page);
+                            self.undo.push(ItemChange::Page(self.last_seen_page), format!("jump from page {}", self.last_seen_page + 1));
+
+                            if self.page_history.len() > MAX_PAGE_HISTORY {
+                                self.page_history.remove(0);
+                            }
+                        }
+
+                        self.last_jump_at = Some(Instant::now());
+                    }
+
+                    self.last_seen_page = current_page;
+                    self.page_changed_at = Instant::now();
+                    self.remember_reading_position();
+
+                    if !self.settings.read().unwrap().keep_zoom {
+                        self.reset_zoom();
+                    }
+
+                    self.pan_to_start = true;
+
+                    // Turning the page manually restarts the slideshow's countdown
+                    if let Some(turn_at) = &mut self.slideshow_turn_at {
+                        *turn_at = Instant::now() + Duration::from_secs(self.settings.read().unwrap().slideshow_delay_secs);
+                    }
+                }
+
+                // Get the current window's size (required to scale the pages properly)
+                let win_size = frame.info().window_info.size;
+
+                // If the "jump to page" modal is opened...
+                if self.page_prompt.is_some() {
+                    let mut submit = false;
+                    let mut cancel = false;
+
+                    // Show it!
+                    Window::new("Jump to page")
+                        .pivot(Align2::CENTER_CENTER)
+                        .default_pos((win_size / 2.0).to_pos2())
+                        .show(&self.ctx, |ui| {
+                            ui.label("Jump to page (e.g. 12, +25, -10 or 50%):");
+
+                            let prompt = self.page_prompt.as_mut().unwrap();
+                            let response = ui.text_edit_singleline(&mut prompt.input);
+
+                            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                                submit = true;
+                            }
+
+                            response.request_focus();
+
+                            if response.changed() {
+                                prompt.error = None;
+                            }
+
+                            if let Some(error) = &prompt.error {
+                                ui.label(RichText::new(error).color(Color32::LIGHT_RED));
+                            }
+
+                            ui.horizontal(|ui| {
+                                if ui.button("OK").clicked() {
+                                    submit = true;
+                                }
+
+                                if ui.button("Cancel").clicked() {
+                                    cancel = true;
+                                }
+                            });
+
+                            // Chapters can be jumped to directly
+                            if !self.chapters.is_empty() {
+                                ui.separator();
+                                ui.label("Jump to chapter:");
+
+                                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                                    for chapter in &self.chapters {
+                                        if ui.button(format!("{} (page {})", chapter.name, chapter.first_page + 1)).clicked() {
+                                            self.current_page.store(chapter.first_page, Ordering::Release);
+                                            self.page_prompt = None;
+                                        }
+                                    }
+                                });
+                            }
+                        });
+
+                    if cancel {
+                        self.page_prompt = None;
+                    } else if submit {
+                        self.jump_to_prompted_page();
+                    }
+                }
+
+                // If the user is asked to confirm exiting the application...
+                if self.exit_prompt {
+                    let mut exit = false;
+                    let mut cancel = false;
+
+                    Window::new("Exit")
+                        .pivot(Align2::CENTER_CENTER)
+                        .default_pos((win_size / 2.0).to_pos2())
+                        .collapsible(false)
+                        .show(&self.ctx, |ui| {
+                            ui.label("Exit the application?");
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Exit").clicked() {
+                                    exit = true;
+                                }
 
-        let file = files.get(0).unwrap();
+                                if ui.button("Cancel").clicked() {
+                                    cancel = true;
+                                }
+                            });
+                        });
 
-        let Some(path) = &file.path else {
-            return show_err_dialog(anyhow!("Dropped file must be a file stored on disk"));
-        };
+                    if exit {
+                        self.exit(frame);
+                    } else if cancel {
+                        self.exit_prompt = false;
+                    }
+                }
 
-        if let Err(err) = self.load_path(path.to_owned()) {
-            show_err_dialog(err);
-        }
-    }
+                // If the user is asked to confirm moving the current item to the trash...
+                if let Some(path) = &self.trash_prompt {
+                    let mut confirm = false;
+                    let mut cancel = false;
 
-    /// Compute a displayable image for a given page
-    fn compute_displayable_page(&self, page: usize) -> Result<Option<(TextureHandle, Vec2)>, String> {
-        let Some(result) = self.loaded_pages.read().unwrap().get(page).cloned() else {
-            return Ok(None);
-        };
+                    Window::new("Move to trash")
+                        .pivot(Align2::CENTER_CENTER)
+                        .default_pos((win_size / 2.0).to_pos2())
+                        .collapsible(false)
+                        .show(&self.ctx, |ui| {
+                            ui.label(format!("Move {} to the trash?", path.display()));
 
-        let (filename, bytes) = result?;
+                            ui.horizontal(|ui| {
+                                if ui.button("Move to trash").clicked() {
+                                    confirm = true;
+                                }
 
-        let DecodedImage { rgb8_pixels, width, height } = decode_image(&filename, &bytes).map_err(|err| format!("Failed to decode image: {err}"))?;
+                                if ui.button("Cancel").clicked() {
+                                    cancel = true;
+                                }
+                            });
+                        });
 
-        let image = ColorImage::from_rgb([width, height], &rgb8_pixels);
+                    if confirm {
+                        let path = self.trash_prompt.take().unwrap();
 
-        let tex_handle = self.ctx.load_texture(format!("{}:[page-{page}]", filename.to_string_lossy()), image, TextureOptions::default());
+                        if let Err(err) = self.trash_item(path) {
+                            self.toasts.push_error(err);
+                        }
+                    } else if cancel {
+                        self.trash_prompt = None;
+                    }
+                }
 
-        Ok(Some((tex_handle, vec2(width as f32, height as f32))))
-    }
-}
+                // If the bookmarks list is opened...
+                if let Some(window) = &mut self.bookmarks_window {
+                    let key = self.path.as_deref().map(item_key);
+                    let bookmarks = key.as_ref().and_then(|key| self.bookmarks.get(key)).map(Vec::as_slice).unwrap_or_default();
 
-impl eframe::App for ReaderApp {
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        // Save settings
-        eframe::set_value(storage, eframe::APP_KEY, &*self.settings.read().unwrap());
-    }
+                    if let Some(action) = window.show(ctx, bookmarks, &mut self.thumbnails, self.rotation) {
+                        self.handle_bookmark_action(action);
+                    }
+                }
 
-    // The main rendering function, which computes the UI in immediate mode
-    fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
-        // We first need a central panel to display everything inside
-        CentralPanel::default()
-            .frame(Frame::none())
-            .show(ctx, |ui| {
-                // We start by handling user inputs
-                // this may impact the current page number, opened file, etc.
-                ctx.input(|i| {
-                    self.handle_inputs(i);
-                    self.handle_file_drops(i);
-                });
+                // If the key bindings editor is opened...
+                if let Some(window) = &mut self.key_bindings_window {
+                    let bindings = &mut self.settings.write().unwrap().key_bindings;
 
-                // Get the current window's size (required to scale the pages properly)
-                let win_size = frame.info().window_info.size;
+                    #[cfg(feature = "gamepad")]
+                    window.capture_buttons(bindings, &self.gamepad_presses);
 
-                // If the "jump to page" modal is opened...
-                if self.page_prompt.is_some() {
-                    // Show it!
-                    Window::new("Jump to page")
+                    if window.show(ctx, bindings) {
+                        self.key_bindings_window = None;
+                    }
+                }
+
+                // If the recent items list is opened...
+                if let Some(window) = &mut self.recent_items_window {
+                    if let Some(action) = window.show(ctx, &self.recent_items, unix_time()) {
+                        match action {
+                            RecentItemsAction::Open(path) => {
+                                self.recent_items_window = None;
+                                self.handle_welcome_action(WelcomeAction::OpenRecent(path));
+                            },
+                            RecentItemsAction::Forget(path) => self.handle_welcome_action(WelcomeAction::ForgetRecent(path)),
+                            RecentItemsAction::Close => self.recent_items_window = None,
+                        }
+                    }
+                }
+
+                // If the password modal is opened...
+                if let Some(prompt) = &mut self.password_prompt {
+                    let mut submit = false;
+                    let mut cancel = false;
+
+                    Window::new("Password required")
                         .pivot(Align2::CENTER_CENTER)
                         .default_pos((win_size / 2.0).to_pos2())
                         .show(&self.ctx, |ui| {
-                            ui.label("Jump to page:");
-
-                            ui.text_edit_singleline(self.page_prompt.as_mut().unwrap());
+                            ui.label(if prompt.wrong_password {
+                                "Wrong password, please try again:"
+                            } else {
+                                "This item is protected by a password:"
+                            });
 
-                            ui.horizontal(|ui| {
-                                if ui.button("OK").clicked() {
-                                    let Ok(page) = self.page_prompt.as_ref().unwrap().parse::<usize>() else {
-                                        return show_err_dialog(anyhow!("Invalid page number provided"));                                    
-                                    };
+                            let response = ui.add(TextEdit::singleline(&mut prompt.password).password(true));
 
-                                    if page == 0 {
-                                        return show_err_dialog(anyhow!("Invalid page number provided"));
-                                    }
+                            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                                submit = true;
+                            }
 
-                                    if page > self.total_pages {
-                                        return show_err_dialog(anyhow!("Book only contains {} pages", self.total_pages));
-                                    }
+                            response.request_focus();
 
-                                    self.current_page.store(page - 1, Ordering::Release);
-                                    self.page_prompt = None;
+                            ui.horizontal(|ui| {
+                                if ui.button("OK").clicked() {
+                                    submit = true;
                                 }
 
                                 if ui.button("Cancel").clicked() {
-                                    self.page_prompt = None;
+                                    cancel = true;
                                 }
                             });
                         });
+
+                    if cancel {
+                        self.password_prompt = None;
+                    } else if submit {
+                        if let Some(PasswordPrompt { path, password, .. }) = self.password_prompt.take() {
+                            if let Err(err) = self.load_path_with_password(path, Some(&password)) {
+                                self.toasts.push_error(err);
+                            }
+                        }
+                    }
                 }
 
                 // Render a given page in the UI, synchronously
-                let render_page = |ui: &mut Ui, page: usize| {
+                // The page is retained in the odd or even pages' slot, which is usually determined
+                // by its number, but the two pages of a spread must always use different ones
+                //
+                // Returns the area covered by the page, if it could be displayed
+                let fit_mode = self.settings.read().unwrap().fit_mode;
+
+                // Pages which failed to be read can be loaded again, once they're rendered
+                let retried_page = Cell::new(None);
+
+                // Decoded dimensions and scale (relative to its pixels) of the current page, for the status bar
+                let current_page_details = Cell::new(None);
+
+                let render_page = |ui: &mut Ui, page: usize, odd_slot: bool, half: Option<PageHalf>| {
                     if page >= self.total_pages {
                         ui.label(" "); // Empty widget
+                        None
                     } else {
-                        let mut ptr = if page % 2 != 0 {
+                        let mut ptr = if odd_slot {
                             self.retained_odd_page_image.borrow_mut()
                         } else {
                             self.retained_even_page_image.borrow_mut()
                         };
 
-                        let loaded = if let Some((_, tex_handle, size)) = ptr.as_ref().filter(|(c_page, _, _)| *c_page == page) {
-                            println!("> Loaded page {page} from cache");
-                            Ok(Some((tex_handle.clone(), *size)))
-                        } else {
-                            println!("> Computing displayable image for page {page}...");
-                            self.compute_displayable_page(page)
+                        let cached = ptr.as_ref().filter(|(c_page, _, _)| *c_page == page).map(|(_, displayable, is_preview)| (displayable.clone(), *is_preview));
+
+                        let loaded = match cached {
+                            Some((displayable, false)) => {
+                                println!("> Loaded page {page} from cache");
+                                self.metrics.write().unwrap().cache_hits += 1;
+                                Ok(Some((displayable, false)))
+                            },
+                            // The preview has been displayed during the previous frame,
+                            // so we can now take the time to decode the full image
+                            Some((_, true)) => {
+                                println!("> Computing displayable image for page {page} (replacing preview)...");
+                                self.metrics.write().unwrap().cache_misses += 1;
+                                self.compute_displayable_page(page).map(|data| data.map(|displayable| (displayable, false)))
+                            },
+                            None => match self.compute_displayable_preview(page) {
+                                Some(preview) => {
+                                    println!("> Displaying preview for page {page}");
+
+                                    // Ensure the full image is decoded right after this frame
+                                    self.ctx.request_repaint();
+
+                                    Ok(Some((preview, true)))
+                                },
+                                None => {
+                                    println!("> Computing displayable image for page {page}...");
+                                    self.metrics.write().unwrap().cache_misses += 1;
+                                    self.compute_displayable_page(page).map(|data| data.map(|displayable| (displayable, false)))
+                                },
+                            },
                         };
 
                         match loaded {
                             Ok(data) => match data {
-                                Some((tex_handle, size)) => {
-                                    let scale = frame.info().window_info.size.y / size.y;
-                                    ui.image(tex_handle.id(), size * scale);
+                                Some((displayable, is_preview)) => {
+                                    // Pages are fitted to the space available to them (half of the window in double page mode)
+                                    let size = self.rotation.rotate_size(displayable.size());
+                                    let size = if half.is_some() { Vec2::new(size.x / 2.0, size.y) } else { size };
+                                    let scale = fit_scale(fit_mode, size, ui.max_rect().size(), ui.ctx().pixels_per_point()) * self.zoom;
+
+                                    let rect = match half {
+                                        Some(half) => displayable.show_half(ui, scale, self.rotation, half),
+                                        None => displayable.show(ui, scale, self.rotation),
+                                    }.rect;
+
+                                    if page == current_page && !is_preview {
+                                        let decoded_size = displayable.decoded_size();
+                                        let pixels_scale = scale * ui.ctx().pixels_per_point() * displayable.size().x / decoded_size.0 as f32;
+
+                                        current_page_details.set(Some((decoded_size, pixels_scale)));
+                                    }
 
-                                    if ptr.is_none() {
-                                        *ptr = Some((page, tex_handle, size));
+                                    if ptr.as_ref().is_none_or(|(c_page, _, c_is_preview)| *c_page != page || *c_is_preview != is_preview) {
+                                        *ptr = Some((page, displayable, is_preview));
                                     }
+
+                                    Some(rect)
                                 },
                                 None => {
                                     ui.heading("Loading...");
                                     ui.add(Spinner::new());
+
+                                    // Pages vary a lot in size, so the progress is measured in bytes
+                                    if let Some((loaded, total)) = self.loading_progress() {
+                                        ui.add(
+                                            ProgressBar::new(loaded as f32 / total as f32)
+                                                .desired_width(300.0)
+                                                .text(format!("{} / {} loaded", format_size(loaded), format_size(total)))
+                                        );
+                                    }
+
+                                    None
                                 },
                             },
                             Err(err) => {
-                                ui.heading(format!("Failed to load page: {err}"));
+                                ui.heading(err.to_string());
+                                ui.label(self.describe_page(page));
+
+                                // Reading the page again may work, unlike decoding invalid data again
+                                if err.is_transient() && ui.button("Retry").clicked() {
+                                    retried_page.set(Some(page));
+                                }
+
+                                None
                             },
                         }
                     }
@@ -491,57 +3227,143 @@ impl eframe::App for ReaderApp {
 
                 let settings = self.settings.read().unwrap();
 
-                let current_page = self.current_page.load(Ordering::Acquire);
+                // Corrupted pages are skipped in double page mode, so they don't break the spreads
+                // If there is no readable page after the current one, it is displayed alone
+                let next_page = Some(self.skip_readable_pages(current_page, 1, true))
+                    .filter(|&page| !self.is_corrupted(page))
+                    .unwrap_or(current_page);
+
+                // Zoomed and panned pages are moved by the offset, while still being clipped to the window
+                let pan_offset = if self.overview { Vec2::ZERO } else { self.pan_offset };
+                let ui = &mut ui.child_ui(ui.max_rect().translate(pan_offset), *ui.layout());
 
                 // Determine the pages to render and render them
+                let mut pages_rect = None;
+
                 let pages = if self.total_pages == 0 {
-                    ui.heading("Nothing to display");
-                    
+                    // Show the welcome screen if nothing was opened yet
+                    if self.path.is_none() && !self.is_playlist {
+                        let recent_items = self.recent_items.iter().map(|item| item.path.clone()).collect::<Vec<_>>();
+                        welcome_action = show_welcome(ui, &recent_items);
+                    } else {
+                        ui.heading("Nothing to display");
+                    }
+
                     (None, None)
-                } else if !settings.double_page || current_page + 1 == self.total_pages || (current_page == 0 && settings.display_first_page_in_single_mode) {
-                    ui.with_layout(Layout::top_down(Align::Center), |ui| {
-                        render_page(ui, current_page);
-                    });
+                } else if self.overview {
+                    // Keep the current page in view when it changes
+                    let scroll_to = (self.overview_scrolled_to != Some(current_page)).then_some(current_page);
+                    self.overview_scrolled_to = Some(current_page);
+
+                    if let Some(page) = show_overview(ui, &mut self.thumbnails, current_page, self.total_pages, scroll_to, self.rotation) {
+                        self.current_page.store(page, Ordering::Release);
+                        self.overview = false;
+                    }
+
+                    (Some(current_page), None)
+                } else if !settings.double_page || next_page == current_page || (current_page == 0 && self.first_page_single(&settings)) || self.alone_in_spread(current_page, &settings) {
+                    let half = self.displayed_half(current_page, &settings);
+
+                    pages_rect = ui.with_layout(Layout::top_down(Align::Center), |ui| {
+                        render_page(ui, current_page, !current_page.is_multiple_of(2), half)
+                    }).inner;
 
                     (Some(current_page), None)
                 } else {
                     // We remove any space between columns to get a gapless display in double mode
                     ui.spacing_mut().item_spacing = Vec2::ZERO;
 
-                    ui.columns(2, |columns| {
-                        let (left_page, right_page) = if settings.right_to_left {
-                            (current_page + 1, current_page)
+                    // The spread is panned as a whole
+                    pages_rect = ui.columns(2, |columns| {
+                        let current_odd_slot = !current_page.is_multiple_of(2);
+
+                        let ((left_page, left_odd_slot), (right_page, right_odd_slot)) = if self.book_right_to_left.unwrap_or(settings.right_to_left) {
+                            ((next_page, !current_odd_slot), (current_page, current_odd_slot))
                         } else {
-                            (current_page, current_page + 1)
+                            ((current_page, current_odd_slot), (next_page, !current_odd_slot))
                         };
 
                         // Using a two-columns layout allows to use custom alignemnt
                         // for each of them
 
-                        columns[0].with_layout(
+                        let left_rect = columns[0].with_layout(
                             Layout::right_to_left(Align::Center),
                             |ui| {
-                                render_page(ui, left_page);
+                                render_page(ui, left_page, left_odd_slot, None)
                             },
-                        );
+                        ).inner;
 
-                        columns[1].with_layout(
+                        let right_rect = columns[1].with_layout(
                             Layout::left_to_right(Align::Center),
                             |ui| {
-                                render_page(ui, right_page);
+                                render_page(ui, right_page, right_odd_slot, None)
                             },
-                        );
+                        ).inner;
+
+                        match (left_rect, right_rect) {
+                            (Some(left_rect), Some(right_rect)) => Some(left_rect.union(right_rect)),
+                            (left_rect, right_rect) => left_rect.or(right_rect),
+                        }
                     });
 
-                    (Some(current_page), Some(current_page + 1))
+                    (Some(current_page), Some(next_page))
                 };
 
+                self.displayed_pages = pages;
+
+                if let Some(page) = retried_page.get() {
+                    self.retry_page(page);
+                }
+
+                // Keep the pages on screen, and show the start of the new ones after a page change
+                // As the pages' size is only known once they're rendered, the corrected offset is used from the next frame
+                if !self.overview {
+                    self.pages_rect = pages_rect.map(|rect| rect.translate(-self.pan_offset));
+
+                    let bounds = pan_bounds(self.pages_rect, self.pages_area);
+
+                    let pan_offset = if self.pan_to_start && self.pages_rect.is_some() {
+                        self.pan_to_start = false;
+
+                        // Right-to-left pages start from their top-right corner
+                        let x = if self.book_right_to_left.unwrap_or(settings.right_to_left) { bounds.min.x } else { bounds.max.x };
+
+                        Vec2::new(x, bounds.max.y)
+                    } else {
+                        bounds.clamp(self.pan_offset.to_pos2()).to_vec2()
+                    };
+
+                    if pan_offset != self.pan_offset {
+                        self.pan_offset = pan_offset;
+                        ctx.request_repaint();
+                    }
+                }
+
+                // The pages counter may only be displayed for a moment after the page changes, then fade out
+                let counter_opacity = if settings.counter_auto_hide { self.counter_opacity() } else { 1.0 };
+
                 // Display the pages number if enabled in the settings
-                if settings.display_pages_number {
+                if settings.pages_counter != PagesCounter::Hidden && counter_opacity > 0.0 {
+                    let anchor = match settings.counter_corner {
+                        Corner::TopLeft => Align2::LEFT_TOP,
+                        Corner::TopRight => Align2::RIGHT_TOP,
+                        Corner::BottomLeft => Align2::LEFT_BOTTOM,
+                        Corner::BottomRight => Align2::RIGHT_BOTTOM,
+                    };
+
                     Area::new("pages_number")
-                        .anchor(Align2::RIGHT_TOP, Vec2::ZERO)
+                        .anchor(anchor, Vec2::ZERO)
                         .show(ctx, |ui| {
-                            let text = format!(
+                            // Wide pages split in two count as two pages, which only happens when a single page is displayed
+                            let (pages, total_pages) = match pages {
+                                (Some(page), None) => {
+                                    let (page, total_pages) = self.displayed_page_number(page, &settings);
+                                    ((Some(page), None), total_pages)
+                                },
+                                pages => (pages, self.total_pages),
+                            };
+
+                            let mut text = format!(
                                 "{}/{}",
                                 match pages {
                                     (None, None) => "-".to_string(),
@@ -549,12 +3371,433 @@ impl eframe::App for ReaderApp {
                                     (Some(left), Some(right)) => format!("{}-{}", left + 1, right + 1),
                                     (None, Some(_)) => unreachable!()
                                 },
-                                self.total_pages
+                                total_pages
                             );
 
-                            ui.add(Label::new(RichText::from(text).heading().background_color(Color32::BLACK)).wrap(false));
+                            if let Some(last_page) = pages.1.or(pages.0) {
+                                let remaining = total_pages - last_page - 1;
+
+                                match settings.pages_counter {
+                                    PagesCounter::Hidden | PagesCounter::Pages => {},
+                                    PagesCounter::PagesAndRemaining => text.push_str(&format!(" ({remaining} left)")),
+                                    PagesCounter::PagesRemainingAndEta => match self.cadence.estimate(remaining) {
+                                        Some(eta) => text.push_str(&format!(" ({remaining} left, ~{})", format_duration(eta))),
+                                        // The estimate is hidden until enough pages were turned
+                                        None => text.push_str(&format!(" ({remaining} left)")),
+                                    },
+                                }
+                            }
+
+                            if let Some(page_name) = self.img_source.page_name(current_page) {
+                                text.push_str(&format!(" — {page_name}"));
+                            }
+
+                            // Indicate which chapter (e.g. volume) the current page belongs to
+                            if let Some(chapter) = self.chapters.iter().rev().find(|chapter| chapter.first_page <= current_page) {
+                                text = format!("{} — page {text}", chapter.name);
+                            }
+
+                            // Tell the user why the pages are not in the files' order
+                            if let Some(reading_order) = &self.metadata.reading_order {
+                                text.push_str(&format!(" (order from {reading_order})"));
+                            }
+
+                            if let Some((position, volumes)) = self.series_position {
+                                text = format!("vol. {}/{volumes} — {text}", position + 1);
+                            }
+
+                            if let Some(name) = self.metadata.display_name() {
+                                text = format!("{name} — {text}");
+                            }
+
+                            // The counter is kept readable over light backgrounds
+                            let light_background = u32::from(red) * 299 + u32::from(green) * 587 + u32::from(blue) * 114 > 128_000;
+
+                            let (text_color, background_color) = if light_background {
+                                (Color32::BLACK, Color32::WHITE)
+                            } else {
+                                (ui.visuals().text_color(), Color32::BLACK)
+                            };
+
+                            let text = RichText::from(text)
+                                .size(settings.counter_font_size)
+                                .color(text_color.gamma_multiply(counter_opacity))
+                                .background_color(background_color.gamma_multiply(settings.counter_background_opacity.clamp(0.0, 1.0) * counter_opacity));
+
+                            ui.add(Label::new(text).wrap(false));
                         });
                 }
+
+                let loaded_pages = self.loaded_pages.read().unwrap();
+                let right_to_left = self.book_right_to_left.unwrap_or(settings.right_to_left);
+
+                if let Some(page) = show_seek_bar(ctx, current_page, self.total_pages, |page| matches!(loaded_pages.get(page), Some(Ok(_))), right_to_left) {
+                    // In double page mode, the seek bar goes from a spread to another
+                    let page = if settings.double_page { self.spread_start(page, &settings) } else { page };
+
+                    self.current_page.store(page, Ordering::Release);
+                }
+
+                // The loading threads request a repaint each time a page is loaded, which updates the progress
+                show_cache_progress(ctx, loaded_pages.loaded_count(), self.total_pages);
+
+                if settings.status_bar && self.total_pages > 0 {
+                    let page_state = |page: usize| match loaded_pages.get(page) {
+                        Some(Ok(_)) => "loaded",
+                        Some(Err(_)) => "failed",
+                        None => "loading",
+                    };
+
+                    let mut details = vec![];
+
+                    if let Some(name) = self.metadata.display_name().or_else(|| self.path.as_ref().and_then(|path| path.file_name()).map(|name| name.to_string_lossy().into_owned())) {
+                        details.push(name);
+                    }
+
+                    if let Some(page_name) = self.img_source.page_name(current_page) {
+                        details.push(page_name);
+                    }
+
+                    match current_page_details.get() {
+                        Some(((width, height), pixels_scale)) => {
+                            details.push(format!("{width}x{height}"));
+                            details.push(format!("{:.0}%", pixels_scale * 100.0));
+                        },
+                        None => details.push(format!("page {}", page_state(current_page))),
+                    }
+
+                    let last_displayed_page = pages.1.or(pages.0).unwrap_or(current_page);
+
+                    if let Some(previous_page) = current_page.checked_sub(1) {
+                        details.push(format!("previous page {}", page_state(previous_page)));
+                    }
+
+                    if last_displayed_page + 1 < self.total_pages {
+                        details.push(format!("next page {}", page_state(last_displayed_page + 1)));
+                    }
+
+                    if self.path.is_some() {
+                        details.push(self.item_stats.summary());
+                    }
+
+                    show_status_bar(ctx, details.join(" — "), self.total_pages > 1);
+                }
+
+                drop(loaded_pages);
+
+                if settings.debug_overlay {
+                    show_debug_hud(ctx, &self.metrics.read().unwrap());
+                }
+
+                if self.help_shown {
+                    show_help(ctx, &settings.key_bindings);
+                }
+
+                self.toasts.show(ctx);
             });
+
+        if let Some(action) = welcome_action {
+            self.handle_welcome_action(action);
+        }
+
+        // Done last, so the title shows the item and page the user just went to
+        self.update_window_title(frame);
+    }
+}
+
+/// Check if a position is over the displayed pages, rather than over a window, an overlay or a panel
+fn is_over_pages(ctx: &Context, pages_area: Rect, pos: Pos2) -> bool {
+    pages_area.contains(pos) && ctx.layer_id_at(pos).is_none_or(|layer| layer.order == Order::Background)
+}
+
+/// Get the page to jump to from the "jump to page" prompt, as a page number, an offset from the current page
+/// (e.g. `+25` or `-10`) or a percentage of the book (e.g. `50%`)
+/// The page is clamped to the pages of the book
+fn parse_page_target(input: &str, current_page: usize, total_pages: usize) -> Result<usize, String> {
+    let input = input.trim();
+
+    if total_pages == 0 {
+        return Err("There is no page to jump to".to_owned());
+    }
+
+    let invalid = || "Invalid page: expected a page number, an offset (e.g. +25 or -10) or a percentage (e.g. 50%)".to_owned();
+
+    // Pages are numbered from 1 in the prompt
+    let page_number = if let Some(percentage) = input.strip_suffix('%') {
+        let percentage = percentage.trim().parse::<f64>().ok().filter(|percentage| percentage.is_finite()).ok_or_else(invalid)?;
+        (percentage.clamp(0.0, 100.0) / 100.0 * total_pages as f64).ceil() as isize
+    } else if let Some(offset) = input.strip_prefix('+') {
+        (current_page as isize + 1).saturating_add(offset.trim().parse::<isize>().map_err(|_| invalid())?)
+    } else if let Some(offset) = input.strip_prefix('-') {
+        (current_page as isize + 1).saturating_sub(offset.trim().parse::<isize>().map_err(|_| invalid())?)
+    } else {
+        input.parse::<isize>().map_err(|_| invalid())?
+    };
+
+    Ok(usize::try_from(page_number.clamp(1, total_pages as isize)).unwrap() - 1)
+}
+
+/// Check if the horizontal inputs go the other way, the pages being laid out from right to left
+fn mirrored_navigation(invert_keys_in_rtl: bool, right_to_left: bool) -> bool {
+    invert_keys_in_rtl && right_to_left
+}
+
+/// Get the reading direction (1 to go forward, -1 to go backwards) of a horizontal input towards a side of the window
+/// (1 for the right one, -1 for the left one)
+fn reading_direction(side: isize, mirrored: bool) -> isize {
+    if mirrored { -side } else { side }
+}
+
+/// Get the page a relative page change in the reading direction leads to, staying within the item's pages
+/// In double page mode, whole spreads are turned (except for the first page if it's displayed alone), their first page
+/// being provided by `neighbour_spread_start`
+fn turned_page(current_page: usize, inc: isize, total_pages: usize, double_page: bool, first_page_single: bool, neighbour_spread_start: impl FnOnce(bool) -> usize) -> usize {
+    if double_page && (current_page != 0 || !first_page_single) {
+        neighbour_spread_start(inc > 0)
+    } else if inc < 0 {
+        current_page.saturating_sub(inc.unsigned_abs())
+    } else {
+        std::cmp::min(current_page + inc.unsigned_abs(), total_pages.saturating_sub(1))
+    }
+}
+
+/// Get the first page of the spread a page belongs to, in double page mode, if no page is wide
+fn spread_start(page: usize, first_page_single: bool) -> usize {
+    if !first_page_single {
+        page - page % 2
+    } else if page == 0 {
+        0
+    } else {
+        page - (page - 1) % 2
+    }
+}
+
+/// Read the dimensions of a loaded page, if wide pages are displayed alone in double page mode
+/// Pages whose dimensions can't be read are considered narrow
+fn record_dimensions(page_dimensions: &RwLock<GapVec<(usize, usize)>>, page: usize, result: &PageLoadingResult, settings: &RwLock<Settings>) {
+    if settings.read().unwrap().wide_page_ratio.is_none() {
+        return;
+    }
+
+    if let Ok((filename, bytes)) = result {
+        if let Ok(dimensions) = image_dimensions(filename, bytes) {
+            page_dimensions.write().unwrap().set(page, dimensions);
+        }
+    }
+}
+
+/// Get the index of a page after its source's pages were sorted, from the previous index of each page
+fn sorted_page(page: usize, previous_indexes: &[usize]) -> usize {
+    previous_indexes.iter().position(|&previous| previous == page).unwrap_or(0)
+}
+
+/// Make bookmarks follow their pages after their source's pages were sorted
+fn sort_bookmarks(bookmarks: &mut [Bookmark], previous_indexes: &[usize]) {
+    for bookmark in bookmarks.iter_mut() {
+        bookmark.page = sorted_page(bookmark.page, previous_indexes);
+    }
+
+    bookmarks.sort_by_key(|bookmark| bookmark.page);
+}
+
+/// Get the current time, in seconds since the Unix epoch
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Get the key an item is remembered under
+/// The same item may be opened with different paths (e.g. relative ones)
+fn item_key(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// Get the scale to display a page at, to fit it in the provided space
+fn fit_scale(fit_mode: FitMode, page_size: Vec2, available: Vec2, pixels_per_point: f32) -> f32 {
+    match fit_mode {
+        FitMode::FitHeight => available.y / page_size.y,
+        FitMode::FitWidth => available.x / page_size.x,
+        FitMode::FitPage => (available.x / page_size.x).min(available.y / page_size.y),
+        FitMode::Original => 1.0 / pixels_per_point,
+    }
+}
+
+/// Get the bounds of the offset the pages can be moved by while staying on screen
+/// Pages larger than the window can be moved until their edges are reached, the other ones can't be moved
+fn pan_bounds(pages_rect: Option<Rect>, pages_area: Rect) -> Rect {
+    let Some(pages_rect) = pages_rect else {
+        return Rect::from_min_max(Pos2::ZERO, Pos2::ZERO);
+    };
+
+    let range = |min: f32, max: f32, area_min: f32, area_max: f32| {
+        if max - min > area_max - area_min {
+            (area_max - max, area_min - min)
+        } else {
+            (0.0, 0.0)
+        }
+    };
+
+    let (min_x, max_x) = range(pages_rect.left(), pages_rect.right(), pages_area.left(), pages_area.right());
+    let (min_y, max_y) = range(pages_rect.top(), pages_rect.bottom(), pages_area.top(), pages_area.bottom());
+
+    Rect::from_min_max(pos2(min_x, min_y), pos2(max_x, max_y))
+}
+
+/// Get the position of an archive in its series (= the archives of its directory), as well as
+/// the number of archives in it, if there are several of them
+fn series_position(path: &Path) -> Option<(usize, usize)> {
+    if !path.is_file() {
+        return None;
+    }
+
+    let volumes = series_items(path.parent()?).ok()?.into_iter().filter(|item| item.is_file()).collect::<Vec<_>>();
+    let position = volumes.iter().position(|volume| volume == path)?;
+
+    (volumes.len() > 1).then_some((position, volumes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::SortOrder;
+
+    const LANDSCAPE: Vec2 = Vec2::new(2000.0, 1000.0);
+    const PORTRAIT: Vec2 = Vec2::new(1000.0, 2000.0);
+
+    /// Window the pages are displayed in
+    const WINDOW: Vec2 = Vec2::new(1600.0, 1000.0);
+
+    /// Space available to each page in double page mode
+    const HALF_WINDOW: Vec2 = Vec2::new(800.0, 1000.0);
+
+    fn assert_scale(fit_mode: FitMode, page_size: Vec2, available: Vec2, expected: f32) {
+        let scale = fit_scale(fit_mode, page_size, available, 1.0);
+        assert!((scale - expected).abs() < 1e-6, "expected a scale of {expected}, got {scale}");
+    }
+
+    /// Turn the page of a 10 pages item towards a side of the window, with spreads of two pages in double page mode
+    fn turn(current_page: usize, side: isize, mirrored: bool, double_page: bool, first_page_single: bool) -> usize {
+        let spread_start = |forward| if forward { Some(current_page + 2).filter(|&next| next < 10).unwrap_or(current_page) } else { current_page.saturating_sub(2) };
+
+        turned_page(current_page, reading_direction(side, mirrored), 10, double_page, first_page_single, spread_start)
+    }
+
+    #[test]
+    fn turns_pages_left_to_right() {
+        assert!(!mirrored_navigation(false, false));
+        assert!(!mirrored_navigation(true, false));
+
+        // Single page
+        assert_eq!(turn(4, 1, false, false, false), 5);
+        assert_eq!(turn(4, -1, false, false, false), 3);
+        assert_eq!(turn(9, 1, false, false, false), 9);
+        assert_eq!(turn(0, -1, false, false, false), 0);
+
+        // Double page
+        assert_eq!(turn(4, 1, false, true, false), 6);
+        assert_eq!(turn(4, -1, false, true, false), 2);
+        assert_eq!(turn(8, 1, false, true, false), 8);
+        assert_eq!(turn(0, -1, false, true, false), 0);
+    }
+
+    #[test]
+    fn turns_pages_right_to_left() {
+        assert!(mirrored_navigation(true, true));
+
+        // Right-to-left pages are still read from left to right if the inputs aren't mirrored
+        assert!(!mirrored_navigation(false, true));
+
+        // Single page
+        assert_eq!(turn(4, -1, true, false, false), 5);
+        assert_eq!(turn(4, 1, true, false, false), 3);
+        assert_eq!(turn(9, -1, true, false, false), 9);
+        assert_eq!(turn(0, 1, true, false, false), 0);
+
+        // Double page
+        assert_eq!(turn(4, -1, true, true, false), 6);
+        assert_eq!(turn(4, 1, true, true, false), 2);
+        assert_eq!(turn(8, -1, true, true, false), 8);
+        assert_eq!(turn(0, 1, true, true, false), 0);
+    }
+
+    #[test]
+    fn turns_first_page_alone() {
+        // The first page is a spread on its own, the next ones being paired from the second one
+        assert_eq!(turn(0, 1, false, true, true), 1);
+        assert_eq!(turn(1, 1, false, true, true), 3);
+        assert_eq!(turn(1, -1, false, true, true), 0);
+        assert_eq!(turn(0, -1, true, true, true), 1);
+
+        // Only matters in double page mode
+        assert_eq!(turn(0, 1, false, false, true), 1);
+        assert_eq!(turn(1, 1, false, false, true), 2);
+
+        assert_eq!(spread_start(0, true), 0);
+        assert_eq!(spread_start(2, true), 1);
+        assert_eq!(spread_start(3, false), 2);
+    }
+
+    #[test]
+    fn fits_page() {
+        // Limited by the width
+        assert_scale(FitMode::FitPage, LANDSCAPE, WINDOW, 0.8);
+        assert_scale(FitMode::FitPage, LANDSCAPE, HALF_WINDOW, 0.4);
+        assert_scale(FitMode::FitPage, PORTRAIT, HALF_WINDOW, 0.5);
+
+        // Limited by the height
+        assert_scale(FitMode::FitPage, PORTRAIT, WINDOW, 0.5);
+        assert_scale(FitMode::FitPage, Vec2::new(500.0, 2000.0), HALF_WINDOW, 0.5);
+    }
+
+    #[test]
+    fn fits_height() {
+        assert_scale(FitMode::FitHeight, LANDSCAPE, WINDOW, 1.0);
+        assert_scale(FitMode::FitHeight, LANDSCAPE, HALF_WINDOW, 1.0);
+        assert_scale(FitMode::FitHeight, PORTRAIT, WINDOW, 0.5);
+        assert_scale(FitMode::FitHeight, PORTRAIT, HALF_WINDOW, 0.5);
+    }
+
+    #[test]
+    fn fits_width() {
+        assert_scale(FitMode::FitWidth, LANDSCAPE, WINDOW, 0.8);
+        assert_scale(FitMode::FitWidth, LANDSCAPE, HALF_WINDOW, 0.4);
+        assert_scale(FitMode::FitWidth, PORTRAIT, WINDOW, 1.6);
+        assert_scale(FitMode::FitWidth, PORTRAIT, HALF_WINDOW, 0.8);
+    }
+
+    #[test]
+    fn keeps_original_size() {
+        for page_size in [LANDSCAPE, PORTRAIT] {
+            for available in [WINDOW, HALF_WINDOW] {
+                assert_scale(FitMode::Original, page_size, available, 1.0);
+            }
+        }
+
+        // One pixel of the page per pixel of the screen, not per point
+        assert!((fit_scale(FitMode::Original, PORTRAIT, WINDOW, 2.0) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bookmarks_follow_sorted_pages() {
+        let dir = std::env::temp_dir().join(format!("reader-test-{}-sorted-bookmarks", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        for name in ["page1.png", "page2.png", "page10.png"] {
+            fs::write(dir.join(name), []).unwrap();
+        }
+
+        let mut img_source = load_image_source(&dir, None).unwrap();
+        img_source.sort_pages(SortOrder::Natural).unwrap();
+
+        let mut bookmarks = vec![Bookmark { page: 1, label: "second".to_owned() }, Bookmark { page: 2, label: "last".to_owned() }];
+        let names = bookmarks.iter().map(|bookmark| img_source.page_name(bookmark.page)).collect::<Vec<_>>();
+
+        let previous_indexes = img_source.sort_pages(SortOrder::Name).unwrap();
+        sort_bookmarks(&mut bookmarks, &previous_indexes);
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        // "page10" now comes before "page2"
+        assert_eq!(bookmarks.iter().map(|bookmark| bookmark.label.as_str()).collect::<Vec<_>>(), ["last", "second"]);
+        assert_eq!(bookmarks.iter().map(|bookmark| img_source.page_name(bookmark.page)).collect::<Vec<_>>(), names.into_iter().rev().collect::<Vec<_>>());
     }
 }
@@ -0,0 +1,187 @@
+use egui::{vec2, Align2, Color32, Context, Key, RichText, ScrollArea, TextEdit, Window};
+use serde::{Deserialize, Serialize};
+
+use super::{page::Rotation, thumbnails::Thumbnails};
+
+/// Size of the thumbnails displayed in the bookmarks list
+const BOOKMARK_THUMBNAIL_SIZE: f32 = 72.0;
+
+/// A page the user bookmarked
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Bookmark {
+    pub page: usize,
+    pub label: String,
+}
+
+/// State of the bookmarks list window
+#[derive(Default)]
+pub struct BookmarksWindow {
+    /// Index of the selected bookmark
+    selected: usize,
+
+    /// New label of the selected bookmark, while it's being renamed
+    renaming: Option<String>,
+}
+
+/// Action requested by the user from the bookmarks list
+pub enum BookmarkAction {
+    /// Jump to a bookmarked page
+    Jump(usize),
+
+    /// Remove a bookmark (from its index)
+    Remove(usize),
+
+    /// Change the label of a bookmark (from its index)
+    Rename(usize, String),
+
+    /// Undo the last change (e.g. restore a removed bookmark)
+    Undo,
+
+    /// Close the list
+    Close,
+}
+
+impl BookmarksWindow {
+    /// Handle the keys used to go through the bookmarks without a mouse
+    fn handle_keys(&mut self, ctx: &Context, bookmarks: &[Bookmark]) -> Option<BookmarkAction> {
+        let pressed = |key| ctx.input(|i| i.key_pressed(key));
+
+        if let Some(label) = &self.renaming {
+            if pressed(Key::Enter) {
+                let label = label.clone();
+                self.renaming = None;
+
+                return Some(BookmarkAction::Rename(self.selected, label));
+            }
+
+            if pressed(Key::Escape) {
+                self.renaming = None;
+            }
+
+            return None;
+        }
+
+        if pressed(Key::Escape) {
+            return Some(BookmarkAction::Close);
+        }
+
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(Key::Z)) {
+            return Some(BookmarkAction::Undo);
+        }
+
+        if bookmarks.is_empty() {
+            return None;
+        }
+
+        if pressed(Key::ArrowDown) {
+            self.selected = (self.selected + 1).min(bookmarks.len() - 1);
+        }
+
+        if pressed(Key::ArrowUp) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+
+        if pressed(Key::Enter) {
+            return Some(BookmarkAction::Jump(bookmarks[self.selected].page));
+        }
+
+        if pressed(Key::Delete) {
+            return Some(BookmarkAction::Remove(self.selected));
+        }
+
+        if pressed(Key::F2) {
+            self.renaming = Some(bookmarks[self.selected].label.clone());
+        }
+
+        None
+    }
+
+    /// Render the list of the bookmarks of the current item
+    ///
+    /// The list can be used with the keyboard only: arrow keys to select a bookmark,
+    /// Enter to jump to it, F2 to rename it, Delete to remove it and Ctrl+Z to undo the last change
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        bookmarks: &[Bookmark],
+        thumbnails: &mut Thumbnails,
+        rotation: Rotation,
+    ) -> Option<BookmarkAction> {
+        // Removed bookmarks may leave the selection out of the list
+        self.selected = self.selected.min(bookmarks.len().saturating_sub(1));
+
+        let mut action = self.handle_keys(ctx, bookmarks);
+
+        Window::new("Bookmarks")
+            .pivot(Align2::CENTER_CENTER)
+            .default_pos(ctx.screen_rect().center())
+            .collapsible(false)
+            .show(ctx, |ui| {
+                if bookmarks.is_empty() {
+                    ui.label("No bookmark yet, press B to bookmark the current page");
+                }
+
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for (index, bookmark) in bookmarks.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if thumbnails.show_thumbnail(
+                                ui,
+                                bookmark.page,
+                                vec2(BOOKMARK_THUMBNAIL_SIZE, BOOKMARK_THUMBNAIL_SIZE),
+                                index == self.selected,
+                                rotation,
+                            ) {
+                                action = Some(BookmarkAction::Jump(bookmark.page));
+                            }
+
+                            ui.vertical(|ui| {
+                                let title = RichText::new(format!("Page {}", bookmark.page + 1));
+
+                                ui.label(if index == self.selected {
+                                    title.strong().color(Color32::LIGHT_BLUE)
+                                } else {
+                                    title.strong()
+                                });
+
+                                match &mut self.renaming {
+                                    Some(label) if index == self.selected => {
+                                        ui.add(TextEdit::singleline(label).hint_text("Label"))
+                                            .request_focus();
+                                    }
+
+                                    _ => {
+                                        ui.label(&bookmark.label);
+                                    }
+                                }
+
+                                ui.horizontal(|ui| {
+                                    if ui.button("Go").clicked() {
+                                        action = Some(BookmarkAction::Jump(bookmark.page));
+                                    }
+
+                                    if ui.button("Rename").clicked() {
+                                        self.selected = index;
+                                        self.renaming = Some(bookmark.label.clone());
+                                    }
+
+                                    if ui.button("Delete").clicked() {
+                                        action = Some(BookmarkAction::Remove(index));
+                                    }
+                                });
+                            });
+                        });
+                    }
+                });
+
+                ui.separator();
+                ui.label(
+                    RichText::new(
+                        "Up/Down: select, Enter: go, F2: rename, Delete: remove, Ctrl+Z: undo",
+                    )
+                    .weak(),
+                );
+            });
+
+        action
+    }
+}
@@ -0,0 +1,35 @@
+use egui::{Align2, Area, Color32, Context, Frame, Label, ProgressBar, RichText, Vec2};
+
+/// Width of the caching progress bar
+const BAR_WIDTH: f32 = 160.0;
+
+/// Render the progress of the loading of the whole item's pages, in the bottom-right corner
+/// Nothing is displayed once all pages are loaded
+pub fn show_cache_progress(ctx: &Context, loaded: usize, total_pages: usize) {
+    if loaded >= total_pages {
+        return;
+    }
+
+    Area::new("cache_progress")
+        .anchor(Align2::RIGHT_BOTTOM, Vec2::ZERO)
+        .show(ctx, |ui| {
+            Frame::none()
+                .fill(Color32::from_black_alpha(160))
+                .inner_margin(4.0)
+                .show(ui, |ui| {
+                    ui.add(
+                        Label::new(
+                            RichText::new(format!("{loaded}/{total_pages} pages cached"))
+                                .small()
+                                .color(Color32::WHITE),
+                        )
+                        .wrap(false),
+                    );
+
+                    ui.add(
+                        ProgressBar::new(loaded as f32 / total_pages as f32)
+                            .desired_width(BAR_WIDTH),
+                    );
+                });
+        });
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use egui::{Align2, Area, Color32, Context, Frame, Label, RichText, Vec2};
+
+use crate::metrics::Metrics;
+
+/// Render the debug overlay, showing where time goes when displaying pages
+pub fn show_debug_hud(ctx: &Context, metrics: &Metrics) {
+    let mut lines = vec![format!(
+        "Cache: {} hits, {} misses",
+        metrics.cache_hits, metrics.cache_misses
+    )];
+
+    // Most recent pages are displayed first
+    lines.extend(metrics.timings().rev().map(|timings| {
+        format!(
+            "Page {:>4}: load {} | decode {} | upload {}",
+            timings.page + 1,
+            format_ms(timings.load),
+            format_ms(timings.decode),
+            format_ms(timings.upload)
+        )
+    }));
+
+    Area::new("debug_hud")
+        .anchor(Align2::LEFT_TOP, Vec2::ZERO)
+        .show(ctx, |ui| {
+            Frame::none()
+                .fill(Color32::from_black_alpha(200))
+                .inner_margin(4.0)
+                .show(ui, |ui| {
+                    ui.add(
+                        Label::new(
+                            RichText::new(lines.join("\n"))
+                                .monospace()
+                                .color(Color32::WHITE),
+                        )
+                        .wrap(false),
+                    );
+                });
+        });
+}
+
+/// Format an optional duration in milliseconds, with a fixed width
+fn format_ms(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!("{:>7.1} ms", duration.as_secs_f64() * 1000.0),
+        None => format!("{:>10}", "-"),
+    }
+}
@@ -0,0 +1,58 @@
+use egui::{Align2, Area, Color32, Context, Frame, Grid, Order, RichText, ScrollArea, Vec2};
+
+use crate::keybindings::{Action, KeyBindings};
+
+/// Render the help overlay, listing the keys bound to each action above the pages
+pub fn show_help(ctx: &Context, bindings: &KeyBindings) {
+    let max_height = ctx.screen_rect().height() - 80.0;
+
+    Area::new("help")
+        .order(Order::Foreground)
+        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .show(ctx, |ui| {
+            Frame::none()
+                .fill(Color32::from_black_alpha(220))
+                .rounding(8.0)
+                .inner_margin(16.0)
+                .show(ui, |ui| {
+                    ui.heading(RichText::new("Keyboard shortcuts").color(Color32::WHITE));
+                    ui.add_space(8.0);
+
+                    ScrollArea::vertical()
+                        .max_height(max_height)
+                        .show(ui, |ui| {
+                            Grid::new("shortcuts")
+                                .num_columns(2)
+                                .spacing([24.0, 4.0])
+                                .show(ui, |ui| {
+                                    for action in Action::ALL.iter().copied() {
+                                        let keys = bindings.input_names(action);
+
+                                        let keys = if keys.is_empty() {
+                                            "(none)".to_owned()
+                                        } else {
+                                            keys.join(" / ")
+                                        };
+
+                                        ui.label(
+                                            RichText::new(keys)
+                                                .monospace()
+                                                .color(Color32::LIGHT_BLUE),
+                                        );
+                                        ui.label(
+                                            RichText::new(action.description())
+                                                .color(Color32::WHITE),
+                                        );
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+
+                    ui.add_space(8.0);
+                    ui.label(
+                        RichText::new("Press any key to close, Ctrl+K to change the shortcuts")
+                            .weak(),
+                    );
+                });
+        });
+}
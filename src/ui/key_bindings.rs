@@ -0,0 +1,143 @@
+use egui::{Align2, Color32, Context, Event, Grid, Key, RichText, ScrollArea, Window};
+#[cfg(feature = "gamepad")]
+use gilrs::Button;
+
+use crate::keybindings::{Action, KeyBindings, KeyCombo};
+
+/// State of the key bindings editor window
+#[derive(Default)]
+pub struct KeyBindingsWindow {
+    /// Action waiting for a key (or a gamepad button) to be pressed, to be bound to it
+    capturing: Option<Action>,
+}
+
+impl KeyBindingsWindow {
+    /// Bind the action waiting for an input (if any) to the first gamepad button pressed since the last frame
+    #[cfg(feature = "gamepad")]
+    pub fn capture_buttons(&mut self, bindings: &mut KeyBindings, pressed_buttons: &[Button]) {
+        if let (Some(action), Some(button)) = (self.capturing, pressed_buttons.first()) {
+            bindings.add_button(action, *button);
+            self.capturing = None;
+        }
+    }
+
+    /// Render the editor, changing the provided bindings
+    ///
+    /// Returns `true` once the editor is closed
+    pub fn show(&mut self, ctx: &Context, bindings: &mut KeyBindings) -> bool {
+        let pressed = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                Event::Key {
+                    key,
+                    pressed: true,
+                    repeat: false,
+                    modifiers,
+                } => Some(KeyCombo::pressed(*key, *modifiers)),
+                _ => None,
+            })
+        });
+
+        match (self.capturing, pressed) {
+            // Escape cancels the capture, so it can't be bound without modifiers from here
+            (Some(_), Some(combo)) if combo.key == Key::Escape && !combo.ctrl && !combo.alt => {
+                self.capturing = None;
+            }
+
+            (Some(action), Some(combo)) => {
+                bindings.add_key(action, combo);
+                self.capturing = None;
+            }
+
+            (None, Some(combo)) if combo.key == Key::Escape => return true,
+
+            _ => {}
+        }
+
+        let mut closed = false;
+
+        Window::new("Key bindings")
+            .pivot(Align2::CENTER_CENTER)
+            .default_pos(ctx.screen_rect().center())
+            .collapsible(false)
+            .show(ctx, |ui| {
+                for (combo, action, other) in bindings.conflicts() {
+                    ui.label(
+                        RichText::new(format!(
+                            "{combo} is bound to both \"{}\" and \"{}\"",
+                            action.description(),
+                            other.description()
+                        ))
+                        .color(Color32::LIGHT_RED),
+                    );
+                }
+
+                ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+                    Grid::new("key_bindings")
+                        .num_columns(if cfg!(feature = "gamepad") { 4 } else { 3 })
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for action in Action::ALL.iter().copied() {
+                                ui.label(action.description());
+
+                                ui.horizontal(|ui| {
+                                    for combo in bindings.keys(action).to_vec() {
+                                        if ui
+                                            .button(combo.to_string())
+                                            .on_hover_text("Click to remove")
+                                            .clicked()
+                                        {
+                                            bindings.remove_key(action, combo);
+                                        }
+                                    }
+
+                                    if self.capturing == Some(action) {
+                                        ui.label(
+                                            RichText::new("Press a key or a gamepad button...")
+                                                .italics(),
+                                        );
+                                    } else if ui.button("+").on_hover_text("Add a key or a gamepad button").clicked() {
+                                        self.capturing = Some(action);
+                                    }
+                                });
+
+                                #[cfg(feature = "gamepad")]
+                                ui.horizontal(|ui| {
+                                    for button in bindings.buttons(action).to_vec() {
+                                        if ui
+                                            .button(format!("{button:?}"))
+                                            .on_hover_text("Click to remove")
+                                            .clicked()
+                                        {
+                                            bindings.remove_button(action, button);
+                                        }
+                                    }
+                                });
+
+                                if ui.button("Reset").clicked() {
+                                    bindings.reset(action);
+                                }
+
+                                ui.end_row();
+                            }
+                        });
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(
+                            "Click a key or button to remove it, + then press a key or button to add one, Escape to close",
+                        )
+                        .weak(),
+                    );
+
+                    if ui.button("Close").clicked() {
+                        closed = true;
+                    }
+                });
+            });
+
+        closed
+    }
+}
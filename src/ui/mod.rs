@@ -1,6 +1,19 @@
 use rfd::{MessageDialog, MessageLevel};
 
 pub mod app;
+mod bookmarks;
+mod cache_progress;
+mod debug_hud;
+mod help;
+mod key_bindings;
+mod page;
+mod recent_items;
+mod seek_bar;
+mod status_bar;
+mod thumbnails;
+mod toasts;
+mod undo;
+mod welcome;
 
 pub fn show_err_dialog(err: anyhow::Error) {
     MessageDialog::new()
@@ -0,0 +1,256 @@
+use egui::{
+    epaint::Vertex, pos2, vec2, Color32, ColorImage, Context, FontId, Mesh, Painter, Pos2, Rect,
+    Response, Sense, TextureHandle, TextureOptions, Ui, Vec2,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::decoders::{downscale, DecodedImage, PixelFormat};
+
+/// A page ready to be displayed
+///
+/// Images larger than the maximum texture size supported by the GPU are split into
+/// multiple textures stacked vertically, but still represent a single logical page
+#[derive(Clone)]
+pub struct DisplayablePage {
+    /// Textures making up the page, from top to bottom, along with their own size
+    tiles: Vec<(TextureHandle, Vec2)>,
+
+    /// Size of the whole page
+    size: Vec2,
+
+    /// Width and height of the decoded image, which may be larger than the textures
+    decoded_size: (usize, usize),
+
+    /// Problem encountered while decoding the page, displayed over it
+    warning: Option<String>,
+}
+
+impl DisplayablePage {
+    /// Upload a decoded image to the GPU, to be scaled with the provided options
+    /// The provided number of threads is used to downscale it if required
+    pub fn upload(
+        ctx: &Context,
+        name: &str,
+        image: DecodedImage,
+        texture_options: TextureOptions,
+        threads: usize,
+    ) -> Self {
+        let max_side = ctx.input(|i| i.max_texture_side);
+
+        let DecodedImage {
+            mut pixels,
+            format,
+            mut width,
+            mut height,
+            warning,
+        } = image;
+
+        let channels = format.channels();
+        let decoded_size = (width, height);
+
+        // Tiles are stacked vertically, so pages too wide for a single texture are downscaled
+        if width > max_side {
+            let new_height = (height * max_side / width).max(1);
+
+            pixels = downscale(
+                &pixels, width, height, channels, max_side, new_height, threads,
+            );
+            width = max_side;
+            height = new_height;
+        }
+
+        let tiles = pixels
+            .chunks(width * max_side * channels)
+            .enumerate()
+            .map(|(i, tile_pixels)| {
+                let tile_height = tile_pixels.len() / (width * channels);
+                let size = [width, tile_height];
+
+                // Textures are always made of RGBA pixels, so other formats are only expanded here
+                let image = match format {
+                    PixelFormat::Gray8 => ColorImage {
+                        size,
+                        pixels: tile_pixels
+                            .iter()
+                            .map(|value| Color32::from_gray(*value))
+                            .collect(),
+                    },
+                    PixelFormat::Rgb8 => ColorImage::from_rgb(size, tile_pixels),
+                    PixelFormat::Rgba8 => ColorImage::from_rgba_unmultiplied(size, tile_pixels),
+                };
+
+                let texture = ctx.load_texture(format!("{name}[tile-{i}]"), image, texture_options);
+
+                (texture, vec2(width as f32, tile_height as f32))
+            })
+            .collect();
+
+        Self {
+            tiles,
+            size: vec2(width as f32, height as f32),
+            decoded_size,
+            warning,
+        }
+    }
+
+    /// Size of the whole page
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Width and height of the decoded image the page was made from
+    pub fn decoded_size(&self) -> (usize, usize) {
+        self.decoded_size
+    }
+
+    /// Render the page with the provided scale and rotation
+    pub fn show(&self, ui: &mut Ui, scale: f32, rotation: Rotation) -> Response {
+        // The page is allocated as a single widget, so it is laid out like a single image
+        let (rect, response) =
+            ui.allocate_exact_size(rotation.rotate_size(self.size) * scale, Sense::hover());
+
+        self.paint(ui.painter(), rect.min, rect, scale, rotation);
+
+        response
+    }
+
+    /// Render a half of the page (once rotated) with the provided scale and rotation
+    pub fn show_half(
+        &self,
+        ui: &mut Ui,
+        scale: f32,
+        rotation: Rotation,
+        half: PageHalf,
+    ) -> Response {
+        let size = rotation.rotate_size(self.size) * scale;
+        let (rect, response) = ui.allocate_exact_size(vec2(size.x / 2.0, size.y), Sense::hover());
+
+        // The whole page is painted, only the requested half being visible
+        let origin = match half {
+            PageHalf::Left => rect.min,
+            PageHalf::Right => rect.min - vec2(size.x / 2.0, 0.0),
+        };
+
+        let painter = ui.painter().with_clip_rect(rect.intersect(ui.clip_rect()));
+        self.paint(&painter, origin, rect, scale, rotation);
+
+        response
+    }
+
+    /// Paint the page with its top-left corner at the provided position
+    /// Its warning (if any) is painted over the visible part of the page
+    fn paint(
+        &self,
+        painter: &Painter,
+        origin: Pos2,
+        visible: Rect,
+        scale: f32,
+        rotation: Rotation,
+    ) {
+        let mut top = 0.0;
+
+        for (texture, size) in &self.tiles {
+            // Corners of the tile in the page, and the corresponding ones in the texture
+            let corners = [
+                (pos2(0.0, top), pos2(0.0, 0.0)),
+                (pos2(size.x, top), pos2(1.0, 0.0)),
+                (pos2(size.x, top + size.y), pos2(1.0, 1.0)),
+                (pos2(0.0, top + size.y), pos2(0.0, 1.0)),
+            ];
+
+            // The tile is drawn as a mesh, so it can be rotated without uploading it again
+            let mut mesh = Mesh::with_texture(texture.id());
+
+            for (corner, uv) in corners {
+                mesh.vertices.push(Vertex {
+                    pos: origin + rotation.rotate_point(corner, self.size).to_vec2() * scale,
+                    uv,
+                    color: Color32::WHITE,
+                });
+            }
+
+            mesh.add_triangle(0, 1, 2);
+            mesh.add_triangle(0, 2, 3);
+
+            painter.add(mesh);
+
+            top += size.y;
+        }
+
+        // Pages that could only be partially decoded still need to be distinguished
+        if let Some(warning) = &self.warning {
+            let galley = painter.layout_no_wrap(
+                format!("⚠ {warning}"),
+                FontId::proportional(16.0),
+                Color32::YELLOW,
+            );
+
+            let text_rect = Rect::from_min_size(visible.left_top() + vec2(8.0, 8.0), galley.size());
+
+            painter.rect_filled(text_rect.expand(4.0), 4.0, Color32::from_black_alpha(200));
+            painter.galley(text_rect.min, galley);
+        }
+    }
+}
+
+/// Half of a page, as displayed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PageHalf {
+    Left,
+    Right,
+}
+
+impl PageHalf {
+    /// Get the other half
+    pub fn other(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+/// Rotation applied to pages when displaying them, clockwise
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    None,
+    Quarter,
+    Half,
+    ThreeQuarters,
+}
+
+impl Rotation {
+    /// Rotate by a quarter turn, clockwise
+    pub fn clockwise(self) -> Self {
+        match self {
+            Self::None => Self::Quarter,
+            Self::Quarter => Self::Half,
+            Self::Half => Self::ThreeQuarters,
+            Self::ThreeQuarters => Self::None,
+        }
+    }
+
+    /// Rotate by a quarter turn, counter-clockwise
+    pub fn counter_clockwise(self) -> Self {
+        self.clockwise().clockwise().clockwise()
+    }
+
+    /// Get the size of a page once rotated
+    pub fn rotate_size(self, size: Vec2) -> Vec2 {
+        match self {
+            Self::None | Self::Half => size,
+            Self::Quarter | Self::ThreeQuarters => vec2(size.y, size.x),
+        }
+    }
+
+    /// Get the position of a point of a page (relative to its top-left corner) once it is rotated
+    fn rotate_point(self, point: Pos2, size: Vec2) -> Pos2 {
+        match self {
+            Self::None => point,
+            Self::Quarter => pos2(size.y - point.y, point.x),
+            Self::Half => pos2(size.x - point.x, size.y - point.y),
+            Self::ThreeQuarters => pos2(point.y, size.x - point.x),
+        }
+    }
+}
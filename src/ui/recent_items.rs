@@ -0,0 +1,145 @@
+use std::path::PathBuf;
+
+use egui::{Align2, Color32, Context, Key, RichText, ScrollArea, Window};
+use serde::{Deserialize, Serialize};
+
+use super::welcome::is_url;
+
+/// An item the user opened recently
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecentItem {
+    pub path: PathBuf,
+
+    /// When the item was opened (in seconds since the Unix epoch)
+    pub opened_at: u64,
+}
+
+impl RecentItem {
+    /// Check if the item can still be opened
+    /// Remote items can't be checked without downloading them
+    pub fn exists(&self) -> bool {
+        self.path.exists() || is_url(&self.path)
+    }
+}
+
+/// Action requested by the user from the recent items list
+pub enum RecentItemsAction {
+    /// Open a recent item
+    Open(PathBuf),
+
+    /// Remove an item from the recent ones
+    Forget(PathBuf),
+
+    /// Close the list
+    Close,
+}
+
+/// State of the recent items list window
+#[derive(Default)]
+pub struct RecentItemsWindow {
+    /// Index of the selected item
+    selected: usize,
+}
+
+impl RecentItemsWindow {
+    /// Render the list of the recently opened items
+    ///
+    /// The list can be used with the keyboard only: arrow keys to select an item, Enter to open it
+    /// (or to remove it if it doesn't exist anymore) and Delete to remove it
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        items: &[RecentItem],
+        now: u64,
+    ) -> Option<RecentItemsAction> {
+        // Removed items may leave the selection out of the list
+        self.selected = self.selected.min(items.len().saturating_sub(1));
+
+        let pressed = |key| ctx.input(|i| i.key_pressed(key));
+
+        let mut action = None;
+
+        if pressed(Key::Escape) {
+            action = Some(RecentItemsAction::Close);
+        }
+
+        if !items.is_empty() {
+            if pressed(Key::ArrowDown) {
+                self.selected = (self.selected + 1).min(items.len() - 1);
+            }
+
+            if pressed(Key::ArrowUp) {
+                self.selected = self.selected.saturating_sub(1);
+            }
+
+            if pressed(Key::Enter) {
+                action = Some(activate(&items[self.selected]));
+            }
+
+            if pressed(Key::Delete) {
+                action = Some(RecentItemsAction::Forget(items[self.selected].path.clone()));
+            }
+        }
+
+        Window::new("Recent items")
+            .pivot(Align2::CENTER_CENTER)
+            .default_pos(ctx.screen_rect().center())
+            .collapsible(false)
+            .show(ctx, |ui| {
+                if items.is_empty() {
+                    ui.label("No item was opened yet");
+                }
+
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for (index, item) in items.iter().enumerate() {
+                        let mut text = RichText::new(format!(
+                            "{} ({})",
+                            item.path.display(),
+                            format_opened_at(item.opened_at, now)
+                        ));
+
+                        // Items that don't exist anymore can only be removed
+                        if !item.exists() {
+                            text = text.strikethrough().weak();
+                        }
+
+                        if index == self.selected {
+                            text = text.strong().color(Color32::LIGHT_BLUE);
+                        }
+
+                        if ui.selectable_label(index == self.selected, text).clicked() {
+                            action = Some(activate(item));
+                        }
+                    }
+                });
+
+                ui.separator();
+                ui.label(
+                    RichText::new(
+                        "Up/Down: select, Enter: open (or remove if not found), Delete: remove",
+                    )
+                    .weak(),
+                );
+            });
+
+        action
+    }
+}
+
+/// Get the action to perform when an item is chosen
+fn activate(item: &RecentItem) -> RecentItemsAction {
+    if item.exists() {
+        RecentItemsAction::Open(item.path.clone())
+    } else {
+        RecentItemsAction::Forget(item.path.clone())
+    }
+}
+
+/// Describe when an item was opened, relatively to now
+fn format_opened_at(opened_at: u64, now: u64) -> String {
+    match now.saturating_sub(opened_at) / 86_400 {
+        0 => "less than a day ago".to_owned(),
+        1 => "1 day ago".to_owned(),
+        days => format!("{days} days ago"),
+    }
+}
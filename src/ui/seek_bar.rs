@@ -0,0 +1,132 @@
+use egui::{pos2, vec2, Align2, Area, Color32, Context, FontId, Rect, Sense, Stroke, Vec2};
+
+/// Height of the area at the bottom of the window revealing the seek bar when hovered
+const REVEAL_HEIGHT: f32 = 80.0;
+
+/// Height of the seek bar itself
+pub const BAR_HEIGHT: f32 = 32.0;
+
+/// Horizontal margin of the seek bar's track
+const TRACK_MARGIN: f32 = 64.0;
+
+/// Check if the seek bar should be displayed
+/// It is revealed when the mouse gets close to the bottom of the window (or drags the bar),
+/// or while a key is held
+pub fn is_revealed(ctx: &Context) -> bool {
+    ctx.input(|i| {
+        let reveal_top = i.screen_rect().bottom() - REVEAL_HEIGHT;
+
+        let dragging = i.pointer.primary_down()
+            && i.pointer
+                .press_origin()
+                .is_some_and(|pos| pos.y >= reveal_top);
+
+        dragging
+            || i.pointer.hover_pos().is_some_and(|pos| pos.y >= reveal_top)
+            || !i.keys_down.is_empty()
+    })
+}
+
+/// Render the seek bar at the bottom of the window, if it's revealed
+///
+/// The pages which are already loaded are indicated on the bar. In right-to-left mode,
+/// the first page is on the right
+///
+/// Returns the page the user seeked to, if any
+pub fn show_seek_bar(
+    ctx: &Context,
+    current_page: usize,
+    total_pages: usize,
+    is_loaded: impl Fn(usize) -> bool,
+    right_to_left: bool,
+) -> Option<usize> {
+    if total_pages < 2 || !is_revealed(ctx) {
+        return None;
+    }
+
+    let width = ctx.screen_rect().width();
+
+    Area::new("seek_bar")
+        .anchor(Align2::LEFT_BOTTOM, Vec2::ZERO)
+        .show(ctx, |ui| {
+            let (rect, response) =
+                ui.allocate_exact_size(vec2(width, BAR_HEIGHT), Sense::click_and_drag());
+
+            let painter = ui.painter();
+
+            painter.rect_filled(rect, 0.0, Color32::from_black_alpha(200));
+
+            let track = Rect::from_min_max(
+                pos2(rect.left() + TRACK_MARGIN, rect.center().y - 2.0),
+                pos2(rect.right() - TRACK_MARGIN, rect.center().y + 2.0),
+            );
+
+            // Get the horizontal position of a page on the track
+            let page_x = |page: usize| {
+                let progress = page as f32 / (total_pages - 1) as f32;
+
+                if right_to_left {
+                    track.right() - progress * track.width()
+                } else {
+                    track.left() + progress * track.width()
+                }
+            };
+
+            painter.rect_filled(track, 2.0, Color32::DARK_GRAY);
+
+            // Contiguous loaded pages are drawn as a single segment
+            let mut page = 0;
+
+            while page < total_pages {
+                if !is_loaded(page) {
+                    page += 1;
+                    continue;
+                }
+
+                let first = page;
+
+                while page < total_pages && is_loaded(page) {
+                    page += 1;
+                }
+
+                let (start, end) = (page_x(first), page_x(page - 1));
+
+                painter.rect_filled(
+                    Rect::from_min_max(
+                        pos2(start.min(end) - 1.0, track.top()),
+                        pos2(start.max(end) + 1.0, track.bottom()),
+                    ),
+                    2.0,
+                    Color32::GRAY,
+                );
+            }
+
+            painter.circle(
+                pos2(page_x(current_page), track.center().y),
+                6.0,
+                Color32::WHITE,
+                Stroke::NONE,
+            );
+
+            painter.text(
+                pos2(rect.left() + 8.0, rect.center().y),
+                Align2::LEFT_CENTER,
+                format!("{}/{total_pages}", current_page + 1),
+                FontId::proportional(14.0),
+                Color32::WHITE,
+            );
+
+            let pos = response.interact_pointer_pos()?;
+
+            let mut progress = ((pos.x - track.left()) / track.width()).clamp(0.0, 1.0);
+
+            if right_to_left {
+                progress = 1.0 - progress;
+            }
+
+            let page = (progress * (total_pages - 1) as f32).round() as usize;
+
+            (page != current_page).then_some(page)
+        })
+        .inner
+}
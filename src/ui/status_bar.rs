@@ -0,0 +1,27 @@
+use egui::{vec2, Align2, Area, Color32, Context, Frame, Label, RichText, Vec2};
+
+use super::seek_bar::{is_revealed, BAR_HEIGHT};
+
+/// Render the status bar at the bottom of the window, if it's revealed (at the same time as the seek bar)
+/// It is displayed above the seek bar, if there is one
+pub fn show_status_bar(ctx: &Context, text: String, above_seek_bar: bool) {
+    if !is_revealed(ctx) {
+        return;
+    }
+
+    let offset = if above_seek_bar { -BAR_HEIGHT } else { 0.0 };
+    let width = ctx.screen_rect().width();
+
+    Area::new("status_bar")
+        .anchor(Align2::LEFT_BOTTOM, Vec2::new(0.0, offset))
+        .show(ctx, |ui| {
+            Frame::none()
+                .fill(Color32::from_black_alpha(200))
+                .inner_margin(vec2(8.0, 4.0))
+                .show(ui, |ui| {
+                    ui.set_width(width - 16.0);
+
+                    ui.add(Label::new(RichText::new(text).color(Color32::WHITE)).wrap(false));
+                });
+        });
+}
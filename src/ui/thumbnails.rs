@@ -0,0 +1,369 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use egui::{
+    pos2, vec2, Align, Align2, Color32, Context, FontId, Layout, Rect, ScrollArea, Sense, Stroke,
+    TextureOptions, Ui, Vec2,
+};
+
+use crate::{
+    decoders::{decode_preview, fit, DecodeOptions, DecodedImage, PagePreview},
+    gap_vec::GapVec,
+    settings::Settings,
+};
+
+use super::{
+    app::PageLoadingResult,
+    page::{DisplayablePage, Rotation},
+};
+
+/// Maximum width and height of thumbnails
+const THUMBNAIL_MAX_SIDE: f32 = 160.0;
+
+/// Width and height the thumbnails are displayed at in the thumbnail strip
+const STRIP_THUMBNAIL_SIDE: f32 = 80.0;
+
+/// Space around the thumbnails in the overview
+const CELL_MARGIN: f32 = 8.0;
+
+/// Height of the page numbers below the thumbnails in the overview
+const LABEL_HEIGHT: f32 = 20.0;
+
+/// Delay before looking for pages that weren't loaded yet, to generate their thumbnail
+const RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Thumbnails of the pages of the current item, generated in the background once needed
+pub struct Thumbnails {
+    /// Generated thumbnails
+    decoded: Arc<RwLock<GapVec<DecodedImage>>>,
+
+    /// Thumbnails uploaded to the GPU, kept so displaying them again is instant
+    textures: HashMap<usize, DisplayablePage>,
+
+    /// First page whose thumbnail is needed, so the displayed ones are generated first
+    wanted_from: Arc<AtomicUsize>,
+
+    /// Set once the thumbnails started being generated
+    started: bool,
+
+    /// Number of pages of the item
+    total_pages: usize,
+}
+
+impl Thumbnails {
+    /// Create an empty set of thumbnails, for an item with the provided number of pages
+    pub fn new(total_pages: usize) -> Self {
+        Self {
+            decoded: Arc::new(RwLock::new(GapVec::new(total_pages))),
+            textures: HashMap::new(),
+            wanted_from: Arc::new(AtomicUsize::new(0)),
+            started: false,
+            total_pages,
+        }
+    }
+
+    /// Start generating the thumbnails in the background, if it isn't already done
+    ///
+    /// Thumbnails are generated from the pages' previews if there are some, or by decoding
+    /// the loaded pages at a reduced resolution otherwise
+    pub fn start(
+        &mut self,
+        loaded_pages: &Arc<RwLock<GapVec<PageLoadingResult>>>,
+        previews: &Arc<RwLock<GapVec<DecodedImage>>>,
+        settings: &Arc<RwLock<Settings>>,
+        stop_signal: &Arc<AtomicBool>,
+        ctx: &Context,
+    ) -> Option<JoinHandle<()>> {
+        if self.started {
+            return None;
+        }
+
+        self.started = true;
+
+        let decoded = Arc::clone(&self.decoded);
+        let wanted_from = Arc::clone(&self.wanted_from);
+        let loaded_pages = Arc::clone(loaded_pages);
+        let previews = Arc::clone(previews);
+        let settings = Arc::clone(settings);
+        let stop_signal = Arc::clone(stop_signal);
+        let ctx = ctx.clone();
+        let total_pages = self.total_pages;
+
+        Some(thread::spawn(move || {
+            // Pages which can't be decoded don't have a thumbnail
+            let mut failed = vec![false; total_pages];
+
+            'passes: loop {
+                let from = wanted_from.load(Ordering::Acquire);
+                let mut waiting_for_pages = false;
+
+                for page in (from..total_pages).chain(0..from) {
+                    if stop_signal.load(Ordering::Acquire) {
+                        return;
+                    }
+
+                    // Start again from the pages the user now wants to see
+                    if wanted_from.load(Ordering::Acquire) != from {
+                        continue 'passes;
+                    }
+
+                    if failed[page] || decoded.read().unwrap().get(page).is_some() {
+                        continue;
+                    }
+
+                    let preview = previews.read().unwrap().get(page).cloned();
+
+                    let image = match preview {
+                        Some(preview) => Ok(preview),
+                        None => match loaded_pages.read().unwrap().get(page).cloned() {
+                            Some(Ok((filename, bytes))) => {
+                                let options = {
+                                    let settings = settings.read().unwrap();
+
+                                    DecodeOptions {
+                                        color_manage: settings.color_manage,
+                                        max_dimension: settings.max_decode_dimension,
+                                        raster_height: None,
+                                        threads: 1,
+                                    }
+                                };
+
+                                decode_preview(&filename, &bytes, options)
+                                    .map(PagePreview::into_image)
+                            }
+                            Some(Err(err)) => Err(err),
+                            None => {
+                                waiting_for_pages = true;
+                                continue;
+                            }
+                        },
+                    };
+
+                    match image {
+                        Ok(image) => {
+                            let thumbnail = fit(image, THUMBNAIL_MAX_SIDE as usize, 1);
+                            decoded.write().unwrap().set(page, thumbnail);
+                            ctx.request_repaint();
+                        }
+                        Err(_) => failed[page] = true,
+                    }
+                }
+
+                if !waiting_for_pages {
+                    return;
+                }
+
+                thread::sleep(RETRY_DELAY);
+            }
+        }))
+    }
+
+    /// Get the thumbnail of a page, if it was generated
+    fn get(&mut self, ctx: &Context, page: usize) -> Option<&DisplayablePage> {
+        if !self.textures.contains_key(&page) {
+            let image = self.decoded.read().unwrap().get(page).cloned()?;
+            let thumbnail = DisplayablePage::upload(
+                ctx,
+                &format!("[thumbnail-{page}]"),
+                image,
+                TextureOptions::LINEAR,
+                1,
+            );
+
+            self.textures.insert(page, thumbnail);
+        }
+
+        self.textures.get(&page)
+    }
+
+    /// Render the thumbnail of a page in the provided area
+    /// Returns `true` if it was clicked
+    pub fn show_thumbnail(
+        &mut self,
+        ui: &mut Ui,
+        page: usize,
+        size: Vec2,
+        highlighted: bool,
+        rotation: Rotation,
+    ) -> bool {
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+
+        let image_rect = Rect::from_min_size(
+            rect.min + vec2(CELL_MARGIN, CELL_MARGIN),
+            vec2(
+                size.x - CELL_MARGIN * 2.0,
+                size.y - CELL_MARGIN * 2.0 - LABEL_HEIGHT,
+            ),
+        );
+
+        match self.get(ui.ctx(), page) {
+            Some(thumbnail) => {
+                let thumbnail_size = rotation.rotate_size(thumbnail.size());
+                let scale = (image_rect.width() / thumbnail_size.x)
+                    .min(image_rect.height() / thumbnail_size.y);
+
+                let mut image_ui = ui.child_ui(image_rect, Layout::top_down(Align::Center));
+                thumbnail.show(&mut image_ui, scale, rotation);
+            }
+
+            // Placeholder until the thumbnail is generated (or if it can't be)
+            None => ui
+                .painter()
+                .rect_filled(image_rect, 4.0, Color32::from_gray(40)),
+        }
+
+        ui.painter().text(
+            pos2(
+                rect.center().x,
+                rect.bottom() - CELL_MARGIN - LABEL_HEIGHT / 2.0,
+            ),
+            Align2::CENTER_CENTER,
+            (page + 1).to_string(),
+            FontId::proportional(14.0),
+            Color32::WHITE,
+        );
+
+        if highlighted {
+            ui.painter()
+                .rect_stroke(rect.shrink(2.0), 4.0, Stroke::new(3.0, Color32::LIGHT_BLUE));
+        } else if response.hovered() {
+            ui.painter()
+                .rect_stroke(rect.shrink(2.0), 4.0, Stroke::new(1.0, Color32::GRAY));
+        }
+
+        response.clicked()
+    }
+}
+
+/// Render a scrollable grid with the thumbnails of all pages, the current one being highlighted
+/// If provided, the view is scrolled to get the given page into view
+///
+/// Returns the page whose thumbnail was clicked, if any
+pub fn show_overview(
+    ui: &mut Ui,
+    thumbnails: &mut Thumbnails,
+    current_page: usize,
+    total_pages: usize,
+    scroll_to: Option<usize>,
+    rotation: Rotation,
+) -> Option<usize> {
+    let cell_size = vec2(
+        THUMBNAIL_MAX_SIDE + CELL_MARGIN * 2.0,
+        THUMBNAIL_MAX_SIDE + CELL_MARGIN * 2.0 + LABEL_HEIGHT,
+    );
+
+    // We remove any space between the thumbnails, as they already have margins
+    ui.spacing_mut().item_spacing = Vec2::ZERO;
+
+    let columns = ((ui.available_width() / cell_size.x) as usize).max(1);
+    let rows = total_pages.div_ceil(columns);
+
+    let mut scroll_area = ScrollArea::vertical().auto_shrink([false; 2]);
+
+    if let Some(page) = scroll_to {
+        // Keep some space above the page's row, so the previous ones are visible too
+        let row = page / columns;
+        let offset = (row as f32 * cell_size.y) - (ui.available_height() - cell_size.y) / 2.0;
+
+        scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+    }
+
+    let mut clicked = None;
+
+    scroll_area.show_rows(ui, cell_size.y, rows, |ui, visible_rows| {
+        thumbnails
+            .wanted_from
+            .store(visible_rows.start * columns, Ordering::Release);
+
+        for row in visible_rows {
+            ui.horizontal(|ui| {
+                for page in row * columns..((row + 1) * columns).min(total_pages) {
+                    if thumbnails.show_thumbnail(
+                        ui,
+                        page,
+                        cell_size,
+                        page == current_page,
+                        rotation,
+                    ) {
+                        clicked = Some(page);
+                    }
+                }
+            });
+        }
+    });
+
+    clicked
+}
+
+/// Render a strip with the thumbnails of the pages around the current one, which is kept
+/// at its center
+///
+/// In right-to-left mode, the next pages are on the left
+///
+/// Returns the page whose thumbnail was clicked, if any
+pub fn show_strip(
+    ui: &mut Ui,
+    thumbnails: &mut Thumbnails,
+    current_page: usize,
+    total_pages: usize,
+    right_to_left: bool,
+    rotation: Rotation,
+) -> Option<usize> {
+    let cell_size = vec2(
+        STRIP_THUMBNAIL_SIDE + CELL_MARGIN * 2.0,
+        STRIP_THUMBNAIL_SIDE + CELL_MARGIN * 2.0 + LABEL_HEIGHT,
+    );
+
+    ui.spacing_mut().item_spacing = Vec2::ZERO;
+
+    // An odd number of cells is used, so the current page is exactly at the center
+    let cells = ((ui.available_width() / cell_size.x) as usize).max(1) | 1;
+    let center = cells / 2;
+
+    thumbnails
+        .wanted_from
+        .store(current_page.saturating_sub(center), Ordering::Release);
+
+    let mut clicked = None;
+
+    ui.horizontal(|ui| {
+        // Center the cells in the strip
+        ui.add_space((ui.available_width() - cells as f32 * cell_size.x) / 2.0);
+
+        for cell in 0..cells {
+            let offset = cell as isize - center as isize;
+            let offset = if right_to_left { -offset } else { offset };
+
+            let page = current_page
+                .checked_add_signed(offset)
+                .filter(|&page| page < total_pages);
+
+            match page {
+                Some(page) => {
+                    if thumbnails.show_thumbnail(
+                        ui,
+                        page,
+                        cell_size,
+                        page == current_page,
+                        rotation,
+                    ) {
+                        clicked = Some(page);
+                    }
+                }
+
+                None => {
+                    ui.allocate_exact_size(cell_size, Sense::hover());
+                }
+            }
+        }
+    });
+
+    clicked
+}
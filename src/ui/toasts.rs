@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+use egui::{Align2, Area, Color32, Context, Frame, Label, RichText, Vec2};
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// How long an error stays on screen, as it's usually longer to read
+const ERROR_TOAST_DURATION: Duration = Duration::from_secs(8);
+
+/// Small non-blocking notifications displayed on top of the pages
+#[derive(Default)]
+pub struct Toasts {
+    queue: Vec<Toast>,
+}
+
+struct Toast {
+    message: String,
+    error: bool,
+    expires_at: Instant,
+}
+
+impl Toasts {
+    /// Display a new notification
+    pub fn push(&mut self, message: impl Into<String>) {
+        self.queue.push(Toast {
+            message: message.into(),
+            error: false,
+            expires_at: Instant::now() + TOAST_DURATION,
+        });
+    }
+
+    /// Display an error, along with its causes
+    pub fn push_error(&mut self, err: anyhow::Error) {
+        self.queue.push(Toast {
+            message: format!("{err:#}"),
+            error: true,
+            expires_at: Instant::now() + ERROR_TOAST_DURATION,
+        });
+    }
+
+    /// Render the active notifications and forget about the expired ones
+    pub fn show(&mut self, ctx: &Context) {
+        let now = Instant::now();
+
+        self.queue.retain(|toast| toast.expires_at > now);
+
+        let Some(next_expiration) = self.queue.iter().map(|toast| toast.expires_at).min() else {
+            return;
+        };
+
+        Area::new("toasts")
+            .anchor(Align2::CENTER_BOTTOM, Vec2::new(0.0, -20.0))
+            .show(ctx, |ui| {
+                for toast in &self.queue {
+                    let color = if toast.error {
+                        Color32::LIGHT_RED
+                    } else {
+                        Color32::WHITE
+                    };
+
+                    Frame::popup(ui.style())
+                        .fill(Color32::from_black_alpha(200))
+                        .show(ui, |ui| {
+                            ui.add(
+                                Label::new(RichText::new(&toast.message).color(color)).wrap(false),
+                            );
+                        });
+                }
+            });
+
+        // Ensure the UI is refreshed when the next toast expires, even if nothing else happens
+        ctx.request_repaint_after(next_expiration - now);
+    }
+}
@@ -0,0 +1,149 @@
+use super::{bookmarks::Bookmark, page::Rotation};
+
+/// Maximum number of changes which can be undone
+const MAX_UNDO_CHANGES: usize = 50;
+
+/// Change of the state remembered for the current item
+/// Applying a change provides its inverse, so every change can be reverted the same way
+#[derive(Clone)]
+pub enum ItemChange {
+    /// Replace the item's bookmarks
+    Bookmarks(Vec<Bookmark>),
+
+    /// Rotate the item's pages
+    Rotation(Rotation),
+
+    /// Pair the pages with an offset of one (or not)
+    PairingOffset(bool),
+
+    /// Go to a page
+    Page(usize),
+}
+
+/// Changes made to the current item, which can be undone (and then redone)
+#[derive(Default)]
+pub struct UndoStack {
+    /// Inverses of the last changes, along with the description of the change they revert
+    /// The most recent change comes last
+    undo: Vec<(ItemChange, String)>,
+
+    /// Inverses of the last undone changes, in the same order
+    redo: Vec<(ItemChange, String)>,
+}
+
+impl UndoStack {
+    /// Remember the inverse of a change which was just made
+    /// Changes which were undone can't be redone anymore
+    pub fn push(&mut self, inverse: ItemChange, description: String) {
+        self.redo.clear();
+        push_capped(&mut self.undo, inverse, description);
+    }
+
+    /// Get the inverse of the last change (if any) to undo it, along with its description
+    pub fn undo(&mut self) -> Option<(ItemChange, String)> {
+        self.undo.pop()
+    }
+
+    /// Remember the inverse of a change which was just undone, so it can be redone
+    pub fn push_undone(&mut self, inverse: ItemChange, description: String) {
+        push_capped(&mut self.redo, inverse, description);
+    }
+
+    /// Get the inverse of the last undone change (if any) to redo it, along with its description
+    pub fn redo(&mut self) -> Option<(ItemChange, String)> {
+        self.redo.pop()
+    }
+
+    /// Remember the inverse of a change which was just redone, so it can be undone again
+    pub fn push_redone(&mut self, inverse: ItemChange, description: String) {
+        push_capped(&mut self.undo, inverse, description);
+    }
+
+    /// Forget all changes (e.g. when another item is opened)
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+/// Add a change to a list, forgetting the oldest one if there are too many of them
+fn push_capped(changes: &mut Vec<(ItemChange, String)>, change: ItemChange, description: String) {
+    changes.push((change, description));
+
+    if changes.len() > MAX_UNDO_CHANGES {
+        changes.remove(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(change: Option<(ItemChange, String)>) -> Option<usize> {
+        match change? {
+            (ItemChange::Page(page), _) => Some(page),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn undoes_changes_in_reverse_order() {
+        let mut stack = UndoStack::default();
+
+        stack.push(ItemChange::Page(1), "first jump".to_owned());
+        stack.push(ItemChange::Page(2), "second jump".to_owned());
+
+        assert_eq!(page(stack.undo()), Some(2));
+        assert_eq!(page(stack.undo()), Some(1));
+        assert_eq!(page(stack.undo()), None);
+    }
+
+    #[test]
+    fn redoes_undone_changes() {
+        let mut stack = UndoStack::default();
+
+        stack.push(ItemChange::Page(1), "jump".to_owned());
+        let (_, description) = stack.undo().unwrap();
+        stack.push_undone(ItemChange::Page(5), description);
+
+        assert_eq!(page(stack.redo()), Some(5));
+        assert_eq!(page(stack.redo()), None);
+
+        // Making a new change forgets the undone ones
+        stack.push_undone(ItemChange::Page(5), "jump".to_owned());
+        stack.push(ItemChange::Page(3), "other jump".to_owned());
+
+        assert_eq!(page(stack.redo()), None);
+        assert_eq!(page(stack.undo()), Some(3));
+    }
+
+    #[test]
+    fn forgets_oldest_changes() {
+        let mut stack = UndoStack::default();
+
+        for i in 0..MAX_UNDO_CHANGES + 10 {
+            stack.push(ItemChange::Page(i), "jump".to_owned());
+        }
+
+        let mut undone = vec![];
+
+        while let Some(page) = page(stack.undo()) {
+            undone.push(page);
+        }
+
+        assert_eq!(undone.len(), MAX_UNDO_CHANGES);
+        assert_eq!(undone.last(), Some(&10));
+    }
+
+    #[test]
+    fn clears_changes() {
+        let mut stack = UndoStack::default();
+
+        stack.push(ItemChange::Page(1), "jump".to_owned());
+        stack.push_undone(ItemChange::Page(2), "jump".to_owned());
+        stack.clear();
+
+        assert!(stack.undo().is_none());
+        assert!(stack.redo().is_none());
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Align, Button, Layout, RichText, Ui};
+
+/// Shortcuts displayed on the welcome screen, to get started
+const BASIC_SHORTCUTS: &[(&str, &str)] = &[
+    ("Ctrl+O", "open a file"),
+    ("Ctrl+Shift+O", "open a folder"),
+    ("Left / Right", "go to the previous / next page"),
+    ("D", "toggle double page mode"),
+    ("R", "toggle reverse reading mode (right-to-left)"),
+    ("G", "jump to a specific page or chapter"),
+    ("Escape", "exit the application"),
+];
+
+/// Action requested by the user from the welcome screen
+pub enum WelcomeAction {
+    /// Pick a file to open
+    OpenFile,
+
+    /// Pick a folder to open
+    OpenFolder,
+
+    /// Open a recent item
+    OpenRecent(PathBuf),
+
+    /// Remove an item from the recent ones
+    ForgetRecent(PathBuf),
+}
+
+/// Render the welcome screen, displayed when no item is opened
+/// Recent items that don't exist anymore are greyed out and can be removed from the list
+pub fn show_welcome(ui: &mut Ui, recent_items: &[PathBuf]) -> Option<WelcomeAction> {
+    let mut action = None;
+
+    ui.with_layout(Layout::top_down(Align::Center), |ui| {
+        ui.add_space(ui.available_height() / 6.0);
+
+        ui.heading("Reader");
+        ui.add_space(16.0);
+
+        if ui
+            .add(Button::new(RichText::new("Open file…").heading()))
+            .clicked()
+        {
+            action = Some(WelcomeAction::OpenFile);
+        }
+
+        if ui.button("Open folder…").clicked() {
+            action = Some(WelcomeAction::OpenFolder);
+        }
+
+        if !recent_items.is_empty() {
+            ui.add_space(24.0);
+            ui.label(RichText::new("Recent items").strong());
+
+            for item in recent_items {
+                if let Some(item_action) = show_recent_item(ui, item) {
+                    action = Some(item_action);
+                }
+            }
+        }
+
+        ui.add_space(24.0);
+        ui.label(RichText::new("Shortcuts").strong());
+
+        for (keys, description) in BASIC_SHORTCUTS {
+            ui.label(format!("{keys}: {description}"));
+        }
+    });
+
+    action
+}
+
+/// Render an entry of the recent items list
+fn show_recent_item(ui: &mut Ui, item: &Path) -> Option<WelcomeAction> {
+    // Remote items can't be checked without downloading them
+    if item.exists() || is_url(item) {
+        let button = Button::new(item.display().to_string()).frame(false);
+
+        return ui
+            .add(button)
+            .clicked()
+            .then(|| WelcomeAction::OpenRecent(item.to_owned()));
+    }
+
+    let text = format!("{} (not found, click to remove)", item.display());
+
+    ui.add(Button::new(RichText::new(text).weak()).frame(false))
+        .clicked()
+        .then(|| WelcomeAction::ForgetRecent(item.to_owned()))
+}
+
+/// Check if an item is a URL rather than a path
+pub fn is_url(item: &Path) -> bool {
+    item.to_str()
+        .is_some_and(|item| item.starts_with("http://") || item.starts_with("https://"))
+}
@@ -0,0 +1,138 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::settings::Settings;
+
+/// Delay between two checks of the watched item
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Delay between two checks of the stop signal while waiting
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Time the watched item must stay unchanged before a change is reported
+/// This avoids reporting each file of a batch copy as a different change
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(1500);
+
+/// Maximum depth of the subdirectories that are watched
+const MAX_DEPTH: usize = 8;
+
+/// State of an item on disk, used to detect changes made to it
+#[derive(PartialEq, Eq)]
+struct ItemState {
+    entries: Vec<(PathBuf, u64, Option<SystemTime>)>,
+}
+
+impl ItemState {
+    /// Read the state of an item
+    /// For directories, this includes the state of all the files inside it
+    fn read(path: &Path) -> Self {
+        let mut entries = vec![];
+
+        read_entries(path, 0, &mut entries);
+
+        Self { entries }
+    }
+}
+
+/// Collect the size and modification time of an item and of all the items inside it
+/// Symbolic links are not followed, so they can't cause loops
+fn read_entries(path: &Path, depth: usize, entries: &mut Vec<(PathBuf, u64, Option<SystemTime>)>) {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return;
+    };
+
+    entries.push((path.to_owned(), metadata.len(), metadata.modified().ok()));
+
+    if !metadata.is_dir() || depth == MAX_DEPTH {
+        return;
+    }
+
+    let Ok(items) = fs::read_dir(path) else {
+        return;
+    };
+
+    for item in items.flatten() {
+        read_entries(&item.path(), depth + 1, entries);
+    }
+}
+
+/// Watch an item on disk until the stop signal is raised, calling the provided function
+/// each time it changes (e.g. files were added to a directory or an archive was rewritten)
+///
+/// The item is only watched while enabled in the settings
+fn watch(path: &Path, settings: &RwLock<Settings>, stop_signal: &AtomicBool, on_change: impl Fn()) {
+    // State the item had when last reported (or when it started being watched)
+    let mut reported_state = None;
+
+    // Last state seen, and when it was first seen
+    let mut pending_state: Option<(ItemState, Instant)> = None;
+
+    loop {
+        let waiting_since = Instant::now();
+
+        // The stop signal is checked more often, so stopping doesn't take long
+        while waiting_since.elapsed() < POLL_INTERVAL {
+            if stop_signal.load(Ordering::Acquire) {
+                return;
+            }
+
+            thread::sleep(STOP_CHECK_INTERVAL);
+        }
+
+        if !settings.read().unwrap().watch_changes {
+            reported_state = None;
+            pending_state = None;
+            continue;
+        }
+
+        let state = ItemState::read(path);
+
+        let Some(reported) = &reported_state else {
+            reported_state = Some(state);
+            continue;
+        };
+
+        if state == *reported {
+            pending_state = None;
+            continue;
+        }
+
+        match &pending_state {
+            Some((pending, since)) if *pending == state => {
+                if since.elapsed() >= DEBOUNCE_DELAY {
+                    on_change();
+
+                    reported_state = pending_state.take().map(|(state, _)| state);
+                }
+            }
+
+            _ => pending_state = Some((state, Instant::now())),
+        }
+    }
+}
+
+/// Start a thread watching an item, which sets the provided flag when it changes
+pub fn spawn_watcher(
+    path: PathBuf,
+    settings: Arc<RwLock<Settings>>,
+    stop_signal: Arc<AtomicBool>,
+    changed: Arc<AtomicBool>,
+    ctx: egui::Context,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        watch(&path, &settings, &stop_signal, || {
+            changed.store(true, Ordering::Release);
+
+            // The change is handled by the UI thread
+            ctx.request_repaint();
+        })
+    })
+}